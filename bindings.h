@@ -9,6 +9,68 @@
 #include <stdint.h>
 #include <stdlib.h>
 
+/**
+ * Codes of `VmError` reported in `ActionResult.error_code`. They are part of the protocol:
+ * a code is never reused or reassigned.
+ */
+#define ERROR_NONE 0
+
+/**
+ * Failures of the VM itself and of the host input.
+ */
+#define ERROR_UNKNOWN 1
+
+#define ERROR_OUT_OF_GAS 2
+
+#define ERROR_CONTRACT_PANIC 3
+
+#define ERROR_READ_ONLY 4
+
+#define ERROR_METHOD_NOT_FOUND 5
+
+#define ERROR_FORBIDDEN_METHOD 6
+
+#define ERROR_BAD_ARGUMENTS 7
+
+#define ERROR_COMPILATION 8
+
+#define ERROR_VALIDATION 9
+
+/**
+ * Traps without a trap code, classified traps have codes of their own.
+ */
+#define ERROR_TRAP 10
+
+#define ERROR_HOST_MISUSE 11
+
+#define ERROR_BACKEND 12
+
+#define ERROR_TRAP_UNREACHABLE 13
+
+#define ERROR_TRAP_MEMORY_OUT_OF_BOUNDS 14
+
+#define ERROR_TRAP_INTEGER_OVERFLOW 15
+
+#define ERROR_TRAP_DIVISION_BY_ZERO 16
+
+#define ERROR_TRAP_STACK_OVERFLOW 17
+
+#define ERROR_TRAP_INDIRECT_CALL 18
+
+#define ERROR_TRAP_STACK_HEIGHT_EXCEEDED 19
+
+#define ERROR_BATCH_REVERTED 20
+
+#define ERROR_JOURNAL_FULL 21
+
+#define ERROR_UNSUPPORTED_VERSION 22
+
+#define ERROR_SKIPPED 23
+
+#define ERROR_HOST_FUNCTION_NOT_FOUND 24
+
+#define MAX_CODE_SIZE (1024 * 1024)
+
 #define MAX_RETURN_VALUE_SIZE (64 * 1024)
 
 #define BASE_PROMISE_COST 100000
@@ -19,6 +81,12 @@
 
 #define BASE_BYTES_TO_HEX_COST 10000
 
+/**
+ * Version of the latest gas schedule. The host passes the version of the block protocol to
+ * `execute` and `deploy`, so old blocks replay with the prices they were produced with.
+ */
+#define GAS_SCHEDULE_VERSION 9
+
 #define ACTION_FUNCTION_CALL 1
 
 #define ACTION_TRANSFER 2
@@ -29,6 +97,45 @@
 
 #define ACTION_READ_IDENTITY 5
 
+#define ACTION_BATCH 6
+
+/**
+ * Default upper bound of the memory taken by compiled modules kept in the cache.
+ */
+#define DEFAULT_MODULE_CACHE_SIZE ((256 * 1024) * 1024)
+
+/**
+ * Version of the latest VM config. Like the gas schedule, the host passes the version of the
+ * block protocol, so the limits can only be raised by a protocol upgrade.
+ */
+#define VM_CONFIG_VERSION 3
+
+#define VIOLATION_CODE_TOO_LARGE 1
+
+#define VIOLATION_INVALID_MODULE 2
+
+#define VIOLATION_FORBIDDEN_OPERATOR 3
+
+#define VIOLATION_UNKNOWN_IMPORT 4
+
+#define VIOLATION_IMPORT_SIGNATURE 5
+
+#define VIOLATION_MISSING_EXPORT 6
+
+#define VIOLATION_INVALID_EXPORT 7
+
+#define VIOLATION_MEMORY_COUNT 8
+
+#define VIOLATION_MEMORY_TOO_LARGE 9
+
+#define VIOLATION_TABLE_TOO_LARGE 10
+
+#define VIOLATION_TOO_MANY_GLOBALS 11
+
+#define VIOLATION_TOO_MANY_FUNCTIONS 12
+
+#define VIOLATION_INVALID_ABI 13
+
 /**
  * This enum gives names to the status codes returned from Go callbacks to Rust.
  *
@@ -119,6 +226,13 @@ typedef struct GoApi_vtable {
   int32_t (*keccak256)(const struct api_t*, struct U8SliceView, uint64_t*, struct UnmanagedVector*);
   int32_t (*global_state)(const struct api_t*, uint64_t*, struct UnmanagedVector*);
   int32_t (*ecrecover)(const struct api_t*, struct U8SliceView, struct U8SliceView, uint64_t*, struct UnmanagedVector*);
+  int32_t (*store_artifact)(const struct api_t*, struct U8SliceView, struct U8SliceView);
+  int32_t (*load_artifact)(const struct api_t*, struct U8SliceView, struct UnmanagedVector*);
+  int32_t (*log)(const struct api_t*, uint32_t, struct U8SliceView, struct U8SliceView, struct U8SliceView);
+  int32_t (*snapshot)(const struct api_t*, uint64_t*, uint32_t*);
+  int32_t (*revert_to_snapshot)(const struct api_t*, uint32_t, uint64_t*);
+  int32_t (*contract_code)(const struct api_t*, struct U8SliceView, uint64_t*, struct UnmanagedVector*);
+  int32_t (*balance_of)(const struct api_t*, struct U8SliceView, uint64_t*, struct UnmanagedVector*);
 } GoApi_vtable;
 
 typedef struct GoApi {
@@ -136,6 +250,24 @@ typedef struct ByteSliceView {
   uintptr_t len;
 } ByteSliceView;
 
+/**
+ * Gas the host charges for the operations discarded during estimation. The backend is never
+ * asked to perform them, so the costs have to be known upfront.
+ */
+typedef struct WriteCosts {
+  uint64_t set_storage;
+  uint64_t remove_storage;
+  uint64_t event;
+  /**
+   * Cost of burning, deducting or adding balance.
+   */
+  uint64_t transfer;
+  /**
+   * Charged on top for each byte of keys, values and event data.
+   */
+  uint64_t per_byte;
+} WriteCosts;
+
 void destroy_unmanaged_vector(struct UnmanagedVector v);
 
 struct UnmanagedVector new_unmanaged_vector(bool nil, const uint8_t *ptr, uintptr_t length);
@@ -147,6 +279,8 @@ uint8_t execute(struct GoApi api,
                 struct ByteSliceView invocation_context,
                 struct ByteSliceView contract_addr,
                 uint64_t gas_limit,
+                uint32_t gas_schedule_version,
+                uint32_t vm_config_version,
                 uint64_t *gas_used,
                 struct UnmanagedVector *action_result,
                 bool is_debug);
@@ -156,6 +290,99 @@ uint8_t deploy(struct GoApi api,
                struct ByteSliceView args,
                struct ByteSliceView contract_addr,
                uint64_t gas_limit,
+               uint32_t gas_schedule_version,
+               uint32_t vm_config_version,
                uint64_t *gas_used,
                struct UnmanagedVector *action_result,
                bool is_debug);
+
+/**
+ * Calls a method in read-only mode: any attempt to change the state fails the call.
+ */
+uint8_t query(struct GoApi api,
+              struct ByteSliceView code,
+              struct ByteSliceView method_name,
+              struct ByteSliceView args,
+              struct ByteSliceView contract_addr,
+              uint64_t gas_limit,
+              uint32_t gas_schedule_version,
+              uint32_t vm_config_version,
+              uint64_t *gas_used,
+              struct UnmanagedVector *action_result,
+              bool is_debug);
+
+/**
+ * Runs the call with `gas_cap` as the gas limit, discarding all changes. Storage, event and
+ * transfer costs are charged according to `costs`. The lowest gas limit the call succeeds with
+ * is written to `min_gas_limit`, zero if it fails.
+ */
+uint8_t estimate_execute(struct GoApi api,
+                         struct ByteSliceView code,
+                         struct ByteSliceView method_name,
+                         struct ByteSliceView args,
+                         struct ByteSliceView invocation_context,
+                         struct ByteSliceView contract_addr,
+                         uint64_t gas_cap,
+                         uint32_t gas_schedule_version,
+                         uint32_t vm_config_version,
+                         struct WriteCosts costs,
+                         uint64_t *gas_used,
+                         uint64_t *min_gas_limit,
+                         struct UnmanagedVector *action_result,
+                         bool is_debug);
+
+/**
+ * Deploy counterpart of `estimate_execute`.
+ */
+uint8_t estimate_deploy(struct GoApi api,
+                        struct ByteSliceView code,
+                        struct ByteSliceView args,
+                        struct ByteSliceView contract_addr,
+                        uint64_t gas_cap,
+                        uint32_t gas_schedule_version,
+                        uint32_t vm_config_version,
+                        struct WriteCosts costs,
+                        uint64_t *gas_used,
+                        uint64_t *min_gas_limit,
+                        struct UnmanagedVector *action_result,
+                        bool is_debug);
+
+/**
+ * Checks the code without compiling it and writes a protobuf `ValidationReport` listing all
+ * violations to `report`. Returns true iff the code is valid, false with an empty report if the
+ * gas schedule or VM config version is unknown.
+ */
+bool validate_code(struct ByteSliceView code,
+                   uint32_t gas_schedule_version,
+                   uint32_t vm_config_version,
+                   struct UnmanagedVector *report);
+
+/**
+ * Writes the ABI of the code as JSON to `abi`. Returns false if the code has no ABI or it is
+ * invalid, the reason is written to `error` in the latter case.
+ */
+bool contract_abi(struct ByteSliceView code,
+                  struct UnmanagedVector *abi,
+                  struct UnmanagedVector *error);
+
+/**
+ * Encodes the JSON arguments of the method to `args` in the typed format, see
+ * `ContractAbi::encode_args`. Returns false and writes the reason to `error` on failure.
+ */
+bool encode_json_args(struct ByteSliceView code,
+                      struct ByteSliceView method_name,
+                      struct ByteSliceView json,
+                      struct UnmanagedVector *args,
+                      struct UnmanagedVector *error);
+
+/**
+ * Decodes `ActionResult.output_data` of the method to JSON, see `ContractAbi::decode_output`.
+ * Returns false and writes the reason to `error` on failure.
+ */
+bool decode_json_output(struct ByteSliceView code,
+                        struct ByteSliceView method_name,
+                        struct ByteSliceView output,
+                        struct UnmanagedVector *json,
+                        struct UnmanagedVector *error);
+
+void module_cache_stats(uint64_t *hits, uint64_t *misses, uint64_t *entries, uint64_t *size);
@@ -0,0 +1,376 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value as Json};
+use wasmer::Module;
+use wasmer::wasmparser::{
+    ExternalKind, FuncType, ImportSectionEntryType, Parser, Payload, Type as WasmType, TypeDef,
+};
+
+use crate::args::{encode_typed_args, Argument};
+use crate::errors::VmError;
+use crate::memory::VmResult;
+
+/// Name of the custom section holding the JSON encoded [`ContractAbi`].
+pub const ABI_SECTION: &str = "idena_abi";
+
+/// Types of parameters, return values and event fields. They are the types of the typed argument
+/// format, see [`Argument`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AbiType {
+    I32,
+    I64,
+    U64,
+    Bool,
+    Bytes,
+    String,
+    Address,
+}
+
+impl AbiType {
+    /// The wasm type the value is passed as, other values are passed as pointers.
+    fn wasm_type(&self) -> WasmType {
+        match self {
+            AbiType::I64 | AbiType::U64 => WasmType::I64,
+            _ => WasmType::I32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ParamAbi {
+    pub name: String,
+    #[serde(rename = "type")]
+    pub ty: AbiType,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MethodAbi {
+    pub name: String,
+    #[serde(default)]
+    pub params: Vec<ParamAbi>,
+    #[serde(default)]
+    pub returns: Option<AbiType>,
+    /// The method accepts coins.
+    #[serde(default)]
+    pub payable: bool,
+    /// The method doesn't change the state and can be queried.
+    #[serde(default)]
+    pub view: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct EventAbi {
+    pub name: String,
+    #[serde(default)]
+    pub fields: Vec<ParamAbi>,
+}
+
+/// Description of a contract for wallets and explorers. It is optional and only checked for
+/// consistency with the exports of the module. Calls only use it to tell `i32` and `bool` return
+/// values from pointers to the output, see [`returned_type`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ContractAbi {
+    #[serde(default)]
+    pub methods: Vec<MethodAbi>,
+    #[serde(default)]
+    pub events: Vec<EventAbi>,
+}
+
+/// Reads and checks the ABI of the code, `None` if the code has no ABI section.
+pub fn read_abi(code: &[u8]) -> Result<Option<ContractAbi>, String> {
+    let mut section = None;
+    let mut types: Vec<Option<FuncType>> = vec![];
+    // Type indices of imported and defined functions.
+    let mut functions: Vec<u32> = vec![];
+    let mut exports: Vec<(String, u32)> = vec![];
+
+    for payload in Parser::new(0).parse_all(code) {
+        match payload.map_err(|err| err.to_string())? {
+            Payload::CustomSection { name: ABI_SECTION, data, .. } => {
+                if section.is_some() {
+                    return Err("duplicate section".to_string());
+                }
+                section = Some(data);
+            }
+            Payload::TypeSection(reader) => {
+                for ty in reader {
+                    types.push(match ty.map_err(|err| err.to_string())? {
+                        TypeDef::Func(func) => Some(func),
+                        _ => None,
+                    });
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    if let ImportSectionEntryType::Function(ty) = import.map_err(|err| err.to_string())?.ty {
+                        functions.push(ty);
+                    }
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                for ty in reader {
+                    functions.push(ty.map_err(|err| err.to_string())?);
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.map_err(|err| err.to_string())?;
+                    if let ExternalKind::Function = export.kind {
+                        exports.push((export.field.to_string(), export.index));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let data = match section {
+        Some(data) => data,
+        None => return Ok(None),
+    };
+    let abi: ContractAbi = serde_json::from_slice(data).map_err(|err| err.to_string())?;
+    let signature = |name: &str| exports.iter()
+        .find(|(field, _)| field == name)
+        .and_then(|(_, index)| functions.get(*index as usize))
+        .and_then(|ty| types.get(*ty as usize))
+        .and_then(|ty| ty.as_ref());
+
+    let mut names = HashSet::new();
+    for method in abi.methods.iter() {
+        if !names.insert(&method.name) {
+            return Err(format!("duplicate method {}", method.name));
+        }
+        check_params(&method.params).map_err(|msg| format!("method {}: {}", method.name, msg))?;
+        let func = signature(&method.name).ok_or_else(|| format!("method {} is not exported", method.name))?;
+        if method.payable && method.view {
+            return Err(format!("method {} can't be both payable and view", method.name));
+        }
+        if method.params.len() > func.params.len() {
+            return Err(format!("method {} has {} parameters, the export takes {}", method.name, method.params.len(), func.params.len()));
+        }
+        for (param, ty) in method.params.iter().zip(func.params.iter()) {
+            if param.ty.wasm_type() != *ty {
+                return Err(format!("parameter {} of method {} doesn't match the export", param.name, method.name));
+            }
+        }
+        if let Some(ty) = method.returns {
+            if *func.returns != [ty.wasm_type()] {
+                return Err(format!("return value of method {} doesn't match the export", method.name));
+            }
+        }
+    }
+    let mut names = HashSet::new();
+    for event in abi.events.iter() {
+        if !names.insert(&event.name) {
+            return Err(format!("duplicate event {}", event.name));
+        }
+        check_params(&event.fields).map_err(|msg| format!("event {}: {}", event.name, msg))?;
+    }
+    Ok(Some(abi))
+}
+
+/// Returns the declared return type of the method, `None` if the module has no ABI or the ABI
+/// doesn't describe it. The ABI was checked when the contract was deployed.
+pub fn returned_type(module: &Module, method: &str) -> Option<AbiType> {
+    let data = module.custom_sections(ABI_SECTION).next()?;
+    let abi: ContractAbi = serde_json::from_slice(&data).ok()?;
+    abi.methods.into_iter().find(|m| m.name == method)?.returns
+}
+
+fn check_params(params: &[ParamAbi]) -> Result<(), String> {
+    let mut names = HashSet::new();
+    for param in params {
+        if !names.insert(&param.name) {
+            return Err(format!("duplicate name {}", param.name));
+        }
+    }
+    Ok(())
+}
+
+impl ContractAbi {
+    pub fn method(&self, name: &str) -> VmResult<&MethodAbi> {
+        self.methods.iter()
+            .find(|method| method.name == name)
+            .ok_or_else(|| VmError::method_not_found(name))
+    }
+
+    /// Encodes JSON arguments of the method in the typed format. The arguments are either an array
+    /// or an object keyed by the parameter names, missing and null arguments are nil.
+    pub fn encode_args(&self, method: &str, json: &Json) -> VmResult<Vec<u8>> {
+        let method = self.method(method)?;
+        let values: Vec<&Json> = match json {
+            Json::Array(values) => {
+                if values.len() > method.params.len() {
+                    return Err(VmError::bad_args("too many arguments"));
+                }
+                method.params.iter().enumerate().map(|(i, _)| values.get(i).unwrap_or(&Json::Null)).collect()
+            }
+            Json::Object(values) => {
+                if let Some(name) = values.keys().find(|name| method.params.iter().all(|param| param.name != **name)) {
+                    return Err(VmError::bad_args(format!("unknown argument {}", name)));
+                }
+                method.params.iter().map(|param| values.get(&param.name).unwrap_or(&Json::Null)).collect()
+            }
+            Json::Null => method.params.iter().map(|_| &Json::Null).collect(),
+            _ => return Err(VmError::bad_args("arguments must be an array or an object")),
+        };
+        let args = method.params.iter().zip(values)
+            .map(|(param, value)| from_json(param.ty, value).map_err(|msg| VmError::bad_args(format!("argument {}: {}", param.name, msg))))
+            .collect::<VmResult<Vec<_>>>()?;
+        Ok(encode_typed_args(&args))
+    }
+
+    /// Decodes `ActionResult.output_data` of the method to JSON, null if the method returns nothing.
+    pub fn decode_output(&self, method: &str, output: &[u8]) -> VmResult<Json> {
+        let method = self.method(method)?;
+        match method.returns {
+            Some(ty) if !output.is_empty() => to_json(ty, output).map_err(VmError::custom),
+            _ => Ok(Json::Null),
+        }
+    }
+
+    /// Decodes the arguments of an event to a JSON object keyed by the field names, `None` if the
+    /// event is not described.
+    pub fn decode_event(&self, name: &str, args: &[Vec<u8>]) -> Option<VmResult<Json>> {
+        let event = self.events.iter().find(|event| event.name == name)?;
+        let mut fields = Map::new();
+        for (field, data) in event.fields.iter().zip(args.iter()) {
+            match to_json(field.ty, data) {
+                Ok(value) => fields.insert(field.name.clone(), value),
+                Err(msg) => return Some(Err(VmError::custom(format!("field {}: {}", field.name, msg)))),
+            };
+        }
+        Some(Ok(Json::Object(fields)))
+    }
+}
+
+/// Numbers are JSON numbers, u64 and i64 can be decimal strings as well. Bytes and addresses are
+/// 0x-prefixed hex strings.
+fn from_json(ty: AbiType, value: &Json) -> Result<Argument, String> {
+    if value.is_null() {
+        return Ok(Argument::Nil);
+    }
+    let arg = match ty {
+        AbiType::I32 => Argument::I32(value.as_i64().and_then(|v| i32::try_from(v).ok()).ok_or("expected i32")?),
+        AbiType::I64 => Argument::I64(value.as_i64().or_else(|| value.as_str().and_then(|s| s.parse().ok())).ok_or("expected i64")?),
+        AbiType::U64 => Argument::U64(value.as_u64().or_else(|| value.as_str().and_then(|s| s.parse().ok())).ok_or("expected u64")?),
+        AbiType::Bool => Argument::Bool(value.as_bool().ok_or("expected bool")?),
+        AbiType::Bytes => Argument::Bytes(from_hex(value)?),
+        AbiType::String => Argument::String(value.as_str().ok_or("expected string")?.to_string()),
+        AbiType::Address => {
+            let address = from_hex(value)?;
+            if address.len() != 20 {
+                return Err("address must be 20 bytes".to_string());
+            }
+            Argument::Address(address)
+        }
+    };
+    Ok(arg)
+}
+
+fn from_hex(value: &Json) -> Result<Vec<u8>, String> {
+    let s = value.as_str().ok_or("expected hex string")?;
+    hex::decode(s.strip_prefix("0x").unwrap_or(s)).map_err(|err| err.to_string())
+}
+
+/// Decodes a value encoded as in the typed argument format.
+fn to_json(ty: AbiType, data: &[u8]) -> Result<Json, String> {
+    let size_err = || format!("unexpected size {} of {:?}", data.len(), ty);
+    let value = match ty {
+        AbiType::I32 => Json::from(i32::from_le_bytes(data.try_into().map_err(|_| size_err())?)),
+        // Strings keep 64-bit numbers exact in JavaScript.
+        AbiType::I64 => Json::from(i64::from_le_bytes(data.try_into().map_err(|_| size_err())?).to_string()),
+        AbiType::U64 => Json::from(u64::from_le_bytes(data.try_into().map_err(|_| size_err())?).to_string()),
+        AbiType::Bool => match data {
+            [0] => Json::from(false),
+            [1] => Json::from(true),
+            _ => return Err(size_err()),
+        },
+        AbiType::Bytes | AbiType::Address => Json::from(format!("0x{}", hex::encode(data))),
+        AbiType::String => Json::from(String::from_utf8(data.to_vec()).map_err(|err| err.to_string())?),
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::args::convert_args;
+
+    use super::*;
+
+    fn code(abi: &str) -> Vec<u8> {
+        let abi: String = abi.bytes().map(|b| format!("\\{:02x}", b)).collect();
+        wat::parse_str(format!(r#"(module
+          (memory (export "memory") 1)
+          (func (export "transfer") (param i32 i64))
+          (func (export "balance") (param i32) (result i64) (i64.extend_i32_u (local.get 0)))
+          (@custom "idena_abi" "{}"))"#, abi)).unwrap()
+    }
+
+    const ABI: &str = r#"{
+      "methods": [
+        {"name": "transfer", "params": [{"name": "to", "type": "address"}, {"name": "amount", "type": "u64"}], "payable": true},
+        {"name": "balance", "params": [{"name": "owner", "type": "address"}], "returns": "u64", "view": true}
+      ],
+      "events": [{"name": "Transfer", "fields": [{"name": "to", "type": "address"}, {"name": "amount", "type": "u64"}]}]
+    }"#;
+
+    #[test]
+    fn test_read_abi() {
+        let abi = read_abi(&code(ABI)).unwrap().unwrap();
+        assert_eq!(abi.methods.len(), 2);
+        assert_eq!(abi.method("balance").unwrap().returns, Some(AbiType::U64));
+        assert_eq!(read_abi(&wat::parse_str("(module)").unwrap()).unwrap(), None);
+
+        let invalid = [
+            r#"{"methods": [{"name": "missing"}]}"#,
+            r#"{"methods": [{"name": "transfer", "params": [{"name": "to", "type": "u64"}]}]}"#,
+            r#"{"methods": [{"name": "transfer", "payable": true, "view": true}]}"#,
+            r#"{"methods": [{"name": "transfer", "returns": "i32"}]}"#,
+            r#"{"methods": [{"name": "balance", "returns": "bool"}]}"#,
+            r#"{"methods": [{"name": "transfer"}, {"name": "transfer"}]}"#,
+            r#"{"methods": [{"name": "transfer", "params": [{"name": "a", "type": "float"}]}]}"#,
+            r#"{"events": [{"name": "E", "fields": [{"name": "a", "type": "i32"}, {"name": "a", "type": "i32"}]}]}"#,
+            "not json",
+        ];
+        for abi in invalid.iter() {
+            assert!(read_abi(&code(abi)).is_err(), "{}", abi);
+        }
+    }
+
+    #[test]
+    fn test_encode_args() {
+        let abi = read_abi(&code(ABI)).unwrap().unwrap();
+        let to = format!("0x{}", "ab".repeat(20));
+        let expected = vec![Argument::Address(vec![0xab; 20]), Argument::U64(u64::MAX)];
+
+        let json = serde_json::json!([to, u64::MAX.to_string()]);
+        assert_eq!(convert_args(&abi.encode_args("transfer", &json).unwrap()).unwrap(), expected);
+        let json = serde_json::json!({"amount": u64::MAX, "to": to});
+        assert_eq!(convert_args(&abi.encode_args("transfer", &json).unwrap()).unwrap(), expected);
+        let json = serde_json::json!({"to": to});
+        assert_eq!(convert_args(&abi.encode_args("transfer", &json).unwrap()).unwrap()[1], Argument::Nil);
+
+        assert!(abi.encode_args("transfer", &serde_json::json!(["0x01"])).is_err());
+        assert!(abi.encode_args("transfer", &serde_json::json!({"from": to})).is_err());
+        assert!(abi.encode_args("missing", &Json::Null).is_err());
+    }
+
+    #[test]
+    fn test_decode_output() {
+        let abi = read_abi(&code(ABI)).unwrap().unwrap();
+        assert_eq!(abi.decode_output("balance", &42u64.to_le_bytes()).unwrap(), serde_json::json!("42"));
+        assert_eq!(abi.decode_output("transfer", b"ignored").unwrap(), Json::Null);
+        assert!(abi.decode_output("balance", &[1]).is_err());
+
+        let event = abi.decode_event("Transfer", &[vec![1; 20], 5u64.to_le_bytes().to_vec()]).unwrap().unwrap();
+        assert_eq!(event, serde_json::json!({"to": format!("0x{}", "01".repeat(20)), "amount": "5"}));
+        assert!(abi.decode_event("Unknown", &[]).is_none());
+    }
+}
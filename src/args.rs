@@ -3,26 +3,131 @@ use protobuf::Message;
 use crate::errors::VmError;
 use crate::memory::VmResult;
 use crate::proto;
+use crate::proto::models::ArgType;
 
 const ARGS_PROTOBUF_FORMAT: u8 = 0x1;
 const ARGS_PLAIN_FORMAT: u8 = 0x0;
+/// The protobuf format in which every argument declares its type.
+const ARGS_TYPED_FORMAT: u8 = 0x2;
 
+const ADDRESS_SIZE: usize = 20;
 
-pub fn convert_args(args: &[u8]) -> VmResult<protobuf::RepeatedField<proto::models::ProtoArgs_Argument>> {
-    let mut result: protobuf::RepeatedField<proto::models::ProtoArgs_Argument>;
+/// A decoded argument. Numbers and bools are passed to the contract as they are, everything else
+/// is written to the contract memory and passed as a pointer.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Argument {
+    Nil,
+    /// Arguments of the untyped formats.
+    Raw(Vec<u8>),
+    I32(i32),
+    I64(i64),
+    U64(u64),
+    Bool(bool),
+    Bytes(Vec<u8>),
+    String(String),
+    Address(Vec<u8>),
+}
+
+pub fn convert_args(args: &[u8]) -> VmResult<Vec<Argument>> {
+    let mut result = vec![];
 
     match args[0] {
         ARGS_PROTOBUF_FORMAT => {
-            result = proto::models::ProtoArgs::parse_from_bytes(&args[1..])
-                .or(Err(VmError::custom("failed to parse arguments")))?.args;
+            let parsed = proto::models::ProtoArgs::parse_from_bytes(&args[1..])
+                .or(Err(VmError::bad_args("failed to parse arguments")))?.args;
+            for arg in parsed.into_iter() {
+                if arg.get_is_nil() {
+                    result.push(Argument::Nil);
+                } else {
+                    result.push(Argument::Raw(arg.value));
+                }
+            }
         }
         ARGS_PLAIN_FORMAT => {
-            let mut arg = proto::models::ProtoArgs_Argument::new();
-            arg.set_value(args[1..].to_vec());
-            result = protobuf::RepeatedField::<proto::models::ProtoArgs_Argument>::new();
-            result.push(arg);
+            result.push(Argument::Raw(args[1..].to_vec()));
         }
-        _ => return Err(VmError::custom("unknown format of args"))
+        ARGS_TYPED_FORMAT => {
+            let parsed = proto::models::ProtoArgs::parse_from_bytes(&args[1..])
+                .or(Err(VmError::bad_args("failed to parse arguments")))?.args;
+            for (i, arg) in parsed.into_iter().enumerate() {
+                result.push(typed_arg(i, arg)?);
+            }
+        }
+        _ => return Err(VmError::bad_args("unknown format of args"))
     }
     Ok(result)
 }
+
+fn typed_arg(index: usize, arg: proto::models::ProtoArgs_Argument) -> VmResult<Argument> {
+    let invalid = |reason: &str| VmError::bad_args(format!("argument {}: {}", index, reason));
+    if arg.get_is_nil() {
+        return Ok(Argument::Nil);
+    }
+    let value = arg.value;
+    let res = match arg.field_type {
+        ArgType::ARG_UNTYPED => return Err(invalid("type is required")),
+        ArgType::ARG_I32 => Argument::I32(i32::from_le_bytes(value.as_slice().try_into().map_err(|_| invalid("i32 must be 4 bytes"))?)),
+        ArgType::ARG_I64 => Argument::I64(i64::from_le_bytes(value.as_slice().try_into().map_err(|_| invalid("i64 must be 8 bytes"))?)),
+        ArgType::ARG_U64 => Argument::U64(u64::from_le_bytes(value.as_slice().try_into().map_err(|_| invalid("u64 must be 8 bytes"))?)),
+        ArgType::ARG_BOOL => match value.as_slice() {
+            [0] => Argument::Bool(false),
+            [1] => Argument::Bool(true),
+            _ => return Err(invalid("bool must be a single 0 or 1 byte")),
+        },
+        ArgType::ARG_BYTES => Argument::Bytes(value),
+        ArgType::ARG_STRING => Argument::String(String::from_utf8(value).map_err(|_| invalid("string must be utf-8"))?),
+        ArgType::ARG_ADDRESS if value.len() != ADDRESS_SIZE => return Err(invalid("address must be 20 bytes")),
+        ArgType::ARG_ADDRESS => Argument::Address(value),
+    };
+    Ok(res)
+}
+
+/// Encodes arguments in the protobuf format, `None` stands for a nil argument.
+pub fn encode_args(args: &[Option<Vec<u8>>]) -> Vec<u8> {
+    let mut proto_args = proto::models::ProtoArgs::new();
+    for value in args {
+        let mut arg = proto::models::ProtoArgs_Argument::new();
+        match value {
+            Some(v) => arg.set_value(v.clone()),
+            None => arg.set_is_nil(true),
+        }
+        proto_args.args.push(arg);
+    }
+    let mut result = vec![ARGS_PROTOBUF_FORMAT];
+    result.extend(proto_args.write_to_bytes().unwrap_or_default());
+    result
+}
+
+/// Encodes a single argument in the plain format.
+pub fn encode_plain_arg(value: &[u8]) -> Vec<u8> {
+    let mut result = vec![ARGS_PLAIN_FORMAT];
+    result.extend_from_slice(value);
+    result
+}
+
+/// Encodes arguments in the typed format, see [`Argument`].
+pub fn encode_typed_args(args: &[Argument]) -> Vec<u8> {
+    let mut proto_args = proto::models::ProtoArgs::new();
+    for value in args {
+        let mut arg = proto::models::ProtoArgs_Argument::new();
+        let (field_type, bytes) = match value {
+            Argument::Nil => {
+                arg.set_is_nil(true);
+                (ArgType::ARG_UNTYPED, vec![])
+            }
+            Argument::Raw(v) | Argument::Bytes(v) => (ArgType::ARG_BYTES, v.clone()),
+            Argument::I32(v) => (ArgType::ARG_I32, v.to_le_bytes().to_vec()),
+            Argument::I64(v) => (ArgType::ARG_I64, v.to_le_bytes().to_vec()),
+            Argument::U64(v) => (ArgType::ARG_U64, v.to_le_bytes().to_vec()),
+            Argument::Bool(v) => (ArgType::ARG_BOOL, vec![*v as u8]),
+            Argument::String(v) => (ArgType::ARG_STRING, v.as_bytes().to_vec()),
+            Argument::Address(v) => (ArgType::ARG_ADDRESS, v.clone()),
+        };
+        arg.set_field_type(field_type);
+        arg.set_value(bytes);
+        proto_args.args.push(arg);
+    }
+    let mut result = vec![ARGS_TYPED_FORMAT];
+    result.extend(proto_args.write_to_bytes().unwrap_or_default());
+    result
+}
@@ -0,0 +1,145 @@
+use std::any::type_name;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+
+use sha2::{Digest, Sha256};
+use wasmer::{Module, Store};
+
+use crate::cache::CacheKey;
+use crate::errors::VmError;
+use crate::memory::VmResult;
+use crate::runner::Compiler;
+
+const ARTIFACT_MAGIC: &[u8; 8] = b"IDNAWASM";
+/// Changed whenever the compiled code changes without the engine, e.g. by a new middleware.
+const ARTIFACT_FORMAT_VERSION: u32 = 2;
+
+const HASH_SIZE: usize = 32;
+const HEADER_SIZE: usize = ARTIFACT_MAGIC.len() + 2 * HASH_SIZE;
+
+/// Revision of the wasmer fork the crate is built with. The build can't tell which revision it
+/// links, so it has to be updated together with the wasmer dependencies in Cargo.toml: artifacts
+/// of another engine would be loaded otherwise.
+const WASMER_REVISION: &str = "de83b23";
+
+/// Identifies the engine producing the machine code.
+fn engine_id() -> String {
+    format!(
+        "wasmer-{}-{}/{}/universal-{}",
+        wasmer::VERSION,
+        WASMER_REVISION,
+        type_name::<Compiler>(),
+        wasmer_engine_universal::VERSION,
+    )
+}
+
+/// Hash of everything the compiled code depends on. Artifacts with another fingerprint are stale.
+fn fingerprint(key: &CacheKey) -> [u8; HASH_SIZE] {
+    let mut hasher = Sha256::new();
+    hasher.update(ARTIFACT_FORMAT_VERSION.to_le_bytes());
+    hasher.update(engine_id().as_bytes());
+    hasher.update(std::env::consts::ARCH.as_bytes());
+    hasher.update(std::env::consts::OS.as_bytes());
+    hasher.update(key.code_hash);
+    hasher.update(key.gas_schedule_version.to_le_bytes());
+    hasher.update(format!("{:?}", key.gatekeeper).as_bytes());
+    hasher.update(key.vm_config_version.to_le_bytes());
+    hasher.finalize().into()
+}
+
+/// Serializes a compiled module into an artifact which can be persisted by the host.
+///
+/// Layout: magic | fingerprint | sha256(payload) | payload
+pub fn serialize_module(module: &Module, key: &CacheKey) -> VmResult<Vec<u8>> {
+    let payload = module
+        .serialize()
+        .map_err(|err| VmError::custom(format!("failed to serialize module: {}", err)))?;
+    let mut data = Vec::with_capacity(HEADER_SIZE + payload.len());
+    data.extend_from_slice(ARTIFACT_MAGIC);
+    data.extend_from_slice(&fingerprint(key));
+    data.extend_from_slice(&Sha256::digest(&payload));
+    data.extend_from_slice(&payload);
+    Ok(data)
+}
+
+/// Restores a module from an artifact. Stale and corrupted artifacts are rejected with an error,
+/// the caller is expected to compile the code instead.
+pub fn deserialize_module(store: &Store, key: &CacheKey, data: &[u8]) -> VmResult<Module> {
+    if data.len() < HEADER_SIZE || &data[..ARTIFACT_MAGIC.len()] != ARTIFACT_MAGIC {
+        return Err(VmError::custom("unknown artifact format"));
+    }
+    let (fp, rest) = data[ARTIFACT_MAGIC.len()..].split_at(HASH_SIZE);
+    if fp != fingerprint(key) {
+        return Err(VmError::custom("stale artifact"));
+    }
+    let (checksum, payload) = rest.split_at(HASH_SIZE);
+    if checksum != Sha256::digest(payload).as_slice() {
+        return Err(VmError::custom("corrupted artifact"));
+    }
+    // Wasmer expects the serialized metadata to be 16-byte aligned, which is not the case for
+    // a payload following the header.
+    let mut aligned = vec![0u128; (payload.len() + 15) / 16];
+    let aligned_bytes = unsafe {
+        std::slice::from_raw_parts_mut(aligned.as_mut_ptr() as *mut u8, payload.len())
+    };
+    aligned_bytes.copy_from_slice(payload);
+    // The payload is checked above, unwinding is only a last line of defence.
+    catch_unwind(AssertUnwindSafe(|| unsafe { Module::deserialize(store, aligned_bytes) }))
+        .map_err(|_| VmError::custom("failed to deserialize module: panicked"))?
+        .map_err(|err| VmError::custom(format!("failed to deserialize module: {}", err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmer::{Cranelift, Universal};
+
+    use crate::gatekeeper::Gatekeeper;
+
+    use super::*;
+
+    const WAT: &str = r#"(module (func (export "sum") (param i32 i32) (result i32) local.get 0 local.get 1 i32.add))"#;
+
+    fn key(gas_schedule_version: u32) -> CacheKey {
+        CacheKey::new(WAT.as_bytes(), gas_schedule_version, Gatekeeper::default().config(), 1)
+    }
+
+    fn artifact() -> Vec<u8> {
+        let store = Store::new(&Universal::new(Cranelift::default()).engine());
+        let module = Module::new(&store, wat::parse_str(WAT).unwrap()).unwrap();
+        serialize_module(&module, &key(1)).unwrap()
+    }
+
+    fn headless_store() -> Store {
+        Store::new(&Universal::headless().engine())
+    }
+
+    #[test]
+    fn round_trip() {
+        let module = deserialize_module(&headless_store(), &key(1), &artifact()).unwrap();
+        assert!(module.info().exports.contains_key("sum"));
+    }
+
+    #[test]
+    fn engine_id_names_the_compiler() {
+        let id = engine_id();
+        assert!(id.starts_with(&format!("wasmer-{}-{}/", wasmer::VERSION, WASMER_REVISION)), "{}", id);
+        assert!(id.contains(type_name::<Compiler>()), "{}", id);
+    }
+
+    #[test]
+    fn stale_artifact_is_rejected() {
+        let err = deserialize_module(&headless_store(), &key(2), &artifact()).unwrap_err();
+        assert!(err.to_string().contains("stale artifact"));
+    }
+
+    #[test]
+    fn corrupted_artifact_is_rejected() {
+        let mut data = artifact();
+        let last = data.len() - 1;
+        data[last] ^= 0xff;
+        let err = deserialize_module(&headless_store(), &key(1), &data).unwrap_err();
+        assert!(err.to_string().contains("corrupted artifact"));
+
+        let err = deserialize_module(&headless_store(), &key(1), &data[..10]).unwrap_err();
+        assert!(err.to_string().contains("unknown artifact format"));
+    }
+}
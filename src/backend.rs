@@ -2,7 +2,7 @@ use std::fmt::{Debug, Display, Formatter};
 
 use thiserror::Error;
 
-use crate::types::{ActionResult, Address, IDNA};
+use crate::types::{ActionResult, Address, LogRecord, IDNA};
 
 #[derive(Error, Debug)]
 pub enum BackendError {
@@ -33,7 +33,7 @@ impl BackendError {
 
 pub type BackendResult<T> = (core::result::Result<T, BackendError>, u64);
 
-pub trait Backend: Copy + Clone + Send {
+pub trait Backend: Clone + Send {
     fn set_remaining_gas(&self, gas_limit: u64) -> BackendResult<()>;
     fn set_storage(&self, key: Vec<u8>, value: Vec<u8>) -> BackendResult<()>;
     fn get_storage(&self, key: Vec<u8>) -> BackendResult<Option<Vec<u8>>>;
@@ -53,7 +53,7 @@ pub trait Backend: Copy + Clone + Send {
     fn original_caller(&self) -> BackendResult<Vec<u8>>;
     //fn commit(&self) -> BackendResult<()>;
     fn deduct_balance(&self, amount: IDNA) -> BackendResult<()>;
-    fn add_balance(&self, to: Address, amount: IDNA) ->u64;
+    fn add_balance(&self, to: Address, amount: IDNA) -> BackendResult<()>;
     fn own_addr(&self) -> BackendResult<Address>;
     fn contract_addr(&self, code:  &[u8], args: &[u8], nonce: &[u8]) -> BackendResult<Address>;
     fn deploy(&self, code : &[u8], args: &[u8], nonce: &[u8], amount: &[u8], gas_limit: u64) -> BackendResult<ActionResult>;
@@ -66,142 +66,15 @@ pub trait Backend: Copy + Clone + Send {
     fn keccak256(&self, data: &[u8]) -> BackendResult<Vec<u8>>;
     fn global_state(&self) -> BackendResult<Vec<u8>>;
     fn ecrecover(&self, data : &[u8], sig : &[u8]) -> BackendResult<Vec<u8>>;
-}
-
-pub struct MockBackend {}
-
-impl Copy for MockBackend {}
-
-impl Clone for MockBackend {
-    fn clone(&self) -> Self {
-        MockBackend {}
-    }
-}
-
-#[allow(unused_variables)]
-impl Backend for MockBackend {
-    fn set_remaining_gas(&self, gas_limit: u64) -> BackendResult<()> {
-        println!("called set_remaining_gas");
-        (Ok(()), 0)
-    }
-
-    fn set_storage(&self, key: Vec<u8>, value: Vec<u8>) -> BackendResult<()> {
-        println!("called set_storage");
-        (Ok(()), 0)
-    }
-
-    fn get_storage(&self, key: Vec<u8>) -> BackendResult<Option<Vec<u8>>> {
-        todo!()
-    }
-
-    fn remove_storage(&self, key: Vec<u8>) -> BackendResult<()> {
-        todo!()
-    }
-
-    fn block_timestamp(&self) -> BackendResult<i64> {
-        todo!()
-    }
-
-    fn block_number(&self) -> BackendResult<u64> {
-        todo!()
-    }
-
-    fn min_fee_per_gas(&self) -> BackendResult<IDNA> {
-        todo!()
-    }
-
-    fn balance(&self) -> BackendResult<IDNA> {
-        todo!()
-    }
-
-    fn block_seed(&self) -> BackendResult<Vec<u8>> {
-        todo!()
-    }
-
-    fn network_size(&self) -> BackendResult<u64> {
-        todo!()
-    }
-
-    fn burn(&self, amount: IDNA) -> BackendResult<()> {
-        todo!()
-    }
-
-    fn read_contract_data(&self, addr: Address, key: Vec<u8>) -> BackendResult<Option<Vec<u8>>> {
-        todo!()
-    }
-
-    fn epoch(&self) -> BackendResult<u16> {
-        todo!()
-    }
-
-    fn identity(&self, addr: Address) -> BackendResult<Option<Vec<u8>>> {
-        todo!()
-    }
-
-    fn call(&self, addr: Address, method: &[u8], args: &[u8], amount: &[u8], gas_limit: u64, invocation_ctx: &[u8]) -> BackendResult<ActionResult> {
-        todo!()
-    }
-
-    fn caller(&self) -> BackendResult<Vec<u8>> {
-        (Ok(vec![1, 2, 3]), 10)
-    }
-    fn original_caller(&self) -> BackendResult<Vec<u8>> {
-        todo!()
-    }
-
-    fn deduct_balance(&self, amount: IDNA) -> BackendResult<()> {
-        todo!()
-    }
-
-    fn add_balance(&self, to: Address, amount: IDNA) -> u64 {
-        todo!()
-    }
-
-    fn own_addr(&self) -> BackendResult<Address> {
-        todo!()
-    }
-
-    fn contract_addr(&self, code: &[u8], args: &[u8], nonce: &[u8]) -> BackendResult<Address> {
-        todo!()
-    }
-
-    fn deploy(&self, code: &[u8], args: &[u8], nonce: &[u8], amount: &[u8], gas_limit: u64) -> BackendResult<ActionResult> {
-        todo!()
-    }
-
-    fn contract_addr_by_hash(&self, hash: &[u8], args: &[u8], nonce: &[u8]) -> BackendResult<Address> {
-        todo!()
-    }
-
-    fn own_code(&self) -> BackendResult<Vec<u8>> {
-        todo!()
-    }
-
-    fn code_hash(&self) -> BackendResult<Vec<u8>> {
-        todo!()
-    }
-
-    fn event(&self, event_name: &[u8], args: &[u8]) -> BackendResult<()> {
-        todo!()
-    }
-
-    fn pay_amount(&self) -> BackendResult<IDNA> {
-        todo!()
-    }
-
-    fn block_header(&self, height: u64) -> BackendResult<Option<Vec<u8>>> {
-        todo!()
-    }
-
-    fn keccak256(&self, data: &[u8]) -> BackendResult<Vec<u8>> {
-        todo!()
-    }
-
-    fn global_state(&self) -> BackendResult<Vec<u8>> {
-        todo!()
-    }
-
-    fn ecrecover(&self, data: &[u8], sig: &[u8]) -> BackendResult<Vec<u8>> {
-        todo!()
-    }
+    fn store_artifact(&self, code_hash : &[u8], artifact : &[u8]) -> BackendResult<()>;
+    fn load_artifact(&self, code_hash : &[u8]) -> BackendResult<Option<Vec<u8>>>;
+    /// Receives the log of executions run without the debug mode.
+    fn log(&self, record: &LogRecord) -> BackendResult<()>;
+    /// Remembers the state changed by promises, returns the id of the snapshot.
+    fn snapshot(&self) -> BackendResult<u32>;
+    /// Discards the changes made since the snapshot was taken, along with the later snapshots.
+    fn revert_to_snapshot(&self, id: u32) -> BackendResult<()>;
+    /// Code of a deployed contract, `None` if there is no contract at the address.
+    fn contract_code(&self, addr: Address) -> BackendResult<Option<Vec<u8>>>;
+    fn balance_of(&self, addr: Address) -> BackendResult<IDNA>;
 }
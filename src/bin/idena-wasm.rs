@@ -0,0 +1,361 @@
+//! Deploys or calls a contract locally against an in-memory backend and prints the outcome.
+
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::process::exit;
+
+use serde::Deserialize;
+
+use idena_wasm::args::{encode_args, encode_plain_arg};
+use idena_wasm::mock::{keccak256, MockBackend, MockContract, MockState};
+use idena_wasm::types::{idna_to_u128, u128_to_idna, Action, ActionResult, Address, ReadShardedDataAction};
+
+const USAGE: &str = "Usage: idena-wasm <CODE> [OPTIONS]
+
+Deploys a contract (.wasm or .wat) or calls one of its methods against an in-memory state.
+
+Options:
+  --state <FILE>           JSON state fixture
+  --method <NAME>          method to call, the contract is deployed if omitted
+  --arg <VALUE>            protobuf-encoded argument, can be repeated
+  --nil-arg                protobuf-encoded nil argument, can be repeated
+  --plain-arg <VALUE>      a single argument in the plain format
+  --raw-args <HEX>         arguments already encoded, including the format byte
+  --amount <N>             amount paid to the contract
+  --gas-limit <N>          gas limit, 10000000 by default
+  --caller <HEX>           caller address
+  --contract <HEX>         address of the called contract, derived from the code by default
+  --nonce <HEX>            nonce used to derive the address of a deployed contract
+  --gas-schedule <N>       gas schedule version, the latest one by default
+  --vm-config <N>          VM config version, the latest one by default
+  --debug                  run in debug mode
+
+Values are 0x-prefixed hex or UTF-8 strings.";
+
+const DEFAULT_GAS_LIMIT: u64 = 10_000_000;
+const DEFAULT_CALLER: &str = "0x0000000000000000000000000000000000000001";
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct Fixture {
+    block_number: u64,
+    block_timestamp: i64,
+    block_seed: String,
+    epoch: u16,
+    network_size: u64,
+    min_fee_per_gas: String,
+    global_state: String,
+    balances: HashMap<String, String>,
+    identities: HashMap<String, String>,
+    block_headers: HashMap<u64, String>,
+    contracts: HashMap<String, FixtureContract>,
+}
+
+#[derive(Deserialize, Default)]
+#[serde(default, deny_unknown_fields)]
+struct FixtureContract {
+    /// Hex encoded code.
+    code: Option<String>,
+    /// Path to the code, relative to the fixture.
+    code_file: Option<PathBuf>,
+    storage: BTreeMap<String, String>,
+}
+
+struct Options {
+    code: PathBuf,
+    state: Option<PathBuf>,
+    method: Option<String>,
+    args: Vec<Option<Vec<u8>>>,
+    plain_arg: Option<Vec<u8>>,
+    raw_args: Option<Vec<u8>>,
+    amount: u128,
+    gas_limit: u64,
+    caller: Address,
+    contract: Option<Address>,
+    nonce: Vec<u8>,
+    gas_schedule_version: Option<u32>,
+    vm_config_version: Option<u32>,
+    debug: bool,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item=String>) -> Result<Self, String> {
+        let mut options = Options {
+            code: PathBuf::new(),
+            state: None,
+            method: None,
+            args: vec![],
+            plain_arg: None,
+            raw_args: None,
+            amount: 0,
+            gas_limit: DEFAULT_GAS_LIMIT,
+            caller: parse_hex(DEFAULT_CALLER)?,
+            contract: None,
+            nonce: vec![],
+            gas_schedule_version: None,
+            vm_config_version: None,
+            debug: false,
+        };
+        let mut code = None;
+        while let Some(arg) = args.next() {
+            let mut value = || args.next().ok_or_else(|| format!("missing value of {}", arg));
+            match arg.as_str() {
+                "--state" => options.state = Some(value()?.into()),
+                "--method" => options.method = Some(value()?),
+                "--arg" => options.args.push(Some(parse_value(&value()?)?)),
+                "--nil-arg" => options.args.push(None),
+                "--plain-arg" => options.plain_arg = Some(parse_value(&value()?)?),
+                "--raw-args" => options.raw_args = Some(parse_hex(&value()?)?),
+                "--amount" => options.amount = parse_number(&value()?)?,
+                "--gas-limit" => options.gas_limit = parse_number(&value()?)?,
+                "--caller" => options.caller = parse_hex(&value()?)?,
+                "--contract" => options.contract = Some(parse_hex(&value()?)?),
+                "--nonce" => options.nonce = parse_hex(&value()?)?,
+                "--gas-schedule" => options.gas_schedule_version = Some(parse_number(&value()?)?),
+                "--vm-config" => options.vm_config_version = Some(parse_number(&value()?)?),
+                "--debug" => options.debug = true,
+                "-h" | "--help" => return Err(USAGE.to_string()),
+                _ if arg.starts_with("--") => return Err(format!("unknown option {}\n\n{}", arg, USAGE)),
+                _ if code.is_none() => code = Some(PathBuf::from(arg)),
+                _ => return Err(format!("unexpected argument {}\n\n{}", arg, USAGE)),
+            }
+        }
+        options.code = code.ok_or_else(|| USAGE.to_string())?;
+        Ok(options)
+    }
+
+    fn encoded_args(&self) -> Result<Vec<u8>, String> {
+        match (&self.raw_args, &self.plain_arg) {
+            (Some(_), Some(_)) => Err("--raw-args and --plain-arg are mutually exclusive".to_string()),
+            (Some(_), _) | (_, Some(_)) if !self.args.is_empty() => Err("--arg cannot be combined with --raw-args or --plain-arg".to_string()),
+            (Some(raw), None) => Ok(raw.clone()),
+            (None, Some(plain)) => Ok(encode_plain_arg(plain)),
+            (None, None) => Ok(encode_args(&self.args)),
+        }
+    }
+}
+
+fn parse_hex(value: &str) -> Result<Vec<u8>, String> {
+    hex::decode(value.trim_start_matches("0x")).map_err(|err| format!("invalid hex {}: {}", value, err))
+}
+
+fn parse_value(value: &str) -> Result<Vec<u8>, String> {
+    if value.starts_with("0x") {
+        parse_hex(value)
+    } else {
+        Ok(value.as_bytes().to_vec())
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid number {}", value))
+}
+
+fn read_code(path: &Path) -> Result<Vec<u8>, String> {
+    let data = std::fs::read(path).map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+    // Binary modules are returned as is.
+    wat::parse_bytes(&data)
+        .map(|code| code.into_owned())
+        .map_err(|err| format!("failed to parse {}: {}", path.display(), err))
+}
+
+fn load_state(path: &Path) -> Result<MockState, String> {
+    let data = std::fs::read(path).map_err(|err| format!("failed to read {}: {}", path.display(), err))?;
+    let fixture: Fixture = serde_json::from_slice(&data).map_err(|err| format!("invalid state fixture: {}", err))?;
+    let amount = |value: &str| -> Result<u128, String> {
+        if value.is_empty() { Ok(0) } else { parse_number(value) }
+    };
+
+    let mut state = MockState {
+        block_number: fixture.block_number,
+        block_timestamp: fixture.block_timestamp,
+        block_seed: parse_hex(&fixture.block_seed)?,
+        epoch: fixture.epoch,
+        network_size: fixture.network_size,
+        min_fee_per_gas: u128_to_idna(amount(&fixture.min_fee_per_gas)?),
+        global_state: parse_hex(&fixture.global_state)?,
+        ..MockState::default()
+    };
+    for (addr, balance) in fixture.balances.iter() {
+        state.balances.insert(parse_hex(addr)?, amount(balance)?);
+    }
+    for (addr, identity) in fixture.identities.iter() {
+        state.identities.insert(parse_hex(addr)?, parse_hex(identity)?);
+    }
+    for (height, header) in fixture.block_headers.iter() {
+        state.block_headers.insert(*height, parse_hex(header)?);
+    }
+    for (addr, contract) in fixture.contracts.iter() {
+        let code = match (&contract.code, &contract.code_file) {
+            (Some(code), _) => parse_hex(code)?,
+            (None, Some(file)) => read_code(&path.parent().unwrap_or(Path::new("")).join(file))?,
+            (None, None) => vec![],
+        };
+        let mut storage = BTreeMap::new();
+        for (key, value) in contract.storage.iter() {
+            storage.insert(parse_hex(key)?, parse_hex(value)?);
+        }
+        state.contracts.insert(parse_hex(addr)?, MockContract { code, storage });
+    }
+    Ok(state)
+}
+
+fn describe(action: &Action) -> String {
+    match action {
+        Action::None => "none".to_string(),
+        Action::DeployContract(_) => "deploy".to_string(),
+        Action::FunctionCall(call) => format!("call {}", call.method_name),
+        Action::Transfer(transfer) => format!("transfer {}", idna_to_u128(&transfer.amount).unwrap_or_default()),
+        Action::ReadShardedData(ReadShardedDataAction::ReadContractData(read)) => format!("read 0x{}", hex::encode(&read.key)),
+        Action::ReadShardedData(ReadShardedDataAction::GetIdentity(read)) => format!("identity 0x{}", hex::encode(&read.addr)),
+        Action::Batch(batch) => format!("batch of {}", batch.actions.len()),
+    }
+}
+
+fn print_result(res: &ActionResult, depth: usize) {
+    let indent = "  ".repeat(depth);
+    let status = if res.success { "ok".to_string() } else { format!("failed ({}): {}", res.error_code, res.error) };
+    println!("{}{} at 0x{}: {}", indent, describe(&res.input_action), hex::encode(&res.contract), status);
+    println!("{}  gas used: {}, remaining gas: {}", indent, res.gas_used, res.remaining_gas);
+    if !res.output_data.is_empty() {
+        println!("{}  output: 0x{}", indent, hex::encode(&res.output_data));
+    }
+    for record in res.logs.iter() {
+        println!("{}  {:?}: {}", indent, record.level, record.message);
+    }
+    for sub_res in res.sub_action_results.iter() {
+        print_result(sub_res, depth + 1);
+    }
+}
+
+fn print_storage_diff(before: &HashMap<Address, MockContract>, after: &HashMap<Address, MockContract>) {
+    let empty = BTreeMap::new();
+    let addrs: BTreeSet<&Address> = before.keys().chain(after.keys()).collect();
+    for addr in addrs {
+        let old = before.get(addr).map(|c| &c.storage).unwrap_or(&empty);
+        let new = after.get(addr).map(|c| &c.storage).unwrap_or(&empty);
+        if old == new {
+            continue;
+        }
+        println!("storage of 0x{}:", hex::encode(addr));
+        let keys: BTreeSet<&Vec<u8>> = old.keys().chain(new.keys()).collect();
+        for key in keys {
+            match (old.get(key), new.get(key)) {
+                (None, Some(value)) => println!("  + 0x{} = 0x{}", hex::encode(key), hex::encode(value)),
+                (Some(_), None) => println!("  - 0x{}", hex::encode(key)),
+                (Some(prev), Some(value)) if prev != value => {
+                    println!("  ~ 0x{} = 0x{} (was 0x{})", hex::encode(key), hex::encode(value), hex::encode(prev))
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+fn run(options: Options) -> Result<bool, String> {
+    let code = read_code(&options.code)?;
+    let args = options.encoded_args()?;
+    let mut state = match &options.state {
+        Some(path) => load_state(path)?,
+        None => MockState::default(),
+    };
+    state.is_debug = options.debug;
+    state.gas_schedule_version = options.gas_schedule_version;
+    state.vm_config_version = options.vm_config_version;
+
+    let contract = options.contract.clone().unwrap_or_else(|| keccak256(&code)[12..].to_vec());
+    if options.method.is_some() {
+        state.contracts.entry(contract.clone()).or_default().code = code.clone();
+    }
+    let before = state.contracts.clone();
+
+    let backend = MockBackend::new(state, vec![], vec![]);
+    let amount = u128_to_idna(options.amount);
+    let res = match &options.method {
+        Some(method) => backend.run_call(options.caller, contract, method, &args, amount, options.gas_limit),
+        None => backend.run_deploy(options.caller, &code, &args, &options.nonce, amount, options.gas_limit),
+    }.map_err(|err| err.to_string())?;
+
+    print_result(&res, 0);
+    println!("gas used: {}", res.gas_used);
+    backend.with_state(|state| {
+        for event in state.events.iter() {
+            println!("event {} from 0x{}: 0x{}", String::from_utf8_lossy(&event.name), hex::encode(&event.contract), hex::encode(&event.args));
+        }
+        for record in state.logs.iter() {
+            println!("{:?} from 0x{} {}: {}", record.level, hex::encode(&record.contract), record.method, record.message);
+        }
+        print_storage_diff(&before, &state.contracts);
+    });
+    Ok(res.success)
+}
+
+fn main() {
+    let result = Options::parse(std::env::args().skip(1)).and_then(run);
+    match result {
+        Ok(true) => {}
+        Ok(false) => exit(1),
+        Err(err) => {
+            eprintln!("{}", err);
+            exit(2);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use idena_wasm::args::{convert_args, Argument};
+
+    use super::*;
+
+    /// Stores nothing, the deploy only has to receive its argument.
+    const DEPLOY_WAT: &str = r#"
+      (module
+        (memory (export "memory") 1)
+        (global $heap (mut i32) (i32.const 1024))
+        (func (export "allocate") (param $size i32) (result i32)
+          (local $ptr i32)
+          (local.set $ptr (global.get $heap))
+          (i32.store (local.get $ptr) (i32.add (local.get $ptr) (i32.const 12)))
+          (i32.store offset=4 (local.get $ptr) (local.get $size))
+          (global.set $heap (i32.add (global.get $heap) (i32.add (local.get $size) (i32.const 12))))
+          (local.get $ptr))
+        (func (export "deploy") (param i32)))
+    "#;
+
+    fn parse(args: &[&str]) -> Result<Options, String> {
+        Options::parse(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn plain_arg_is_encoded() {
+        let options = parse(&["code.wat", "--plain-arg", "0x0102"]).unwrap();
+        let encoded = options.encoded_args().unwrap();
+        assert_eq!(encoded, encode_plain_arg(&[1, 2]));
+        assert_eq!(convert_args(&encoded).unwrap(), vec![Argument::Raw(vec![1, 2])]);
+    }
+
+    #[test]
+    fn protobuf_args_are_encoded() {
+        let options = parse(&["code.wat", "--arg", "abc", "--nil-arg"]).unwrap();
+        let encoded = options.encoded_args().unwrap();
+        assert_eq!(convert_args(&encoded).unwrap(), vec![Argument::Raw(b"abc".to_vec()), Argument::Nil]);
+    }
+
+    #[test]
+    fn conflicting_args_are_rejected() {
+        let options = parse(&["code.wat", "--plain-arg", "a", "--raw-args", "0x00"]).unwrap();
+        assert!(options.encoded_args().is_err());
+        assert!(parse(&["--debug"]).is_err());
+    }
+
+    #[test]
+    fn deploy_runs() {
+        let path = std::env::temp_dir().join(format!("idena-wasm-cli-{}.wat", std::process::id()));
+        std::fs::write(&path, DEPLOY_WAT).unwrap();
+        let options = parse(&[path.to_str().unwrap(), "--plain-arg", "0x01"]).unwrap();
+        let res = run(options);
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(res, Ok(true));
+    }
+}
@@ -0,0 +1,194 @@
+use std::sync::Mutex;
+
+use indexmap::IndexMap;
+use sha2::{Digest, Sha256};
+use wasmer::Module;
+
+use crate::gatekeeper::GatekeeperConfig;
+
+/// Default upper bound of the memory taken by compiled modules kept in the cache.
+pub const DEFAULT_MODULE_CACHE_SIZE: usize = 256 * 1024 * 1024;
+
+static MODULE_CACHE: Mutex<Option<ModuleCache>> = Mutex::new(None);
+
+/// Identifies a compiled module. Everything that changes the compilation result for the same
+/// code must be a part of the key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CacheKey {
+    pub code_hash: [u8; 32],
+    pub gas_schedule_version: u32,
+    pub gatekeeper: GatekeeperConfig,
+    pub vm_config_version: u32,
+}
+
+impl CacheKey {
+    pub fn new(code: &[u8], gas_schedule_version: u32, gatekeeper: GatekeeperConfig, vm_config_version: u32) -> Self {
+        CacheKey {
+            code_hash: Sha256::digest(code).into(),
+            gas_schedule_version,
+            gatekeeper,
+            vm_config_version,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+    pub size: usize,
+}
+
+struct CacheEntry {
+    module: Module,
+    size: usize,
+}
+
+/// A size-bounded LRU cache of compiled modules.
+///
+/// Entries are kept in the order of use, the least recently used one is at the front and is the
+/// first to be evicted once the total size of the modules exceeds `max_size`.
+pub struct ModuleCache {
+    entries: IndexMap<CacheKey, CacheEntry>,
+    max_size: usize,
+    size: usize,
+    hits: u64,
+    misses: u64,
+}
+
+impl ModuleCache {
+    pub fn new(max_size: usize) -> Self {
+        ModuleCache {
+            entries: IndexMap::new(),
+            max_size,
+            size: 0,
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &CacheKey) -> Option<Module> {
+        match self.entries.shift_remove(key) {
+            Some(entry) => {
+                self.hits += 1;
+                let module = entry.module.clone();
+                self.entries.insert(*key, entry);
+                Some(module)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
+    }
+
+    pub fn insert(&mut self, key: CacheKey, module: Module) {
+        let size = loupe::size_of_val(&module);
+        if size > self.max_size {
+            return;
+        }
+        if let Some(old) = self.entries.insert(key, CacheEntry { module, size }) {
+            self.size -= old.size;
+        }
+        self.size += size;
+        while self.size > self.max_size {
+            match self.entries.shift_remove_index(0) {
+                Some((_, evicted)) => self.size -= evicted.size,
+                None => break,
+            }
+        }
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits,
+            misses: self.misses,
+            entries: self.entries.len(),
+            size: self.size,
+        }
+    }
+}
+
+/// Runs the callback with the process-wide module cache, which is shared by all FFI calls.
+pub fn with_module_cache<C, R>(callback: C) -> R
+    where
+        C: FnOnce(&mut ModuleCache) -> R,
+{
+    let mut guard = MODULE_CACHE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+    let cache = guard.get_or_insert_with(|| ModuleCache::new(DEFAULT_MODULE_CACHE_SIZE));
+    callback(cache)
+}
+
+/// Returns a cached module or compiles it. The cache is not locked while compiling,
+/// so a slow compilation does not block calls of other contracts.
+pub fn get_or_compile<C, E>(key: CacheKey, compile: C) -> Result<Module, E>
+    where
+        C: FnOnce() -> Result<Module, E>,
+{
+    if let Some(module) = with_module_cache(|cache| cache.get(&key)) {
+        return Ok(module);
+    }
+    let module = compile()?;
+    with_module_cache(|cache| cache.insert(key, module.clone()));
+    Ok(module)
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmer::{Cranelift, Store, Universal};
+
+    use crate::gatekeeper::Gatekeeper;
+
+    use super::*;
+
+    fn compile(wat: &str) -> Module {
+        let store = Store::new(&Universal::new(Cranelift::default()).engine());
+        Module::new(&store, wat::parse_str(wat).unwrap()).unwrap()
+    }
+
+    fn key(code: &[u8]) -> CacheKey {
+        CacheKey::new(code, 1, Gatekeeper::default().config(), 1)
+    }
+
+    #[test]
+    fn key_depends_on_config() {
+        let code = b"code";
+        assert_eq!(key(code), key(code));
+        assert_ne!(key(code), key(b"other code"));
+        assert_ne!(key(code), CacheKey::new(code, 2, Gatekeeper::default().config(), 1));
+        assert_ne!(key(code), CacheKey::new(code, 1, Gatekeeper::default().config(), 2));
+    }
+
+    #[test]
+    fn get_counts_hits_and_misses() {
+        let mut cache = ModuleCache::new(DEFAULT_MODULE_CACHE_SIZE);
+        assert!(cache.get(&key(b"a")).is_none());
+        cache.insert(key(b"a"), compile("(module)"));
+        assert!(cache.get(&key(b"a")).is_some());
+        assert!(cache.get(&key(b"a")).is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[test]
+    fn evicts_least_recently_used() {
+        let module = compile("(module (func (export \"f\")))");
+        let size = loupe::size_of_val(&module);
+        let mut cache = ModuleCache::new(size * 2);
+        cache.insert(key(b"a"), module.clone());
+        cache.insert(key(b"b"), module.clone());
+        // "a" becomes the most recently used one, so "b" is evicted.
+        assert!(cache.get(&key(b"a")).is_some());
+        cache.insert(key(b"c"), module.clone());
+
+        assert!(cache.get(&key(b"b")).is_none());
+        assert!(cache.get(&key(b"a")).is_some());
+        assert!(cache.get(&key(b"c")).is_some());
+        assert_eq!(cache.stats().entries, 2);
+        assert_eq!(cache.stats().size, size * 2);
+    }
+}
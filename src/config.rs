@@ -0,0 +1,79 @@
+use wasmer::Pages;
+
+/// Version of the latest VM config. Like the gas schedule, the host passes the version of the
+/// block protocol, so the limits can only be raised by a protocol upgrade.
+#[allow(dead_code)] // only read by the host through bindings.h
+pub const VM_CONFIG_VERSION: u32 = 3;
+
+/// Limits of the modules a contract can be made of. They are enforced when the code is validated
+/// and compiled, and by the tunables when the module is instantiated.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct VmConfig {
+    pub version: u32,
+    pub max_memory_pages: u32,
+    pub max_table_elements: u32,
+    /// Counts imported globals as well.
+    pub max_globals: u32,
+    /// Counts imported functions as well.
+    pub max_functions: u32,
+    /// Limit of the stack height counted by `StackLimiter`, in values. The stack height isn't
+    /// limited if it is 0.
+    pub max_stack_height: u32,
+    /// Whether `SignatureAlignment` keeps the imported functions of an instance aligned.
+    pub align_signatures: bool,
+}
+
+const VM_CONFIG_V1: VmConfig = VmConfig {
+    version: 1,
+    max_memory_pages: 100,
+    max_table_elements: 10_000,
+    max_globals: 1_000,
+    max_functions: 10_000,
+    max_stack_height: 0,
+    align_signatures: false,
+};
+
+/// Limits the stack height.
+const VM_CONFIG_V2: VmConfig = VmConfig {
+    version: 2,
+    max_stack_height: 32 * 1024,
+    ..VM_CONFIG_V1
+};
+
+/// Keeps the imported functions aligned.
+const VM_CONFIG_V3: VmConfig = VmConfig {
+    version: 3,
+    align_signatures: true,
+    ..VM_CONFIG_V2
+};
+
+const VM_CONFIGS: [VmConfig; 3] = [VM_CONFIG_V1, VM_CONFIG_V2, VM_CONFIG_V3];
+
+/// Returns the VM config of the given version, `None` if the version is unknown.
+pub fn vm_config(version: u32) -> Option<&'static VmConfig> {
+    VM_CONFIGS.iter().find(|config| config.version == version)
+}
+
+impl VmConfig {
+    pub fn memory_limit(&self) -> Pages {
+        Pages(self.max_memory_pages)
+    }
+
+    /// Whether the stack height is limited, see `max_stack_height`.
+    pub fn has_stack_limit(&self) -> bool {
+        self.max_stack_height > 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configs_are_versioned() {
+        assert_eq!(vm_config(1), Some(&VM_CONFIG_V1));
+        assert_eq!(vm_config(VM_CONFIG_VERSION).unwrap().version, VM_CONFIG_VERSION);
+        assert_eq!(vm_config(0), None);
+        assert_eq!(vm_config(VM_CONFIG_VERSION + 1), None);
+    }
+}
@@ -9,12 +9,348 @@ pub const BASE_CALL_COST: u64 = 100000;
 
 pub const BASE_BYTES_TO_HEX_COST :u64 = 10000;
 
+/// Version of the latest gas schedule. The host passes the version of the block protocol to
+/// `execute` and `deploy`, so old blocks replay with the prices they were produced with.
+#[allow(dead_code)] // only read by the host through bindings.h
+pub const GAS_SCHEDULE_VERSION: u32 = 9;
 
-pub fn cost_function(operator: &Operator) -> u64 {
-    1
-    /*match operator {
-        Operator::LocalGet { .. } | Operator::I32Const { .. } => 1,
-        Operator::I32Add { .. } => 1,
-        _ => 1,
-    }*/
-}
\ No newline at end of file
+/// Operator prices consulted by the metering middleware. Metering is compiled into the module,
+/// so a schedule must never change once released: new prices go to a new version, and blocks
+/// are replayed with the schedule of their protocol version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GasSchedule {
+    pub version: u32,
+    /// Constants, locals, globals, `drop` and `select`.
+    pub const_local: u64,
+    pub arithmetic: u64,
+    /// Division, remainder and square root.
+    pub division: u64,
+    /// Memory loads, stores and `memory.size`.
+    pub memory: u64,
+    /// Blocks, branches and `return`.
+    pub control_flow: u64,
+    pub call: u64,
+    pub call_indirect: u64,
+    pub memory_grow: u64,
+    /// Charged by `memory.grow` per requested page on top of `memory_grow`.
+    pub memory_grow_page: u64,
+    /// Charged by the host per byte copied between the guest memory and the host.
+    pub memory_copy: u64,
+    /// Native hash functions, charged `hash` plus `hash_byte` per hashed byte. The functions are
+    /// only provided when `hash` is set.
+    pub hash: u64,
+    pub hash_byte: u64,
+    /// Signature checks, charged per signature. The functions are only provided when the costs
+    /// are set.
+    pub verify_secp256k1: u64,
+    pub verify_ed25519: u64,
+    /// Storage accesses journaled by the VM, charged `storage_*` plus `storage_byte` per byte of
+    /// the key and value. Storage is journaled only when `storage_write` is set, otherwise every
+    /// access is passed to the backend, which charges it.
+    pub storage_read: u64,
+    pub storage_write: u64,
+    pub storage_remove: u64,
+    pub storage_byte: u64,
+    /// Events buffered by the VM, charged `event` plus `event_byte` per byte of the topic and data
+    /// when they are emitted. Events are buffered only when `event` is set, otherwise they are
+    /// passed to the backend right away, which charges them.
+    pub event: u64,
+    pub event_byte: u64,
+    /// Whether `i64`, `bool` and `i32` return values are encoded like typed arguments. Otherwise
+    /// every return value is taken as a pointer to the region of the output.
+    pub typed_returns: bool,
+    /// Whether host calls may use up all the gas left, like the metering does. Otherwise a host
+    /// call fails once no gas is left after it.
+    pub exact_host_gas: bool,
+}
+
+/// The original flat schedule, every operator costs 1.
+const GAS_SCHEDULE_V1: GasSchedule = GasSchedule {
+    version: 1,
+    const_local: 1,
+    arithmetic: 1,
+    division: 1,
+    memory: 1,
+    control_flow: 1,
+    call: 1,
+    call_indirect: 1,
+    memory_grow: 1,
+    memory_grow_page: 0,
+    memory_copy: 0,
+    hash: 0,
+    hash_byte: 0,
+    verify_secp256k1: 0,
+    verify_ed25519: 0,
+    storage_read: 0,
+    storage_write: 0,
+    storage_remove: 0,
+    storage_byte: 0,
+    event: 0,
+    event_byte: 0,
+    typed_returns: false,
+    exact_host_gas: false,
+};
+
+const GAS_SCHEDULE_V2: GasSchedule = GasSchedule {
+    version: 2,
+    const_local: 1,
+    arithmetic: 2,
+    division: 8,
+    memory: 4,
+    control_flow: 2,
+    call: 20,
+    call_indirect: 40,
+    memory_grow: 1000,
+    memory_grow_page: 0,
+    memory_copy: 0,
+    hash: 0,
+    hash_byte: 0,
+    verify_secp256k1: 0,
+    verify_ed25519: 0,
+    storage_read: 0,
+    storage_write: 0,
+    storage_remove: 0,
+    storage_byte: 0,
+    event: 0,
+    event_byte: 0,
+    typed_returns: false,
+    exact_host_gas: false,
+};
+
+/// Charges host copies, which were free before.
+const GAS_SCHEDULE_V3: GasSchedule = GasSchedule {
+    version: 3,
+    memory_copy: 1,
+    ..GAS_SCHEDULE_V2
+};
+
+/// Charges grown memory by the page, a page was as cheap as any other `memory.grow` before.
+const GAS_SCHEDULE_V4: GasSchedule = GasSchedule {
+    version: 4,
+    memory_grow_page: 10_000,
+    ..GAS_SCHEDULE_V3
+};
+
+/// Hashes are computed by the VM instead of the backend.
+const GAS_SCHEDULE_V5: GasSchedule = GasSchedule {
+    version: 5,
+    hash: 200,
+    hash_byte: 2,
+    ..GAS_SCHEDULE_V4
+};
+
+/// Adds signature verification.
+const GAS_SCHEDULE_V6: GasSchedule = GasSchedule {
+    version: 6,
+    verify_secp256k1: 20_000,
+    verify_ed25519: 15_000,
+    ..GAS_SCHEDULE_V5
+};
+
+/// Journals storage writes until the call succeeds, the VM charges them when they are made. As
+/// the storage is charged by the host calls, they may use up all the gas like the metering.
+const GAS_SCHEDULE_V7: GasSchedule = GasSchedule {
+    version: 7,
+    storage_read: 100,
+    storage_write: 200,
+    storage_remove: 100,
+    storage_byte: 1,
+    exact_host_gas: true,
+    ..GAS_SCHEDULE_V6
+};
+
+/// Encodes primitive return values instead of reading them as pointers.
+const GAS_SCHEDULE_V8: GasSchedule = GasSchedule {
+    version: 8,
+    typed_returns: true,
+    ..GAS_SCHEDULE_V7
+};
+
+/// Buffers events until the call succeeds, the VM charges them when they are emitted.
+const GAS_SCHEDULE_V9: GasSchedule = GasSchedule {
+    version: 9,
+    event: 100,
+    event_byte: 1,
+    ..GAS_SCHEDULE_V8
+};
+
+const GAS_SCHEDULES: [GasSchedule; 9] = [
+    GAS_SCHEDULE_V1,
+    GAS_SCHEDULE_V2,
+    GAS_SCHEDULE_V3,
+    GAS_SCHEDULE_V4,
+    GAS_SCHEDULE_V5,
+    GAS_SCHEDULE_V6,
+    GAS_SCHEDULE_V7,
+    GAS_SCHEDULE_V8,
+    GAS_SCHEDULE_V9,
+];
+
+/// Returns the gas schedule of the given version, `None` if the version is unknown.
+pub fn gas_schedule(version: u32) -> Option<&'static GasSchedule> {
+    GAS_SCHEDULES.iter().find(|schedule| schedule.version == version)
+}
+
+impl GasSchedule {
+    /// Cost of copying `len` bytes between the guest memory and the host.
+    pub fn copy_cost(&self, len: usize) -> u64 {
+        self.memory_copy.saturating_mul(len as u64)
+    }
+
+    /// Whether hashes are computed natively, see `hash`.
+    pub fn has_native_hashes(&self) -> bool {
+        self.hash > 0
+    }
+
+    /// Whether signatures can be verified, see `verify_secp256k1`.
+    pub fn has_signature_verification(&self) -> bool {
+        self.verify_secp256k1 > 0 && self.verify_ed25519 > 0
+    }
+
+    /// Whether storage writes are journaled, see `storage_write`.
+    pub fn has_storage_journal(&self) -> bool {
+        self.storage_write > 0
+    }
+
+    /// Cost of accessing `len` bytes of storage with the given base cost.
+    pub fn storage_cost(&self, base: u64, len: usize) -> u64 {
+        base.saturating_add(self.storage_byte.saturating_mul(len as u64))
+    }
+
+    /// Whether events are buffered, see `event`.
+    pub fn has_event_buffer(&self) -> bool {
+        self.event > 0
+    }
+
+    /// Cost of emitting an event of `len` bytes.
+    pub fn event_cost(&self, len: usize) -> u64 {
+        self.event.saturating_add(self.event_byte.saturating_mul(len as u64))
+    }
+
+    /// Cost of hashing `len` bytes natively.
+    pub fn hash_cost(&self, len: usize) -> u64 {
+        self.hash.saturating_add(self.hash_byte.saturating_mul(len as u64))
+    }
+
+    pub fn cost(&self, operator: &Operator) -> u64 {
+        match operator {
+            Operator::I32Const { .. }
+            | Operator::I64Const { .. }
+            | Operator::F32Const { .. }
+            | Operator::F64Const { .. }
+            | Operator::LocalGet { .. }
+            | Operator::LocalSet { .. }
+            | Operator::LocalTee { .. }
+            | Operator::GlobalGet { .. }
+            | Operator::GlobalSet { .. }
+            | Operator::Drop
+            | Operator::Select
+            | Operator::TypedSelect { .. } => self.const_local,
+
+            Operator::I32DivS
+            | Operator::I32DivU
+            | Operator::I32RemS
+            | Operator::I32RemU
+            | Operator::I64DivS
+            | Operator::I64DivU
+            | Operator::I64RemS
+            | Operator::I64RemU
+            | Operator::F32Div
+            | Operator::F64Div
+            | Operator::F32Sqrt
+            | Operator::F64Sqrt => self.division,
+
+            Operator::I32Load { .. }
+            | Operator::I64Load { .. }
+            | Operator::F32Load { .. }
+            | Operator::F64Load { .. }
+            | Operator::I32Load8S { .. }
+            | Operator::I32Load8U { .. }
+            | Operator::I32Load16S { .. }
+            | Operator::I32Load16U { .. }
+            | Operator::I64Load8S { .. }
+            | Operator::I64Load8U { .. }
+            | Operator::I64Load16S { .. }
+            | Operator::I64Load16U { .. }
+            | Operator::I64Load32S { .. }
+            | Operator::I64Load32U { .. }
+            | Operator::I32Store { .. }
+            | Operator::I64Store { .. }
+            | Operator::F32Store { .. }
+            | Operator::F64Store { .. }
+            | Operator::I32Store8 { .. }
+            | Operator::I32Store16 { .. }
+            | Operator::I64Store8 { .. }
+            | Operator::I64Store16 { .. }
+            | Operator::I64Store32 { .. }
+            | Operator::MemorySize { .. } => self.memory,
+
+            Operator::Unreachable
+            | Operator::Nop
+            | Operator::Block { .. }
+            | Operator::Loop { .. }
+            | Operator::If { .. }
+            | Operator::Else
+            | Operator::End
+            | Operator::Br { .. }
+            | Operator::BrIf { .. }
+            | Operator::BrTable { .. }
+            | Operator::Return => self.control_flow,
+
+            Operator::Call { .. } => self.call,
+            Operator::CallIndirect { .. } => self.call_indirect,
+            Operator::MemoryGrow { .. } => self.memory_grow,
+
+            _ => self.arithmetic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use wasmer::wasmparser::{MemoryImmediate, Type, TypeOrFuncType};
+
+    use super::*;
+
+    #[test]
+    fn schedules_are_versioned() {
+        assert_eq!(gas_schedule(1), Some(&GAS_SCHEDULE_V1));
+        assert_eq!(gas_schedule(GAS_SCHEDULE_VERSION).unwrap().version, GAS_SCHEDULE_VERSION);
+        assert_eq!(gas_schedule(0), None);
+        assert_eq!(gas_schedule(GAS_SCHEDULE_VERSION + 1), None);
+    }
+
+    #[test]
+    fn operators_are_priced_by_class() {
+        let memarg = MemoryImmediate { align: 0, offset: 0, memory: 0 };
+        let operators = [
+            Operator::LocalGet { local_index: 0 },
+            Operator::I64Add,
+            Operator::I64DivU,
+            Operator::I32Load { memarg },
+            Operator::Block { ty: TypeOrFuncType::Type(Type::EmptyBlockType) },
+            Operator::Call { function_index: 0 },
+            Operator::CallIndirect { index: 0, table_index: 0 },
+            Operator::MemoryGrow { mem: 0, mem_byte: 0 },
+        ];
+        for operator in operators.iter() {
+            assert_eq!(GAS_SCHEDULE_V1.cost(operator), 1);
+        }
+        let costs: Vec<u64> = operators.iter().map(|op| GAS_SCHEDULE_V2.cost(op)).collect();
+        assert_eq!(costs, vec![1, 2, 8, 4, 2, 20, 40, 1000]);
+    }
+
+    #[test]
+    fn copies_are_charged_since_v3() {
+        assert_eq!(GAS_SCHEDULE_V2.copy_cost(1024), 0);
+        assert_eq!(GAS_SCHEDULE_V3.copy_cost(1024), 1024);
+        assert_eq!(GAS_SCHEDULE_V3.cost(&Operator::I64Add), GAS_SCHEDULE_V2.cost(&Operator::I64Add));
+    }
+
+    #[test]
+    fn hashes_are_native_since_v5() {
+        assert!(!GAS_SCHEDULE_V4.has_native_hashes());
+        assert!(GAS_SCHEDULE_V5.has_native_hashes());
+        assert_eq!(GAS_SCHEDULE_V5.hash_cost(100), 400);
+    }
+}
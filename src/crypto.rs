@@ -0,0 +1,106 @@
+use ed25519_dalek::{Signature as Ed25519Signature, VerifyingKey as Ed25519Key};
+use k256::ecdsa::{RecoveryId, Signature, VerifyingKey};
+use sha3::{Digest, Keccak256};
+
+pub(crate) const ADDRESS_SIZE: usize = 20;
+pub(crate) const HASH_SIZE: usize = 32;
+/// `r | s | v` with `v` being 0 or 1, as accepted by `ecrecover`.
+pub(crate) const SECP256K1_SIGNATURE_SIZE: usize = 65;
+pub(crate) const ED25519_PUBKEY_SIZE: usize = 32;
+pub(crate) const ED25519_SIGNATURE_SIZE: usize = 64;
+
+/// Returns whether `sig` is a signature of `hash` made by the key of `address`. The address is
+/// derived from the recovered key the way the node derives it: the last 20 bytes of the keccak256
+/// of the uncompressed key without its prefix.
+pub fn verify_secp256k1(address: &[u8], hash: &[u8], sig: &[u8]) -> bool {
+    if address.len() != ADDRESS_SIZE || hash.len() != HASH_SIZE || sig.len() != SECP256K1_SIGNATURE_SIZE {
+        return false;
+    }
+    let signature = match Signature::from_slice(&sig[..64]) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    let recovery_id = match RecoveryId::from_byte(sig[64]) {
+        Some(id) => id,
+        None => return false,
+    };
+    match VerifyingKey::recover_from_prehash(hash, &signature, recovery_id) {
+        Ok(key) => pubkey_to_address(&key) == address,
+        Err(_) => false,
+    }
+}
+
+fn pubkey_to_address(key: &VerifyingKey) -> [u8; ADDRESS_SIZE] {
+    let point = key.to_encoded_point(false);
+    let hash = Keccak256::digest(&point.as_bytes()[1..]);
+    let mut address = [0u8; ADDRESS_SIZE];
+    address.copy_from_slice(&hash[HASH_SIZE - ADDRESS_SIZE..]);
+    address
+}
+
+/// Returns whether `sig` is a signature of `msg` made by `pubkey`. Non-canonical signatures and
+/// weak keys are rejected.
+pub fn verify_ed25519(pubkey: &[u8], msg: &[u8], sig: &[u8]) -> bool {
+    let pubkey: &[u8; ED25519_PUBKEY_SIZE] = match pubkey.try_into() {
+        Ok(pubkey) => pubkey,
+        Err(_) => return false,
+    };
+    let signature = match Ed25519Signature::from_slice(sig) {
+        Ok(signature) => signature,
+        Err(_) => return false,
+    };
+    match Ed25519Key::from_bytes(pubkey) {
+        Ok(key) => key.verify_strict(msg, &signature).is_ok(),
+        Err(_) => false,
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use ed25519_dalek::{Signer, SigningKey as Ed25519SigningKey};
+    use k256::ecdsa::SigningKey;
+
+    use super::*;
+
+    /// Signs `hash` with a key derived from `seed`, returns the address and the signature.
+    pub fn sign_secp256k1(seed: u8, hash: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let key = SigningKey::from_slice(&[seed; 32]).unwrap();
+        let (signature, recovery_id) = key.sign_prehash_recoverable(hash).unwrap();
+        let mut sig = signature.to_bytes().to_vec();
+        sig.push(recovery_id.to_byte());
+        (pubkey_to_address(key.verifying_key()).to_vec(), sig)
+    }
+
+    /// Signs `msg` with a key derived from `seed`, returns the public key and the signature.
+    pub fn sign_ed25519(seed: u8, msg: &[u8]) -> (Vec<u8>, Vec<u8>) {
+        let key = Ed25519SigningKey::from_bytes(&[seed; 32]);
+        (key.verifying_key().to_bytes().to_vec(), key.sign(msg).to_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_verify_secp256k1() {
+        let hash = Keccak256::digest(b"voucher");
+        let (address, sig) = sign_secp256k1(1, &hash);
+        assert!(verify_secp256k1(&address, &hash, &sig));
+
+        let (other, _) = sign_secp256k1(2, &hash);
+        assert!(!verify_secp256k1(&other, &hash, &sig));
+        assert!(!verify_secp256k1(&address, &Keccak256::digest(b"other"), &sig));
+        let mut bad_v = sig.clone();
+        bad_v[64] = 27;
+        assert!(!verify_secp256k1(&address, &hash, &bad_v));
+        assert!(!verify_secp256k1(&address, &hash, &sig[..64]));
+    }
+
+    #[test]
+    fn test_verify_ed25519() {
+        let (pubkey, sig) = sign_ed25519(1, b"voucher");
+        assert!(verify_ed25519(&pubkey, b"voucher", &sig));
+        assert!(!verify_ed25519(&pubkey, b"other", &sig));
+
+        let (other, _) = sign_ed25519(2, b"voucher");
+        assert!(!verify_ed25519(&other, b"voucher", &sig));
+        assert!(!verify_ed25519(&pubkey[..31], b"voucher", &sig));
+        assert!(!verify_ed25519(&pubkey, b"voucher", &sig[..63]));
+    }
+}
@@ -0,0 +1,53 @@
+use loupe::MemoryUsage;
+use wasmer::{
+    ExportIndex, FunctionMiddleware, LocalFunctionIndex, MiddlewareError, MiddlewareReaderState,
+    ModuleMiddleware,
+};
+use wasmer::wasmparser::Operator;
+use wasmer_types::ModuleInfo;
+
+/// Name of the export the start function is moved to.
+pub const DEFERRED_START_EXPORT: &str = "__idena_start";
+
+/// A middleware that removes the start function from a module and exports it as
+/// [`DEFERRED_START_EXPORT`] instead.
+///
+/// Wasmer runs the start function inside of `Instance::new`, i.e. before the host had a chance to
+/// set the remaining gas points. Since compiled modules are cached and shared between calls with
+/// different gas limits, the runner has to invoke the start function itself once the gas limit
+/// of the current call is known.
+#[derive(Debug, MemoryUsage, Default)]
+#[non_exhaustive]
+pub struct DeferredStart {}
+
+impl ModuleMiddleware for DeferredStart {
+    fn generate_function_middleware(&self, _: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
+        Box::new(FunctionDeferredStart {})
+    }
+
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) {
+        // The name is reserved: a function the contract exported under it must never be
+        // mistaken for the start function.
+        module_info.exports.shift_remove(DEFERRED_START_EXPORT);
+        if let Some(start) = module_info.start_function.take() {
+            module_info
+                .exports
+                .insert(DEFERRED_START_EXPORT.to_string(), ExportIndex::Function(start));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FunctionDeferredStart {}
+
+impl FunctionMiddleware for FunctionDeferredStart {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        state.push_operator(operator);
+        Ok(())
+    }
+}
+
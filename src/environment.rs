@@ -1,17 +1,24 @@
 use std::borrow::{Borrow, BorrowMut};
 use std::ptr::NonNull;
-use std::sync::{Arc, RwLock};
+use std::sync::{Arc, Mutex, RwLock};
 
+use indexmap::IndexMap;
 use wasmer::{HostEnvInitError, Instance, Memory, Val, WasmerEnv};
 use wasmer_middlewares::metering::{get_remaining_points, MeteringPoints, set_remaining_points};
 
 use crate::{unwrap_or_return};
 use crate::backend::{Backend, BackendError, BackendResult};
-use crate::costs::BASE_PROMISE_COST;
-use crate::errors::VmError;
+use crate::costs::{BASE_PROMISE_COST, GasSchedule};
+use crate::errors::{TrapKind, VmError};
 use crate::memory::VmResult;
-use crate::types::{Address, DeployContractAction, IDNA, ReadShardedDataAction};
-use crate::types::{Action, FunctionCallAction, Promise, PromiseResult, TransferAction};
+use crate::stack_limiter::stack_limit_exceeded;
+use crate::types::{Address, DeployContractAction, IDNA, LogLevel, ReadShardedDataAction, StorageWrite};
+use crate::types::{Action, BatchAction, FunctionCallAction, Promise, PromiseResult, TransferAction};
+
+/// Maximum number of actions of a batch promise.
+const MAX_BATCH_ACTIONS: usize = 16;
+/// Maximum number of bytes of the keys and values journaled by a call.
+const MAX_JOURNAL_SIZE: usize = 4 * 1024 * 1024;
 
 #[derive(Debug)]
 pub enum Never {}
@@ -19,20 +26,44 @@ pub enum Never {}
 pub struct Env<B: Backend> {
     pub backend: B,
     data: Arc<RwLock<ContextData>>,
-    pub promise_result: Option<PromiseResult>,
+    /// Results of the promises the executed callback waits for.
+    pub promise_results: Vec<PromiseResult>,
     gas_limit : u64,
+    /// Set for queries, which must not change the state.
+    read_only: bool,
+    gas_schedule: &'static GasSchedule,
+    log: ExecutionLog,
 }
 
 impl<B: Backend> Env<B> {
-    pub fn new(api: B, promise_res: Option<PromiseResult>, gas_limit : u64) -> Self {
+    pub fn new(api: B, promise_results: Vec<PromiseResult>, gas_limit : u64, read_only: bool, gas_schedule: &'static GasSchedule, log: ExecutionLog) -> Self {
         Env {
             backend: api,
             data: Arc::new(RwLock::new(ContextData::new())),
-            promise_result: promise_res,
+            promise_results,
             gas_limit : gas_limit,
+            read_only,
+            gas_schedule,
+            log,
         }
     }
 
+    pub fn gas_schedule(&self) -> &'static GasSchedule {
+        self.gas_schedule
+    }
+
+    pub fn log(&self, level: LogLevel, message: impl Into<String>) {
+        self.log.push(level, message);
+    }
+
+    /// Fails in read-only calls, must be checked by every import changing the state.
+    pub fn check_writable(&self) -> VmResult<()> {
+        if self.read_only {
+            return Err(VmError::read_only());
+        }
+        Ok(())
+    }
+
     pub fn set_wasmer_instance(&self, wasmer_instance: Option<NonNull<Instance>>) {
         self.with_context_data_mut(|context_data| {
             context_data.wasmer_instance = wasmer_instance;
@@ -120,7 +151,8 @@ impl<B: Backend> Env<B> {
         func.call(args).map_err(|runtime_err| -> VmError {
             self.with_wasmer_instance::<_, Never>(|instance| {
                 let err: VmError = match get_remaining_points(instance) {
-                    MeteringPoints::Remaining(_) => VmError::custom(runtime_err.to_string()),
+                    MeteringPoints::Remaining(_) if stack_limit_exceeded(instance) => VmError::trap(TrapKind::StackHeightExceeded),
+                    MeteringPoints::Remaining(_) => runtime_err.into(),
                     MeteringPoints::Exhausted => VmError::out_of_gas(),
                 };
                 Err(err)
@@ -134,7 +166,7 @@ impl<B: Backend> Env<B> {
         let expected = 1;
         let actual = result.len();
         if actual != expected {
-            return Err(VmError::custom(format!("Unexpected number of result values when calling '{}'. Expected: {}, actual: {}.", name, expected, actual)));
+            return Err(VmError::host_misuse(format!("Unexpected number of result values when calling '{}'. Expected: {}, actual: {}.", name, expected, actual)));
         }
         Ok(result[0].clone())
     }
@@ -150,6 +182,7 @@ impl<B: Backend> Env<B> {
                     amount
                 }),
                 action_callback: None,
+                joined: vec![],
             })
         });
         (Ok(()), gas_used.saturating_add(BASE_PROMISE_COST))
@@ -170,6 +203,7 @@ impl<B: Backend> Env<B> {
                     deposit: amount,
                 }),
                 action_callback: None,
+                joined: vec![],
             });
             (Ok(data.pending_promises.len() as u32 - 1), gas_used.saturating_add(BASE_PROMISE_COST))
         })
@@ -196,6 +230,7 @@ impl<B: Backend> Env<B> {
                     deposit: amount,
                 }),
                 action_callback: None,
+                joined: vec![],
             });
             (Ok(data.pending_promises.len() as u32 - 1), gas_used.saturating_add(BASE_PROMISE_COST))
         })
@@ -211,6 +246,7 @@ impl<B: Backend> Env<B> {
                 receiver_id: to,
                 action: Action::ReadShardedData(action),
                 action_callback: None,
+                joined: vec![],
             });
             (Ok(data.pending_promises.len() as u32 - 1), gas_used.saturating_add(BASE_PROMISE_COST))
         })
@@ -235,6 +271,127 @@ impl<B: Backend> Env<B> {
         })
     }
 
+    /// Creates a promise joining the given ones, a callback attached to it runs once all of them
+    /// are done. Only promises with a result can be joined.
+    pub fn promise_and(&self, indices: Vec<usize>) -> BackendResult<u32> {
+        let (own_addr_res, gas_used) = self.backend.own_addr();
+        let own_addr = unwrap_or_return!(own_addr_res, gas_used);
+        let gas_used = gas_used.saturating_add(BASE_PROMISE_COST);
+        self.with_context_data_mut(|data| {
+            if indices.is_empty() {
+                return (Err(BackendError::new("no promises to join")), gas_used);
+            }
+            for (i, idx) in indices.iter().enumerate() {
+                if indices[..i].contains(idx) {
+                    return (Err(BackendError::new("promise is joined twice")), gas_used);
+                }
+                match data.pending_promises.get(*idx) {
+                    Some(promise) if promise.joined.is_empty() && !matches!(promise.action, Action::Transfer(_)) => {}
+                    Some(_) => return (Err(BackendError::new("promise can't be joined")), gas_used),
+                    None => return (Err(BackendError::new("invalid promise_idx")), gas_used),
+                }
+            }
+            data.pending_promises.push(Promise {
+                predecessor_id: own_addr,
+                receiver_id: vec![],
+                action: Action::None,
+                action_callback: None,
+                joined: indices,
+            });
+            (Ok(data.pending_promises.len() as u32 - 1), gas_used)
+        })
+    }
+
+    /// Creates an empty batch promise, actions are added to it by `add_batch_action`.
+    pub fn create_batch_promise(&self, to: Address) -> BackendResult<u32> {
+        let (own_addr_res, gas_used) = self.backend.own_addr();
+        let own_addr = unwrap_or_return!(own_addr_res, gas_used);
+        self.with_context_data_mut(|data| {
+            data.pending_promises.push(Promise {
+                predecessor_id: own_addr,
+                receiver_id: to,
+                action: Action::Batch(BatchAction::default()),
+                action_callback: None,
+                joined: vec![],
+            });
+            (Ok(data.pending_promises.len() as u32 - 1), gas_used.saturating_add(BASE_PROMISE_COST))
+        })
+    }
+
+    /// Appends a transfer, function call or deploy to a batch promise. A deployed contract must
+    /// get the address of the receiver of the batch.
+    pub fn add_batch_action(&self, promise_idx: usize, action: Action) -> BackendResult<()> {
+        let mut gas_used = BASE_PROMISE_COST;
+        let deployed_addr = match &action {
+            Action::Transfer(_) | Action::FunctionCall(_) => None,
+            Action::DeployContract(deploy) => {
+                let (addr_res, gas) = self.backend.contract_addr(&deploy.code, &deploy.args, &deploy.nonce);
+                gas_used = gas_used.saturating_add(gas);
+                Some(unwrap_or_return!(addr_res, gas_used))
+            }
+            _ => return (Err(BackendError::new("action can't be batched")), gas_used),
+        };
+        self.with_context_data_mut(|data| {
+            let promise = match data.pending_promises.get_mut(promise_idx) {
+                Some(promise) => promise,
+                None => return (Err(BackendError::new("invalid promise_idx")), gas_used),
+            };
+            if promise.action_callback.is_some() {
+                return (Err(BackendError::new("promise is completed")), gas_used);
+            }
+            if deployed_addr.is_some_and(|addr| addr != promise.receiver_id) {
+                return (Err(BackendError::new("deployed contract is not the batch receiver")), gas_used);
+            }
+            match &mut promise.action {
+                Action::Batch(batch) if batch.actions.len() < MAX_BATCH_ACTIONS => {
+                    batch.actions.push(action);
+                    (Ok(()), gas_used)
+                }
+                Action::Batch(_) => (Err(BackendError::new("too many batch actions")), gas_used),
+                _ => (Err(BackendError::new("promise is not a batch")), gas_used),
+            }
+        })
+    }
+
+    /// Records a write, `None` removes the key. Fails if the journal would exceed
+    /// `MAX_JOURNAL_SIZE`.
+    pub fn journal_write(&self, key: Vec<u8>, value: Option<Vec<u8>>) -> VmResult<()> {
+        let entry_size = |key: &[u8], value: &Option<Vec<u8>>| key.len() + value.as_ref().map_or(0, |v| v.len());
+        self.with_context_data_mut(|data| {
+            let replaced = data.storage_journal.get(&key).map_or(0, |old| entry_size(&key, old));
+            let size = data.journal_size - replaced + entry_size(&key, &value);
+            if size > MAX_JOURNAL_SIZE {
+                return Err(VmError::journal_full());
+            }
+            data.journal_size = size;
+            // Moving the key to the end keeps the journal in the order of the last writes.
+            data.storage_journal.shift_remove(&key);
+            data.storage_journal.insert(key, value);
+            Ok(())
+        })
+    }
+
+    /// Returns the value written to the key during the call, `Some(None)` if it was removed.
+    pub fn journal_read(&self, key: &[u8]) -> Option<Option<Vec<u8>>> {
+        self.with_context_data(|data| data.storage_journal.get(key).cloned())
+    }
+
+    pub fn take_journal(&self) -> Vec<StorageWrite> {
+        self.with_context_data_mut(|data| {
+            data.journal_size = 0;
+            data.storage_journal.drain(..).map(|(key, value)| StorageWrite { key, value }).collect()
+        })
+    }
+
+    /// Records an event of the call. Buffered events reach the backend only if the call succeeds.
+    pub fn push_event(&self, topic: Vec<u8>, data: Vec<u8>) {
+        self.with_context_data_mut(|context| context.pending_events.push((topic, data)));
+    }
+
+    pub fn take_events(&self) -> Vec<(Vec<u8>, Vec<u8>)> {
+        self.with_context_data_mut(|data| data.pending_events.drain(..).collect())
+    }
+
     pub fn get_promises(&self) -> Vec<Promise> {
         let mut result = Vec::new();
         self.with_context_data_mut(|data| {
@@ -247,10 +404,13 @@ impl<B: Backend> Env<B> {
 impl<B: Backend> Clone for Env<B> {
     fn clone(&self) -> Self {
         Env {
-            backend: self.backend,
+            backend: self.backend.clone(),
             data: self.data.clone(),
-            promise_result: self.promise_result.clone(),
-            gas_limit : self.gas_limit
+            promise_results: self.promise_results.clone(),
+            gas_limit : self.gas_limit,
+            read_only: self.read_only,
+            gas_schedule: self.gas_schedule,
+            log: self.log.clone(),
         }
     }
 }
@@ -265,9 +425,32 @@ impl<B: Backend> WasmerEnv for Env<B> {
     }
 }
 
+/// Messages logged during an execution, shared by the runner and the imports of the contract.
+/// The runner stamps them with their source and hands them over when the execution ends.
+#[derive(Clone, Default)]
+pub struct ExecutionLog {
+    records: Arc<Mutex<Vec<(LogLevel, String)>>>,
+}
+
+impl ExecutionLog {
+    pub fn push(&self, level: LogLevel, message: impl Into<String>) {
+        self.records.lock().unwrap().push((level, message.into()));
+    }
+
+    pub fn take(&self) -> Vec<(LogLevel, String)> {
+        self.records.lock().unwrap().drain(..).collect()
+    }
+}
+
 pub struct ContextData {
     wasmer_instance: Option<NonNull<Instance>>,
     pending_promises: Vec<Promise>,
+    /// Storage writes of the call, applied to the backend only if it succeeds.
+    storage_journal: IndexMap<Vec<u8>, Option<Vec<u8>>>,
+    /// Bytes of the keys and values in the journal.
+    journal_size: usize,
+    /// Topics and data of the events emitted by the call, in order.
+    pending_events: Vec<(Vec<u8>, Vec<u8>)>,
 }
 
 impl ContextData {
@@ -275,6 +458,9 @@ impl ContextData {
         ContextData {
             wasmer_instance: None,
             pending_promises: Vec::new(),
+            storage_journal: IndexMap::new(),
+            journal_size: 0,
+            pending_events: Vec::new(),
         }
     }
 }
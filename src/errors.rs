@@ -1,9 +1,98 @@
 use std::fmt::{Debug};
 
 use thiserror::Error;
+use wasmer_types::TrapCode;
 
 use crate::backend::BackendError;
-use crate::errors::VmError::{Custom, OutOfGas, WasmExecutionErr};
+use crate::errors::VmError::{BackendErr, BadArguments, BatchReverted, CompilationErr, Custom, ForbiddenMethod, HostFunctionNotFound, HostMisuse, JournalFull, MethodNotFound, OutOfGas, ReadOnly, Skipped, Trap, UnsupportedVersion, ValidationErr, WasmExecutionErr};
+
+/// Codes of `VmError` reported in `ActionResult.error_code`. They are part of the protocol:
+/// a code is never reused or reassigned.
+pub const ERROR_NONE: u32 = 0;
+/// Failures of the VM itself and of the host input.
+pub const ERROR_UNKNOWN: u32 = 1;
+pub const ERROR_OUT_OF_GAS: u32 = 2;
+pub const ERROR_CONTRACT_PANIC: u32 = 3;
+pub const ERROR_READ_ONLY: u32 = 4;
+pub const ERROR_METHOD_NOT_FOUND: u32 = 5;
+pub const ERROR_FORBIDDEN_METHOD: u32 = 6;
+pub const ERROR_BAD_ARGUMENTS: u32 = 7;
+pub const ERROR_COMPILATION: u32 = 8;
+pub const ERROR_VALIDATION: u32 = 9;
+/// Traps without a trap code, classified traps have codes of their own.
+pub const ERROR_TRAP: u32 = 10;
+pub const ERROR_HOST_MISUSE: u32 = 11;
+pub const ERROR_BACKEND: u32 = 12;
+pub const ERROR_TRAP_UNREACHABLE: u32 = 13;
+pub const ERROR_TRAP_MEMORY_OUT_OF_BOUNDS: u32 = 14;
+pub const ERROR_TRAP_INTEGER_OVERFLOW: u32 = 15;
+pub const ERROR_TRAP_DIVISION_BY_ZERO: u32 = 16;
+pub const ERROR_TRAP_STACK_OVERFLOW: u32 = 17;
+pub const ERROR_TRAP_INDIRECT_CALL: u32 = 18;
+pub const ERROR_TRAP_STACK_HEIGHT_EXCEEDED: u32 = 19;
+pub const ERROR_BATCH_REVERTED: u32 = 20;
+pub const ERROR_JOURNAL_FULL: u32 = 21;
+pub const ERROR_UNSUPPORTED_VERSION: u32 = 22;
+pub const ERROR_SKIPPED: u32 = 23;
+pub const ERROR_HOST_FUNCTION_NOT_FOUND: u32 = 24;
+
+/// Traps of the compiled code, grouped by the cause a contract author has to look into.
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum TrapKind {
+    #[error("unreachable")]
+    Unreachable,
+    #[error("out of bounds memory access")]
+    MemoryOutOfBounds,
+    /// Includes float to integer conversions out of range.
+    #[error("integer overflow")]
+    IntegerOverflow,
+    #[error("integer division by zero")]
+    DivisionByZero,
+    #[error("call stack exhausted")]
+    StackOverflow,
+    /// Signature mismatch, a null or out of bounds table entry.
+    #[error("indirect call failed")]
+    IndirectCall,
+    /// The limit of `StackLimiter` was exceeded.
+    #[error("stack height limit exceeded")]
+    StackHeightExceeded,
+    /// Runtime errors without a trap code.
+    #[error("{}", _0)]
+    Other(String),
+}
+
+impl TrapKind {
+    pub fn code(&self) -> u32 {
+        match self {
+            TrapKind::Unreachable => ERROR_TRAP_UNREACHABLE,
+            TrapKind::MemoryOutOfBounds => ERROR_TRAP_MEMORY_OUT_OF_BOUNDS,
+            TrapKind::IntegerOverflow => ERROR_TRAP_INTEGER_OVERFLOW,
+            TrapKind::DivisionByZero => ERROR_TRAP_DIVISION_BY_ZERO,
+            TrapKind::StackOverflow => ERROR_TRAP_STACK_OVERFLOW,
+            TrapKind::IndirectCall => ERROR_TRAP_INDIRECT_CALL,
+            TrapKind::StackHeightExceeded => ERROR_TRAP_STACK_HEIGHT_EXCEEDED,
+            TrapKind::Other(_) => ERROR_TRAP,
+        }
+    }
+}
+
+impl From<TrapCode> for TrapKind {
+    fn from(code: TrapCode) -> Self {
+        match code {
+            TrapCode::UnreachableCodeReached => TrapKind::Unreachable,
+            TrapCode::HeapAccessOutOfBounds
+            | TrapCode::HeapMisaligned
+            | TrapCode::OutOfBounds
+            | TrapCode::UnalignedAtomic => TrapKind::MemoryOutOfBounds,
+            TrapCode::IntegerOverflow | TrapCode::BadConversionToInteger => TrapKind::IntegerOverflow,
+            TrapCode::IntegerDivisionByZero => TrapKind::DivisionByZero,
+            TrapCode::StackOverflow => TrapKind::StackOverflow,
+            TrapCode::TableAccessOutOfBounds
+            | TrapCode::IndirectCallToNull
+            | TrapCode::BadSignature => TrapKind::IndirectCall,
+        }
+    }
+}
 
 #[derive(Error, Debug)]
 pub enum VmError {
@@ -13,10 +102,67 @@ pub enum VmError {
     },
     #[error("Out of gas")]
     OutOfGas,
+    /// The contract called `panic` or `abort`.
     #[error("Error in wasm module: {}", msg)]
     WasmExecutionErr {
         msg: String
     },
+    #[error("state modification in read-only call")]
+    ReadOnly,
+    #[error("method is not found: {}", method)]
+    MethodNotFound {
+        method: String,
+    },
+    /// Deploy, the deferred start and callbacks can't be called directly.
+    #[error("direct call to {} is forbidden", method)]
+    ForbiddenMethod {
+        method: String,
+    },
+    #[error("invalid arguments: {}", msg)]
+    BadArguments {
+        msg: String,
+    },
+    #[error("compilation error: {}", msg)]
+    CompilationErr {
+        msg: String,
+    },
+    /// The module is well-formed, but doesn't meet the requirements for contracts.
+    #[error("invalid module: {}", msg)]
+    ValidationErr {
+        msg: String,
+    },
+    #[error("wasm trap: {}", kind)]
+    Trap {
+        kind: TrapKind,
+    },
+    /// A host function was called with bad regions, or the contract broke the calling convention.
+    #[error("invalid host call: {}", msg)]
+    HostMisuse {
+        msg: String,
+    },
+    #[error("backend error: {}", msg)]
+    BackendErr {
+        msg: String,
+    },
+    /// The action succeeded, but another action of its batch failed.
+    #[error("reverted by a failed batch action")]
+    BatchReverted,
+    /// The storage writes of the call exceed the size of the journal.
+    #[error("storage journal is full")]
+    JournalFull,
+    /// The host asked for a gas schedule or VM config this build doesn't know.
+    #[error("unknown {} version", what)]
+    UnsupportedVersion {
+        what: String,
+    },
+    /// The host asked to skip the transaction, nothing is executed.
+    #[error("transaction should be skipped")]
+    Skipped,
+    /// The module imports a host function which doesn't exist for its gas schedule.
+    #[error("host function {} is not implemented", name)]
+    HostFunctionNotFound {
+        name: String,
+    },
 }
 
 impl VmError {
@@ -35,23 +181,148 @@ impl VmError {
     pub fn out_of_gas() -> Self {
         OutOfGas {}
     }
+
+    pub fn read_only() -> Self {
+        ReadOnly
+    }
+
+    pub fn method_not_found(method: impl Into<String>) -> Self {
+        MethodNotFound {
+            method: method.into()
+        }
+    }
+
+    pub fn forbidden_method(method: impl Into<String>) -> Self {
+        ForbiddenMethod {
+            method: method.into()
+        }
+    }
+
+    pub fn bad_args(msg: impl Into<String>) -> Self {
+        BadArguments {
+            msg: msg.into()
+        }
+    }
+
+    pub fn compilation_err(msg: impl Into<String>) -> Self {
+        CompilationErr {
+            msg: msg.into()
+        }
+    }
+
+    pub fn validation_err(msg: impl Into<String>) -> Self {
+        ValidationErr {
+            msg: msg.into()
+        }
+    }
+
+    pub fn trap(kind: TrapKind) -> Self {
+        Trap {
+            kind
+        }
+    }
+
+    pub fn host_misuse(msg: impl Into<String>) -> Self {
+        HostMisuse {
+            msg: msg.into()
+        }
+    }
+
+    pub fn batch_reverted() -> Self {
+        BatchReverted
+    }
+
+    pub fn journal_full() -> Self {
+        JournalFull
+    }
+
+    pub fn unsupported_version(what: impl Into<String>) -> Self {
+        UnsupportedVersion {
+            what: what.into()
+        }
+    }
+
+    pub fn skipped() -> Self {
+        Skipped
+    }
+
+    pub fn host_function_not_found(name: impl Into<String>) -> Self {
+        HostFunctionNotFound {
+            name: name.into()
+        }
+    }
+
+    pub fn code(&self) -> u32 {
+        match self {
+            Custom { .. } => ERROR_UNKNOWN,
+            OutOfGas => ERROR_OUT_OF_GAS,
+            WasmExecutionErr { .. } => ERROR_CONTRACT_PANIC,
+            ReadOnly => ERROR_READ_ONLY,
+            MethodNotFound { .. } => ERROR_METHOD_NOT_FOUND,
+            ForbiddenMethod { .. } => ERROR_FORBIDDEN_METHOD,
+            BadArguments { .. } => ERROR_BAD_ARGUMENTS,
+            CompilationErr { .. } => ERROR_COMPILATION,
+            ValidationErr { .. } => ERROR_VALIDATION,
+            Trap { kind } => kind.code(),
+            HostMisuse { .. } => ERROR_HOST_MISUSE,
+            BackendErr { .. } => ERROR_BACKEND,
+            BatchReverted => ERROR_BATCH_REVERTED,
+            JournalFull => ERROR_JOURNAL_FULL,
+            UnsupportedVersion { .. } => ERROR_UNSUPPORTED_VERSION,
+            Skipped => ERROR_SKIPPED,
+            HostFunctionNotFound { .. } => ERROR_HOST_FUNCTION_NOT_FOUND,
+        }
+    }
 }
 
 impl From<wasmer::ExportError> for VmError {
     fn from(original: wasmer::ExportError) -> Self {
-        VmError::custom(format!("Could not get export: {}", original))
+        VmError::validation_err(format!("Could not get export: {}", original))
     }
 }
 
 impl From<wasmer::InstantiationError> for VmError {
     fn from(original: wasmer::InstantiationError) -> Self {
-        VmError::custom(format!("Failed to instantiate module: {}", original))
+        match original {
+            wasmer::InstantiationError::Link(err) => VmError::validation_err(format!("Failed to instantiate module: {}", err)),
+            wasmer::InstantiationError::Start(err) => err.into(),
+            err => VmError::custom(format!("Failed to instantiate module: {}", err)),
+        }
     }
 }
 
+impl From<wasmer::CompileError> for VmError {
+    fn from(original: wasmer::CompileError) -> Self {
+        match original {
+            // The gatekeeper middleware rejects forbidden operators.
+            wasmer::CompileError::Validate(_)
+            | wasmer::CompileError::Wasm(wasmer::WasmError::InvalidWebAssembly { .. })
+            | wasmer::CompileError::Wasm(wasmer::WasmError::Middleware(_)) => VmError::validation_err(original.to_string()),
+            err => VmError::compilation_err(err.to_string()),
+        }
+    }
+}
+
+/// Errors of host functions are passed through as they are, traps are classified by their code.
+impl From<wasmer::RuntimeError> for VmError {
+    fn from(original: wasmer::RuntimeError) -> Self {
+        let original = match original.downcast::<VmError>() {
+            Ok(err) => return err,
+            Err(original) => original,
+        };
+        let kind = match original.clone().to_trap() {
+            Some(code) => code.into(),
+            None => TrapKind::Other(original.message()),
+        };
+        VmError::trap(kind)
+    }
+}
 
 impl From<BackendError> for VmError {
     fn from(original: BackendError) -> Self {
-        VmError::custom(format!("backend error: {}", original))
+        match original {
+            BackendError::OutOfGas => VmError::out_of_gas(),
+            BackendError::Custom { msg } => BackendErr { msg },
+        }
     }
-}
\ No newline at end of file
+}
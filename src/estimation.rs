@@ -0,0 +1,494 @@
+use std::sync::{Arc, Mutex};
+use std::collections::HashMap;
+
+use protobuf::Message;
+use sha3::{Digest, Keccak256};
+
+use crate::backend::{Backend, BackendError, BackendResult};
+use crate::config::VmConfig;
+use crate::costs::GasSchedule;
+use crate::proto::models::InvocationContext as protoContext;
+use crate::runner::VmRunner;
+use crate::types::{idna_to_u128, u128_to_idna, ActionResult, Address, Gas, InvocationContext, LogRecord, IDNA};
+use crate::unwrap_or_return;
+
+/// Gas the host charges for the operations discarded during estimation. The backend is never
+/// asked to perform them, so the costs have to be known upfront.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WriteCosts {
+    pub set_storage: u64,
+    pub remove_storage: u64,
+    pub event: u64,
+    /// Cost of burning, deducting or adding balance.
+    pub transfer: u64,
+    /// Charged on top for each byte of keys, values and event data.
+    pub per_byte: u64,
+}
+
+impl WriteCosts {
+    fn per_byte(&self, data_len: usize) -> u64 {
+        self.per_byte.saturating_mul(data_len as u64)
+    }
+}
+
+/// Storage writes by contract and key, `None` values are removed keys.
+type Writes = HashMap<(Address, Vec<u8>), Option<Vec<u8>>>;
+
+/// State changed during the estimation.
+#[derive(Clone, Default)]
+struct Changes {
+    storage: Writes,
+    /// Code of the contracts deployed during the estimation.
+    contracts: HashMap<Address, Vec<u8>>,
+    /// Amounts received and spent by each address.
+    received: HashMap<Address, u128>,
+    spent: HashMap<Address, u128>,
+}
+
+impl Changes {
+    fn apply(&self, addr: &[u8], balance: u128) -> u128 {
+        let received = self.received.get(addr).copied().unwrap_or_default();
+        let spent = self.spent.get(addr).copied().unwrap_or_default();
+        balance.saturating_add(received).saturating_sub(spent)
+    }
+}
+
+/// Writes made during the estimation, shared by all the executions it runs.
+#[derive(Default)]
+struct Overlay {
+    changes: Changes,
+    /// Changes when each snapshot was taken, the id of a snapshot is its index.
+    snapshots: Vec<Changes>,
+}
+
+/// Context of a contract executed by a nested call or deploy.
+struct Frame {
+    caller: Address,
+    original_caller: Address,
+    pay_amount: IDNA,
+    code: Vec<u8>,
+}
+
+/// A view of the backend which keeps storage writes, balance changes and deployed code to itself
+/// and drops events. Reads are served by the backend unless they were written before.
+///
+/// Nothing reaches the state of the backend: snapshots are taken of the changes kept here, and
+/// nested calls and deploys are run by the estimator itself, each in a frame of its own over the
+/// same overlay.
+#[derive(Clone)]
+pub struct DiscardingBackend<B: Backend> {
+    inner: B,
+    costs: WriteCosts,
+    gas_schedule: &'static GasSchedule,
+    vm_config: &'static VmConfig,
+    is_debug: bool,
+    overlay: Arc<Mutex<Overlay>>,
+    /// Address of the executed contract.
+    contract: Address,
+    /// `None` for the estimated contract, whose context is served by the backend.
+    frame: Option<Arc<Frame>>,
+}
+
+impl<B: Backend + 'static> DiscardingBackend<B> {
+    fn overlay<C, R>(&self, callback: C) -> R
+        where
+            C: FnOnce(&mut Overlay) -> R,
+    {
+        callback(&mut self.overlay.lock().unwrap_or_else(|poisoned| poisoned.into_inner()))
+    }
+
+    fn spend(&self, amount: IDNA) -> BackendResult<()> {
+        let (balance, _) = self.balance();
+        let res = balance.and_then(|balance| {
+            let balance = idna_to_u128(&balance)?;
+            let amount = idna_to_u128(&amount)?;
+            if balance < amount {
+                return Err(BackendError::new("insufficient funds"));
+            }
+            self.overlay(|overlay| *overlay.changes.spent.entry(self.contract.clone()).or_default() += amount);
+            Ok(())
+        });
+        (res, self.costs.transfer)
+    }
+
+    fn code_of(&self, addr: &Address) -> Result<Option<Vec<u8>>, BackendError> {
+        match self.overlay(|overlay| overlay.changes.contracts.get(addr).cloned()) {
+            Some(code) => Ok(Some(code)),
+            None => self.inner.contract_code(addr.clone()).0,
+        }
+    }
+
+    /// Runs a contract in a new frame. All changes made by a failed execution, including the
+    /// transferred amount, are reverted.
+    fn run_nested<F>(&self, contract: Address, code: Vec<u8>, deployed: bool, amount: &[u8], run: F) -> BackendResult<ActionResult>
+        where
+            F: FnOnce(Self, Vec<u8>) -> ActionResult,
+    {
+        let received = match idna_to_u128(amount) {
+            Ok(amount) => amount,
+            Err(err) => return (Err(err), 0),
+        };
+        let original_caller = match &self.frame {
+            Some(frame) => frame.original_caller.clone(),
+            None => unwrap_or_return!(self.inner.original_caller().0, 0),
+        };
+        let (saved, snapshots) = self.overlay(|overlay| {
+            let saved = (overlay.changes.clone(), overlay.snapshots.len());
+            if deployed {
+                overlay.changes.contracts.insert(contract.clone(), code.clone());
+            }
+            *overlay.changes.received.entry(contract.clone()).or_default() += received;
+            saved
+        });
+        let backend = DiscardingBackend {
+            contract,
+            frame: Some(Arc::new(Frame {
+                caller: self.contract.clone(),
+                original_caller,
+                pay_amount: amount.to_vec(),
+                code: code.clone(),
+            })),
+            ..self.clone()
+        };
+        let result = run(backend, code);
+        if !result.success {
+            self.overlay(|overlay| {
+                overlay.changes = saved;
+                overlay.snapshots.truncate(snapshots);
+            });
+        }
+        let gas_used = result.gas_used;
+        (Ok(result), gas_used)
+    }
+}
+
+impl<B: Backend + 'static> Backend for DiscardingBackend<B> {
+    fn set_remaining_gas(&self, gas_limit: u64) -> BackendResult<()> {
+        self.inner.set_remaining_gas(gas_limit)
+    }
+
+    fn set_storage(&self, key: Vec<u8>, value: Vec<u8>) -> BackendResult<()> {
+        let gas = self.costs.set_storage + self.costs.per_byte(key.len() + value.len());
+        self.overlay(|overlay| overlay.changes.storage.insert((self.contract.clone(), key), Some(value)));
+        (Ok(()), gas)
+    }
+
+    fn get_storage(&self, key: Vec<u8>) -> BackendResult<Option<Vec<u8>>> {
+        // The backend is asked anyway to charge the read.
+        let (res, gas) = match self.frame {
+            Some(_) => self.inner.read_contract_data(self.contract.clone(), key.clone()),
+            None => self.inner.get_storage(key.clone()),
+        };
+        match self.overlay(|overlay| overlay.changes.storage.get(&(self.contract.clone(), key)).cloned()) {
+            Some(value) => (Ok(value), gas),
+            None => (res, gas),
+        }
+    }
+
+    fn remove_storage(&self, key: Vec<u8>) -> BackendResult<()> {
+        let gas = self.costs.remove_storage + self.costs.per_byte(key.len());
+        self.overlay(|overlay| overlay.changes.storage.insert((self.contract.clone(), key), None));
+        (Ok(()), gas)
+    }
+
+    fn block_timestamp(&self) -> BackendResult<i64> {
+        self.inner.block_timestamp()
+    }
+
+    fn block_number(&self) -> BackendResult<u64> {
+        self.inner.block_number()
+    }
+
+    fn min_fee_per_gas(&self) -> BackendResult<IDNA> {
+        self.inner.min_fee_per_gas()
+    }
+
+    fn balance(&self) -> BackendResult<IDNA> {
+        let (res, gas) = match self.frame {
+            Some(_) => self.inner.balance_of(self.contract.clone()),
+            None => self.inner.balance(),
+        };
+        let res = res.and_then(|balance| {
+            let balance = idna_to_u128(&balance)?;
+            Ok(u128_to_idna(self.overlay(|overlay| overlay.changes.apply(&self.contract, balance))))
+        });
+        (res, gas)
+    }
+
+    fn block_seed(&self) -> BackendResult<Vec<u8>> {
+        self.inner.block_seed()
+    }
+
+    fn network_size(&self) -> BackendResult<u64> {
+        self.inner.network_size()
+    }
+
+    fn burn(&self, amount: IDNA) -> BackendResult<()> {
+        self.spend(amount)
+    }
+
+    fn read_contract_data(&self, addr: Address, key: Vec<u8>) -> BackendResult<Option<Vec<u8>>> {
+        let (res, gas) = self.inner.read_contract_data(addr.clone(), key.clone());
+        match self.overlay(|overlay| overlay.changes.storage.get(&(addr, key)).cloned()) {
+            Some(value) => (Ok(value), gas),
+            None => (res, gas),
+        }
+    }
+
+    fn epoch(&self) -> BackendResult<u16> {
+        self.inner.epoch()
+    }
+
+    fn identity(&self, addr: Address) -> BackendResult<Option<Vec<u8>>> {
+        self.inner.identity(addr)
+    }
+
+    fn call(&self, addr: Address, method: &[u8], args: &[u8], amount: &[u8], gas_limit: u64, invocation_ctx: &[u8]) -> BackendResult<ActionResult> {
+        let code = match self.code_of(&addr) {
+            Ok(Some(code)) => code,
+            Ok(None) => return (Err(BackendError::new("contract is not found")), 0),
+            Err(err) => return (Err(err), 0),
+        };
+        let mut ctx = InvocationContext::default();
+        if !invocation_ctx.is_empty() {
+            ctx = protoContext::parse_from_bytes(invocation_ctx).unwrap_or_default().into();
+        }
+        let method = String::from_utf8_lossy(method).to_string();
+        self.run_nested(addr.clone(), code, false, amount, |backend, code| {
+            VmRunner::new(backend, addr, gas_limit, self.gas_schedule, self.vm_config, Some(ctx), self.is_debug)
+                .execute(code, &method, args, &mut 0)
+        })
+    }
+
+    fn caller(&self) -> BackendResult<Vec<u8>> {
+        let (res, gas) = self.inner.caller();
+        match &self.frame {
+            Some(frame) => (Ok(frame.caller.clone()), gas),
+            None => (res, gas),
+        }
+    }
+
+    fn original_caller(&self) -> BackendResult<Vec<u8>> {
+        let (res, gas) = self.inner.original_caller();
+        match &self.frame {
+            Some(frame) => (Ok(frame.original_caller.clone()), gas),
+            None => (res, gas),
+        }
+    }
+
+    fn deduct_balance(&self, amount: IDNA) -> BackendResult<()> {
+        self.spend(amount)
+    }
+
+    fn add_balance(&self, to: Address, amount: IDNA) -> BackendResult<()> {
+        let res = idna_to_u128(&amount).map(|amount| {
+            self.overlay(|overlay| *overlay.changes.received.entry(to).or_default() += amount)
+        });
+        (res, self.costs.transfer)
+    }
+
+    fn own_addr(&self) -> BackendResult<Address> {
+        let (res, gas) = self.inner.own_addr();
+        match self.frame {
+            Some(_) => (Ok(self.contract.clone()), gas),
+            None => (res, gas),
+        }
+    }
+
+    fn contract_addr(&self, code: &[u8], args: &[u8], nonce: &[u8]) -> BackendResult<Address> {
+        self.inner.contract_addr(code, args, nonce)
+    }
+
+    fn deploy(&self, code: &[u8], args: &[u8], nonce: &[u8], amount: &[u8], gas_limit: u64) -> BackendResult<ActionResult> {
+        let (addr, gas) = self.contract_addr(code, args, nonce);
+        let addr = unwrap_or_return!(addr, gas);
+        match self.code_of(&addr) {
+            Ok(None) => {}
+            Ok(Some(_)) => return (Err(BackendError::new("contract already exists")), gas),
+            Err(err) => return (Err(err), gas),
+        }
+        self.run_nested(addr.clone(), code.to_vec(), true, amount, |backend, code| {
+            VmRunner::new(backend, addr, gas_limit, self.gas_schedule, self.vm_config, None, self.is_debug)
+                .deploy(code, args, &mut 0)
+        })
+    }
+
+    fn contract_addr_by_hash(&self, hash: &[u8], args: &[u8], nonce: &[u8]) -> BackendResult<Address> {
+        self.inner.contract_addr_by_hash(hash, args, nonce)
+    }
+
+    fn own_code(&self) -> BackendResult<Vec<u8>> {
+        let (res, gas) = self.inner.own_code();
+        match &self.frame {
+            Some(frame) => (Ok(frame.code.clone()), gas),
+            None => (res, gas),
+        }
+    }
+
+    fn code_hash(&self) -> BackendResult<Vec<u8>> {
+        let (res, gas) = self.inner.code_hash();
+        match &self.frame {
+            Some(frame) => (Ok(Keccak256::digest(&frame.code).to_vec()), gas),
+            None => (res, gas),
+        }
+    }
+
+    fn event(&self, event_name: &[u8], args: &[u8]) -> BackendResult<()> {
+        (Ok(()), self.costs.event + self.costs.per_byte(event_name.len() + args.len()))
+    }
+
+    fn pay_amount(&self) -> BackendResult<IDNA> {
+        let (res, gas) = self.inner.pay_amount();
+        match &self.frame {
+            Some(frame) => (Ok(frame.pay_amount.clone()), gas),
+            None => (res, gas),
+        }
+    }
+
+    fn block_header(&self, height: u64) -> BackendResult<Option<Vec<u8>>> {
+        self.inner.block_header(height)
+    }
+
+    fn keccak256(&self, data: &[u8]) -> BackendResult<Vec<u8>> {
+        self.inner.keccak256(data)
+    }
+
+    fn global_state(&self) -> BackendResult<Vec<u8>> {
+        self.inner.global_state()
+    }
+
+    fn ecrecover(&self, data: &[u8], sig: &[u8]) -> BackendResult<Vec<u8>> {
+        self.inner.ecrecover(data, sig)
+    }
+
+    /// Code which is only estimated is not deployed, its artifact is not kept.
+    fn store_artifact(&self, _code_hash: &[u8], _artifact: &[u8]) -> BackendResult<()> {
+        (Ok(()), 0)
+    }
+
+    fn load_artifact(&self, code_hash: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        self.inner.load_artifact(code_hash)
+    }
+
+    /// The log of the estimation is dropped, it is only returned in debug mode.
+    fn log(&self, _record: &LogRecord) -> BackendResult<()> {
+        (Ok(()), 0)
+    }
+
+    fn snapshot(&self) -> BackendResult<u32> {
+        let id = self.overlay(|overlay| {
+            let snapshot = overlay.changes.clone();
+            overlay.snapshots.push(snapshot);
+            overlay.snapshots.len() - 1
+        });
+        (Ok(id as u32), 0)
+    }
+
+    fn revert_to_snapshot(&self, id: u32) -> BackendResult<()> {
+        let res = self.overlay(|overlay| {
+            if id as usize >= overlay.snapshots.len() {
+                return Err(BackendError::new("unknown snapshot"));
+            }
+            overlay.changes = overlay.snapshots.swap_remove(id as usize);
+            overlay.snapshots.truncate(id as usize);
+            Ok(())
+        });
+        (res, 0)
+    }
+
+    fn contract_code(&self, addr: Address) -> BackendResult<Option<Vec<u8>>> {
+        let (res, gas) = self.inner.contract_code(addr.clone());
+        match self.overlay(|overlay| overlay.changes.contracts.get(&addr).cloned()) {
+            Some(code) => (Ok(Some(code)), gas),
+            None => (res, gas),
+        }
+    }
+
+    fn balance_of(&self, addr: Address) -> BackendResult<IDNA> {
+        let (res, gas) = self.inner.balance_of(addr.clone());
+        let res = res.and_then(|balance| {
+            let balance = idna_to_u128(&balance)?;
+            Ok(u128_to_idna(self.overlay(|overlay| overlay.changes.apply(&addr, balance))))
+        });
+        (res, gas)
+    }
+}
+
+pub struct GasEstimate {
+    /// Result of the execution with the gas cap, `gas_used` includes sub-calls and callbacks.
+    pub result: ActionResult,
+    /// The lowest gas limit the execution succeeds with, `None` if it fails even with the cap.
+    pub gas_limit: Option<Gas>,
+}
+
+/// Runs contracts the way `VmRunner` does, with `gas_cap` as the gas limit, and discards all
+/// their changes.
+pub struct GasEstimator<B: Backend + 'static> {
+    api: B,
+    costs: WriteCosts,
+    contract_addr: Address,
+    gas_cap: Gas,
+    gas_schedule: &'static GasSchedule,
+    vm_config: &'static VmConfig,
+    is_debug: bool,
+}
+
+impl<B: Backend + 'static> GasEstimator<B> {
+    pub fn new(
+        api: B,
+        costs: WriteCosts,
+        contract_addr: Address,
+        gas_cap: Gas,
+        gas_schedule: &'static GasSchedule,
+        vm_config: &'static VmConfig,
+        is_debug: bool,
+    ) -> Self {
+        GasEstimator {
+            api,
+            costs,
+            contract_addr,
+            gas_cap,
+            gas_schedule,
+            vm_config,
+            is_debug,
+        }
+    }
+
+    pub fn execute(self, code: Vec<u8>, method: &String, arg_bytes: &[u8], ctx: Option<InvocationContext>) -> GasEstimate {
+        self.estimate(ctx, Changes::default(), |runner| runner.execute(code, method, arg_bytes, &mut 0))
+    }
+
+    pub fn deploy(self, code: Vec<u8>, arg_bytes: &[u8]) -> GasEstimate {
+        // Callbacks of the deployed contract find its code.
+        let mut changes = Changes::default();
+        changes.contracts.insert(self.contract_addr.clone(), code.clone());
+        self.estimate(None, changes, |runner| runner.deploy(code, arg_bytes, &mut 0))
+    }
+
+    fn estimate<F>(self, ctx: Option<InvocationContext>, changes: Changes, run: F) -> GasEstimate
+        where
+            F: FnOnce(VmRunner<DiscardingBackend<B>>) -> ActionResult,
+    {
+        let backend = DiscardingBackend {
+            inner: self.api,
+            costs: self.costs,
+            gas_schedule: self.gas_schedule,
+            vm_config: self.vm_config,
+            is_debug: self.is_debug,
+            overlay: Arc::new(Mutex::new(Overlay { changes, snapshots: Vec::new() })),
+            contract: self.contract_addr.clone(),
+            frame: None,
+        };
+        let result = run(VmRunner::new(backend, self.contract_addr, self.gas_cap, self.gas_schedule, self.vm_config, ctx, self.is_debug));
+        let gas_limit = if result.success { Some(required_gas_limit(&result)) } else { None };
+        GasEstimate { result, gas_limit }
+    }
+}
+
+/// Gas left is only refunded after the promises are executed, so the limit has to cover the
+/// peak consumption: the gas used plus the unused gas of the promises.
+fn required_gas_limit(result: &ActionResult) -> Gas {
+    let refund = result.sub_action_results.iter().fold(0u64, |a, x| a.saturating_add(x.remaining_gas));
+    result.gas_used.saturating_add(refund)
+}
@@ -1,15 +1,21 @@
 use std::{mem, slice};
+use std::panic::AssertUnwindSafe;
 
 use protobuf::Message;
 
 use crate::{check_go_result, proto};
+use crate::abi::{read_abi, ContractAbi};
 use crate::args::convert_args;
 use crate::backend::{Backend, BackendError, BackendResult};
-use crate::costs::{BASE_CALL_COST, BASE_DEPLOY_COST};
+use crate::cache::with_module_cache;
+use crate::config::{vm_config, VmConfig};
+use crate::costs::{gas_schedule, GasSchedule, BASE_CALL_COST, BASE_DEPLOY_COST};
+use crate::estimation::{GasEstimator, WriteCosts};
 use crate::errors::VmError;
 use crate::memory::ByteSliceView;
 use crate::runner::VmRunner;
-use crate::types::{Action, ActionResult, Address, IDNA, InvocationContext};
+use crate::types::{Action, ActionResult, Address, IDNA, InvocationContext, LogRecord};
+use crate::validation::validate_code as validate;
 
 #[repr(C)]
 pub struct gas_meter_t {
@@ -207,6 +213,45 @@ pub struct GoApi_vtable {
         *mut u64,
         *mut UnmanagedVector, // pubkey
     ) -> i32,
+    pub store_artifact: extern "C" fn(
+        *const api_t,
+        U8SliceView, // code hash
+        U8SliceView, // artifact
+    ) -> i32,
+    pub load_artifact: extern "C" fn(
+        *const api_t,
+        U8SliceView, // code hash
+        *mut UnmanagedVector, // artifact
+    ) -> i32,
+    pub log: extern "C" fn(
+        *const api_t,
+        u32, // level
+        U8SliceView, // contract
+        U8SliceView, // method
+        U8SliceView, // message
+    ) -> i32,
+    pub snapshot: extern "C" fn(
+        *const api_t,
+        *mut u64,
+        *mut u32, // snapshot id
+    ) -> i32,
+    pub revert_to_snapshot: extern "C" fn(
+        *const api_t,
+        u32, // snapshot id
+        *mut u64,
+    ) -> i32,
+    pub contract_code: extern "C" fn(
+        *const api_t,
+        U8SliceView, // addr
+        *mut u64,
+        *mut UnmanagedVector, // code
+    ) -> i32,
+    pub balance_of: extern "C" fn(
+        *const api_t,
+        U8SliceView, // addr
+        *mut u64,
+        *mut UnmanagedVector, // balance
+    ) -> i32,
 }
 
 #[repr(C)]
@@ -530,10 +575,11 @@ impl Backend for apiWrapper {
         (Ok(()), used_gas)
     }
 
-    fn add_balance(&self, to: Address, amount: IDNA) -> u64 {
+    fn add_balance(&self, to: Address, amount: IDNA) -> BackendResult<()> {
         let mut used_gas = 0_u64;
-        (self.api.vtable.add_balance)(self.api.state, U8SliceView::new(Some(&to)), U8SliceView::new(Some(&amount)), &mut used_gas as *mut u64);
-        used_gas
+        let go_result = (self.api.vtable.add_balance)(self.api.state, U8SliceView::new(Some(&to)), U8SliceView::new(Some(&amount)), &mut used_gas as *mut u64);
+        check_go_result!(go_result, used_gas, "add_balance");
+        (Ok(()), used_gas)
     }
 
     fn own_addr(&self) -> BackendResult<Address> {
@@ -676,6 +722,57 @@ impl Backend for apiWrapper {
         };
         (Ok(value), used_gas)
     }
+
+    fn store_artifact(&self, code_hash: &[u8], artifact: &[u8]) -> BackendResult<()> {
+        let go_result = (self.api.vtable.store_artifact)(self.api.state, U8SliceView::new(Some(code_hash)), U8SliceView::new(Some(artifact)));
+        check_go_result!(go_result, 0, "store_artifact");
+        (Ok(()), 0)
+    }
+
+    fn load_artifact(&self, code_hash: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        let mut data = UnmanagedVector::default();
+        let go_result = (self.api.vtable.load_artifact)(self.api.state, U8SliceView::new(Some(code_hash)), &mut data as *mut UnmanagedVector);
+        check_go_result!(go_result, 0, "load_artifact");
+        (Ok(data.consume()), 0)
+    }
+
+    fn log(&self, record: &LogRecord) -> BackendResult<()> {
+        let go_result = (self.api.vtable.log)(self.api.state, record.level as u32, U8SliceView::new(Some(&record.contract)),
+                                              U8SliceView::new(Some(record.method.as_bytes())), U8SliceView::new(Some(record.message.as_bytes())));
+        check_go_result!(go_result, 0, "log");
+        (Ok(()), 0)
+    }
+
+    fn snapshot(&self) -> BackendResult<u32> {
+        let mut used_gas = 0_u64;
+        let mut id = 0_u32;
+        let go_result = (self.api.vtable.snapshot)(self.api.state, &mut used_gas as *mut u64, &mut id as *mut u32);
+        check_go_result!(go_result, used_gas, "snapshot");
+        (Ok(id), used_gas)
+    }
+
+    fn revert_to_snapshot(&self, id: u32) -> BackendResult<()> {
+        let mut used_gas = 0_u64;
+        let go_result = (self.api.vtable.revert_to_snapshot)(self.api.state, id, &mut used_gas as *mut u64);
+        check_go_result!(go_result, used_gas, "revert_to_snapshot");
+        (Ok(()), used_gas)
+    }
+
+    fn contract_code(&self, addr: Address) -> BackendResult<Option<Vec<u8>>> {
+        let mut used_gas = 0_u64;
+        let mut code = UnmanagedVector::default();
+        let go_result = (self.api.vtable.contract_code)(self.api.state, U8SliceView::new(Some(&addr)), &mut used_gas as *mut u64, &mut code as *mut UnmanagedVector);
+        check_go_result!(go_result, used_gas, "contract_code");
+        (Ok(code.consume()), used_gas)
+    }
+
+    fn balance_of(&self, addr: Address) -> BackendResult<IDNA> {
+        let mut used_gas = 0_u64;
+        let mut balance = UnmanagedVector::default();
+        let go_result = (self.api.vtable.balance_of)(self.api.state, U8SliceView::new(Some(&addr)), &mut used_gas as *mut u64, &mut balance as *mut UnmanagedVector);
+        check_go_result!(go_result, used_gas, "balance_of");
+        (Ok(balance.consume().unwrap_or_default()), used_gas)
+    }
 }
 
 unsafe impl Send for apiWrapper {}
@@ -683,18 +780,34 @@ unsafe impl Send for apiWrapper {}
 unsafe impl Sync for apiWrapper {}
 
 
-fn do_execute(api: GoApi, code: ByteSliceView,
-              method_name: ByteSliceView,
-              args: ByteSliceView,
-              invocation_context: ByteSliceView,
-              contract_addr: ByteSliceView,
-              gas_limit: u64,
-              gas_used: &mut u64,
-              is_debug: bool) -> ActionResult {
+/// Validates the input of a call and hands it to `run`, which executes it with the given API.
+fn do_execute<F>(api: GoApi, code: ByteSliceView,
+                 method_name: ByteSliceView,
+                 args: ByteSliceView,
+                 ctx_bytes: &[u8],
+                 contract_addr: ByteSliceView,
+                 gas_limit: u64,
+                 gas_schedule_version: u32,
+                 vm_config_version: u32,
+                 gas_used: &mut u64,
+                 run: F) -> ActionResult
+    where
+        F: FnOnce(apiWrapper, &'static GasSchedule, &'static VmConfig, InvocationContext, Vec<u8>, &String, &[u8]) -> ActionResult,
+{
     *gas_used = BASE_CALL_COST;
 
     let addr = contract_addr.read().unwrap_or(&[]);
 
+    let schedule = match gas_schedule(gas_schedule_version) {
+        Some(s) => s,
+        None => return action_result_from_err(VmError::unsupported_version("gas schedule"), addr, gas_limit, *gas_used)
+    };
+
+    let config = match vm_config(vm_config_version) {
+        Some(c) => c,
+        None => return action_result_from_err(VmError::unsupported_version("vm config"), addr, gas_limit, *gas_used)
+    };
+
     let data: Vec<u8> = match code.read() {
         Some(v) => v.to_vec(),
         None => return action_result_from_err(VmError::custom("code is required"), addr, gas_limit, *gas_used)
@@ -709,34 +822,29 @@ fn do_execute(api: GoApi, code: ByteSliceView,
     let method = String::from_utf8_lossy(&method_bytes).to_string();
 
     if arguments_bytes.len() == 0 {
-        return action_result_from_err(VmError::custom("invalid arguments format"), addr, gas_limit, *gas_used);
+        return action_result_from_err(VmError::bad_args("invalid arguments format"), addr, gas_limit, *gas_used);
     }
 
-    let args = match convert_args(arguments_bytes) {
-        Ok(a) => a,
-        Err(err) => return action_result_from_err(err, addr, gas_limit, *gas_used),
-    };
-    if is_debug {
-        println!("execute code: code len={}, method={}, args={:?}, gas limit={}", data.len(), method, args, gas_limit);
+    if let Err(err) = convert_args(arguments_bytes) {
+        return action_result_from_err(err, addr, gas_limit, *gas_used);
     }
 
     let mut ctx = InvocationContext::default();
 
-    let ctx_bytes = invocation_context.read().unwrap_or(&[]);
     if ctx_bytes.len() > 0 {
         ctx = proto::models::InvocationContext::parse_from_bytes(ctx_bytes).unwrap_or_default().into()
     }
-    std::panic::catch_unwind(|| {
-        VmRunner::new(apiWrapper::new(api), addr.to_vec(), gas_limit, Some(ctx), is_debug)
-            .execute(data, &method, arguments_bytes, &mut gas_used.clone())
-    }).unwrap_or_else(|_| {
-        action_result_from_err(VmError::custom("transaction should be skipped"), addr, gas_limit, *gas_used)
+    std::panic::catch_unwind(AssertUnwindSafe(|| {
+        run(apiWrapper::new(api), schedule, config, ctx, data, &method, arguments_bytes)
+    })).unwrap_or_else(|_| {
+        action_result_from_err(VmError::skipped(), addr, gas_limit, *gas_used)
     })
 }
 
 fn action_result_from_err(err: VmError, contract_addr: &[u8], gas_limit: u64, gas_used: u64) -> ActionResult {
     ActionResult {
         error: err.to_string(),
+        error_code: err.code(),
         success: false,
         gas_used: gas_used,
         remaining_gas: gas_limit.saturating_sub(gas_used),
@@ -744,19 +852,38 @@ fn action_result_from_err(err: VmError, contract_addr: &[u8], gas_limit: u64, ga
         sub_action_results: vec![],
         output_data: vec![],
         contract: contract_addr.to_vec(),
+        write_set: vec![],
+        events: vec![],
+        logs: vec![],
     }
 }
 
 
-fn do_deploy(api: GoApi, code: ByteSliceView,
-             args: ByteSliceView,
-             contract_addr: ByteSliceView,
-             gas_limit: u64,
-             gas_used: &mut u64,
-             is_debug: bool) -> ActionResult {
+/// Validates the input of a deploy and hands it to `run`, which executes it with the given API.
+fn do_deploy<F>(api: GoApi, code: ByteSliceView,
+                args: ByteSliceView,
+                contract_addr: ByteSliceView,
+                gas_limit: u64,
+                gas_schedule_version: u32,
+                vm_config_version: u32,
+                gas_used: &mut u64,
+                run: F) -> ActionResult
+    where
+        F: FnOnce(apiWrapper, &'static GasSchedule, &'static VmConfig, Vec<u8>, &[u8]) -> ActionResult,
+{
     *gas_used = BASE_DEPLOY_COST;
     let addr = contract_addr.read().unwrap_or(&[]);
 
+    let schedule = match gas_schedule(gas_schedule_version) {
+        Some(s) => s,
+        None => return action_result_from_err(VmError::unsupported_version("gas schedule"), addr, gas_limit, *gas_used)
+    };
+
+    let config = match vm_config(vm_config_version) {
+        Some(c) => c,
+        None => return action_result_from_err(VmError::unsupported_version("vm config"), addr, gas_limit, *gas_used)
+    };
+
     let data: Vec<u8> = match code.read() {
         Some(v) => v.to_vec(),
         None => return action_result_from_err(VmError::custom("code is required"), addr, gas_limit, *gas_used)
@@ -764,21 +891,16 @@ fn do_deploy(api: GoApi, code: ByteSliceView,
     let arguments_bytes = args.read().unwrap_or(&[]);
 
     if arguments_bytes.len() == 0 {
-        return action_result_from_err(VmError::custom("invalid arguments"), addr, gas_limit, *gas_used);
+        return action_result_from_err(VmError::bad_args("invalid arguments"), addr, gas_limit, *gas_used);
     }
 
-    let args = match convert_args(arguments_bytes) {
-        Ok(a) => a,
-        Err(err) => return action_result_from_err(err, addr, gas_limit, *gas_used),
-    };
-    if is_debug {
-        println!("deploy code: code len={}, args={:?}, gas limit={}", data.len(), args, gas_limit);
-    }
-    std::panic::catch_unwind(|| {
-        VmRunner::new(apiWrapper::new(api), addr.to_vec(), gas_limit, None, is_debug)
-            .deploy(data, arguments_bytes, &mut gas_used.clone())
-    }).unwrap_or_else(|_| {
-        action_result_from_err(VmError::custom("transaction should be skipped"), addr, gas_limit, *gas_used)
+    if let Err(err) = convert_args(arguments_bytes) {
+        return action_result_from_err(err, addr, gas_limit, *gas_used);
+    }
+    std::panic::catch_unwind(AssertUnwindSafe(|| {
+        run(apiWrapper::new(api), schedule, config, data, arguments_bytes)
+    })).unwrap_or_else(|_| {
+        action_result_from_err(VmError::skipped(), addr, gas_limit, *gas_used)
     })
 }
 
@@ -790,10 +912,17 @@ pub extern "C" fn execute(api: GoApi, code: ByteSliceView,
                           invocation_context: ByteSliceView,
                           contract_addr: ByteSliceView,
                           gas_limit: u64,
+                          gas_schedule_version: u32,
+                          vm_config_version: u32,
                           gas_used: &mut u64,
                           action_result: &mut UnmanagedVector,
                           is_debug: bool) -> u8 {
-    let res = do_execute(api, code, method_name, args, invocation_context, contract_addr, gas_limit, gas_used, is_debug);
+    let addr = contract_addr.read().unwrap_or(&[]).to_vec();
+    let res = do_execute(api, code, method_name, args, invocation_context.read().unwrap_or(&[]), contract_addr, gas_limit, gas_schedule_version, vm_config_version, gas_used,
+                         |api, schedule, config, ctx, code, method, arg_bytes| {
+                             VmRunner::new(api, addr, gas_limit, schedule, config, Some(ctx), is_debug)
+                                 .execute(code, method, arg_bytes, &mut 0)
+                         });
     let proto_action = Into::<crate::proto::models::ActionResult>::into(&res);
     *action_result = UnmanagedVector::new(Some(proto_action.write_to_bytes().unwrap_or(vec![])));
     0
@@ -805,11 +934,211 @@ pub extern "C" fn deploy(api: GoApi, code: ByteSliceView,
                          args: ByteSliceView,
                          contract_addr: ByteSliceView,
                          gas_limit: u64,
+                         gas_schedule_version: u32,
+                         vm_config_version: u32,
                          gas_used: &mut u64,
                          action_result: &mut UnmanagedVector,
                          is_debug: bool) -> u8 {
-    let res = do_deploy(api, code, args, contract_addr, gas_limit, gas_used, is_debug);
+    let addr = contract_addr.read().unwrap_or(&[]).to_vec();
+    let res = do_deploy(api, code, args, contract_addr, gas_limit, gas_schedule_version, vm_config_version, gas_used,
+                        |api, schedule, config, code, arg_bytes| {
+                            VmRunner::new(api, addr, gas_limit, schedule, config, None, is_debug)
+                                .deploy(code, arg_bytes, &mut 0)
+                        });
+    let proto_action = Into::<crate::proto::models::ActionResult>::into(&res);
+    *action_result = UnmanagedVector::new(Some(proto_action.write_to_bytes().unwrap_or(vec![])));
+    0
+}
+
+/// Calls a method in read-only mode: any attempt to change the state fails the call.
+#[no_mangle]
+pub extern "C" fn query(api: GoApi, code: ByteSliceView,
+                        method_name: ByteSliceView,
+                        args: ByteSliceView,
+                        contract_addr: ByteSliceView,
+                        gas_limit: u64,
+                        gas_schedule_version: u32,
+                        vm_config_version: u32,
+                        gas_used: &mut u64,
+                        action_result: &mut UnmanagedVector,
+                        is_debug: bool) -> u8 {
+    let addr = contract_addr.read().unwrap_or(&[]).to_vec();
+    let res = do_execute(api, code, method_name, args, &[], contract_addr, gas_limit, gas_schedule_version, vm_config_version, gas_used,
+                         |api, schedule, config, ctx, code, method, arg_bytes| {
+                             VmRunner::new(api, addr, gas_limit, schedule, config, Some(ctx), is_debug)
+                                 .query(code, method, arg_bytes, &mut 0)
+                         });
+    *gas_used = res.gas_used;
+    let proto_action = Into::<crate::proto::models::ActionResult>::into(&res);
+    *action_result = UnmanagedVector::new(Some(proto_action.write_to_bytes().unwrap_or(vec![])));
+    0
+}
+
+/// Runs the call with `gas_cap` as the gas limit, discarding all changes. Storage, event and
+/// transfer costs are charged according to `costs`. The lowest gas limit the call succeeds with
+/// is written to `min_gas_limit`, zero if it fails.
+#[no_mangle]
+pub extern "C" fn estimate_execute(api: GoApi, code: ByteSliceView,
+                                   method_name: ByteSliceView,
+                                   args: ByteSliceView,
+                                   invocation_context: ByteSliceView,
+                                   contract_addr: ByteSliceView,
+                                   gas_cap: u64,
+                                   gas_schedule_version: u32,
+                                   vm_config_version: u32,
+                                   costs: WriteCosts,
+                                   gas_used: &mut u64,
+                                   min_gas_limit: &mut u64,
+                                   action_result: &mut UnmanagedVector,
+                                   is_debug: bool) -> u8 {
+    let addr = contract_addr.read().unwrap_or(&[]).to_vec();
+    let mut estimated_limit = None;
+    let res = do_execute(api, code, method_name, args, invocation_context.read().unwrap_or(&[]), contract_addr, gas_cap, gas_schedule_version, vm_config_version, gas_used,
+                         |api, schedule, config, ctx, code, method, arg_bytes| {
+                             let estimate = GasEstimator::new(api, costs, addr, gas_cap, schedule, config, is_debug)
+                                 .execute(code, method, arg_bytes, Some(ctx));
+                             estimated_limit = estimate.gas_limit;
+                             estimate.result
+                         });
+    *gas_used = res.gas_used;
+    *min_gas_limit = estimated_limit.unwrap_or_default();
     let proto_action = Into::<crate::proto::models::ActionResult>::into(&res);
     *action_result = UnmanagedVector::new(Some(proto_action.write_to_bytes().unwrap_or(vec![])));
     0
-}
\ No newline at end of file
+}
+
+/// Deploy counterpart of `estimate_execute`.
+#[no_mangle]
+pub extern "C" fn estimate_deploy(api: GoApi, code: ByteSliceView,
+                                  args: ByteSliceView,
+                                  contract_addr: ByteSliceView,
+                                  gas_cap: u64,
+                                  gas_schedule_version: u32,
+                                  vm_config_version: u32,
+                                  costs: WriteCosts,
+                                  gas_used: &mut u64,
+                                  min_gas_limit: &mut u64,
+                                  action_result: &mut UnmanagedVector,
+                                  is_debug: bool) -> u8 {
+    let addr = contract_addr.read().unwrap_or(&[]).to_vec();
+    let mut estimated_limit = None;
+    let res = do_deploy(api, code, args, contract_addr, gas_cap, gas_schedule_version, vm_config_version, gas_used,
+                        |api, schedule, config, code, arg_bytes| {
+                            let estimate = GasEstimator::new(api, costs, addr, gas_cap, schedule, config, is_debug)
+                                .deploy(code, arg_bytes);
+                            estimated_limit = estimate.gas_limit;
+                            estimate.result
+                        });
+    *gas_used = res.gas_used;
+    *min_gas_limit = estimated_limit.unwrap_or_default();
+    let proto_action = Into::<crate::proto::models::ActionResult>::into(&res);
+    *action_result = UnmanagedVector::new(Some(proto_action.write_to_bytes().unwrap_or(vec![])));
+    0
+}
+
+/// Checks the code without compiling it and writes a protobuf `ValidationReport` listing all
+/// violations to `report`. Returns true iff the code is valid, false with an empty report if the
+/// gas schedule or VM config version is unknown.
+#[no_mangle]
+pub extern "C" fn validate_code(code: ByteSliceView, gas_schedule_version: u32, vm_config_version: u32, report: &mut UnmanagedVector) -> bool {
+    let (config, schedule) = match (vm_config(vm_config_version), gas_schedule(gas_schedule_version)) {
+        (Some(c), Some(s)) => (c, s),
+        _ => {
+            *report = UnmanagedVector::new(Some(vec![]));
+            return false;
+        }
+    };
+    let res = validate(code.read().unwrap_or(&[]), config, schedule);
+    let proto_report = Into::<crate::proto::models::ValidationReport>::into(&res);
+    *report = UnmanagedVector::new(Some(proto_report.write_to_bytes().unwrap_or(vec![])));
+    res.is_valid()
+}
+
+/// Writes the ABI of the code as JSON to `abi`. Returns false if the code has no ABI or it is
+/// invalid, the reason is written to `error` in the latter case.
+#[no_mangle]
+pub extern "C" fn contract_abi(code: ByteSliceView, abi: &mut UnmanagedVector, error: &mut UnmanagedVector) -> bool {
+    let res = read_abi(code.read().unwrap_or(&[])).and_then(|abi| match abi {
+        Some(abi) => serde_json::to_vec(&abi).map(Some).map_err(|err| err.to_string()),
+        None => Ok(None),
+    });
+    match res {
+        Ok(Some(json)) => {
+            *abi = UnmanagedVector::new(Some(json));
+            true
+        }
+        Ok(None) => false,
+        Err(err) => {
+            *error = UnmanagedVector::new(Some(err.into_bytes()));
+            false
+        }
+    }
+}
+
+fn abi_of(code: ByteSliceView) -> Result<ContractAbi, String> {
+    read_abi(code.read().unwrap_or(&[]))
+        .map_err(|err| format!("invalid abi: {}", err))?
+        .ok_or_else(|| "contract has no abi".to_string())
+}
+
+/// Encodes the JSON arguments of the method to `args` in the typed format, see
+/// `ContractAbi::encode_args`. Returns false and writes the reason to `error` on failure.
+#[no_mangle]
+pub extern "C" fn encode_json_args(code: ByteSliceView,
+                                   method_name: ByteSliceView,
+                                   json: ByteSliceView,
+                                   args: &mut UnmanagedVector,
+                                   error: &mut UnmanagedVector) -> bool {
+    let res = abi_of(code).and_then(|abi| {
+        let method = String::from_utf8_lossy(method_name.read().unwrap_or(&[])).to_string();
+        let json = serde_json::from_slice(json.read().unwrap_or(b"null")).map_err(|err| err.to_string())?;
+        abi.encode_args(&method, &json).map_err(|err| err.to_string())
+    });
+    match res {
+        Ok(data) => {
+            *args = UnmanagedVector::new(Some(data));
+            true
+        }
+        Err(err) => {
+            *error = UnmanagedVector::new(Some(err.into_bytes()));
+            false
+        }
+    }
+}
+
+/// Decodes `ActionResult.output_data` of the method to JSON, see `ContractAbi::decode_output`.
+/// Returns false and writes the reason to `error` on failure.
+#[no_mangle]
+pub extern "C" fn decode_json_output(code: ByteSliceView,
+                                     method_name: ByteSliceView,
+                                     output: ByteSliceView,
+                                     json: &mut UnmanagedVector,
+                                     error: &mut UnmanagedVector) -> bool {
+    let res = abi_of(code).and_then(|abi| {
+        let method = String::from_utf8_lossy(method_name.read().unwrap_or(&[])).to_string();
+        let value = abi.decode_output(&method, output.read().unwrap_or(&[])).map_err(|err| err.to_string())?;
+        serde_json::to_vec(&value).map_err(|err| err.to_string())
+    });
+    match res {
+        Ok(data) => {
+            *json = UnmanagedVector::new(Some(data));
+            true
+        }
+        Err(err) => {
+            *error = UnmanagedVector::new(Some(err.into_bytes()));
+            false
+        }
+    }
+}
+
+#[no_mangle]
+pub extern "C" fn module_cache_stats(hits: &mut u64,
+                                     misses: &mut u64,
+                                     entries: &mut u64,
+                                     size: &mut u64) {
+    let stats = with_module_cache(|cache| cache.stats());
+    *hits = stats.hits;
+    *misses = stats.misses;
+    *entries = stats.entries as u64;
+    *size = stats.size as u64;
+}
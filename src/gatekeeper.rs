@@ -5,8 +5,8 @@ use wasmer::{
 };
 use wasmer::wasmparser::Operator;
 
-#[derive(Debug, MemoryUsage, Clone, Copy)]
-struct GatekeeperConfig {
+#[derive(Debug, MemoryUsage, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GatekeeperConfig {
     /// True iff float operations are allowed.
     ///
     /// Note: there are float operations in the SIMD block as well and we do not yet handle
@@ -53,6 +53,12 @@ impl Gatekeeper {
     fn new(config: GatekeeperConfig) -> Self {
         Self { config }
     }
+
+    /// Returns the config the middleware was created with. Modules compiled with different
+    /// configs must never be mixed up, so this is a part of the module cache key.
+    pub fn config(&self) -> GatekeeperConfig {
+        self.config
+    }
 }
 
 impl Default for Gatekeeper {
@@ -90,12 +96,9 @@ impl FunctionGatekeeper {
 /// The name used in errors
 const MIDDLEWARE_NAME: &str = "Gatekeeper";
 
-impl FunctionMiddleware for FunctionGatekeeper {
-    fn feed<'a>(
-        &mut self,
-        operator: Operator<'a>,
-        state: &mut MiddlewareReaderState<'a>,
-    ) -> Result<(), MiddlewareError> {
+impl GatekeeperConfig {
+    /// Checks that the operator is allowed by the config, returns the reason of the rejection otherwise.
+    pub fn check_operator(&self, operator: &Operator) -> Result<(), String> {
         match operator {
             Operator::Unreachable
             | Operator::Nop
@@ -206,7 +209,6 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::I64ExtendI32S
             | Operator::I64Extend32S
             | Operator::I64ExtendI32U => {
-                state.push_operator(operator);
                 Ok(())
             }
             Operator::RefNull { .. }
@@ -219,12 +221,11 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::TableSet { .. }
             | Operator::TableGrow { .. }
             | Operator::TableSize { .. } => {
-                if self.config.allow_feature_reference_types {
-                    state.push_operator(operator);
+                if self.allow_feature_reference_types {
                     Ok(())
                 } else {
                     let msg = format!("Reference type operation detected: {:?}. Reference types are not supported.", operator);
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::MemoryAtomicNotify { .. }
@@ -294,12 +295,11 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::I64AtomicRmw8CmpxchgU { .. }
             | Operator::I64AtomicRmw16CmpxchgU { .. }
             | Operator::I64AtomicRmw32CmpxchgU { .. } => {
-                if self.config.allow_feature_threads {
-                    state.push_operator(operator);
+                if self.allow_feature_threads {
                     Ok(())
                 } else {
                     let msg = format!("Threads operator detected: {:?}. The Wasm Threads extension is not supported.", operator);
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::V128Load { .. }
@@ -495,15 +495,14 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::I16x8LaneSelect
             | Operator::I32x4LaneSelect
             | Operator::I64x2LaneSelect => {
-                if self.config.allow_feature_simd {
-                    state.push_operator(operator);
+                if self.allow_feature_simd {
                     Ok(())
                 } else {
                     let msg = format!(
                         "SIMD operator detected: {:?}. The Wasm SIMD extension is not supported.",
                         operator
                     );
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::F32Load { .. }
@@ -642,15 +641,14 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::F32x4Fms
             | Operator::F64x2Fma
             | Operator::F64x2Fms => {
-                if self.config.allow_floats {
-                    state.push_operator(operator);
+                if self.allow_floats {
                     Ok(())
                 } else {
                     let msg = format!(
                         "Float operator detected: {:?}. The use of floats is not supported.",
                         operator
                     );
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::MemoryInit { .. }
@@ -661,12 +659,11 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::ElemDrop { .. }
             | Operator::TableCopy { .. }
             | Operator::TableFill { .. } => {
-                if self.config.allow_feature_bulk_memory_operations {
-                    state.push_operator(operator);
+                if self.allow_feature_bulk_memory_operations {
                     Ok(())
                 } else {
                     let msg = format!("Bulk memory operation detected: {:?}. Bulk memory operations are not supported.", operator);
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
             Operator::Try { .. }
@@ -675,17 +672,30 @@ impl FunctionMiddleware for FunctionGatekeeper {
             | Operator::Rethrow { .. }
             | Operator::Delegate { .. }
             | Operator::CatchAll => {
-                if self.config.allow_feature_exception_handling {
-                    state.push_operator(operator);
+                if self.allow_feature_exception_handling {
                     Ok(())
                 } else {
                     let msg = format!("Exception handling operation detected: {:?}. Exception handling is not supported.", operator);
-                    Err(MiddlewareError::new(MIDDLEWARE_NAME, msg))
+                    Err(msg)
                 }
             }
         }
     }
 }
+
+impl FunctionMiddleware for FunctionGatekeeper {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        self.config
+            .check_operator(&operator)
+            .map_err(|msg| MiddlewareError::new(MIDDLEWARE_NAME, msg))?;
+        state.push_operator(operator);
+        Ok(())
+    }
+}
 #[cfg(test)]
 mod tests {
     use std::sync::Arc;
@@ -0,0 +1,114 @@
+use std::sync::Mutex;
+
+use loupe::MemoryUsage;
+use wasmer::{
+    ExportIndex, FunctionMiddleware, GlobalInit, GlobalType, LocalFunctionIndex, MiddlewareError,
+    MiddlewareReaderState, ModuleMiddleware, Mutability, Type,
+};
+use wasmer::wasmparser::{Operator, Type as WpType, TypeOrFuncType};
+use wasmer_types::{GlobalIndex, ModuleInfo};
+
+/// Globals of the metering middleware, see `wasmer_middlewares::metering`.
+const REMAINING_POINTS_EXPORT: &str = "wasmer_metering_remaining_points";
+const POINTS_EXHAUSTED_EXPORT: &str = "wasmer_metering_points_exhausted";
+
+#[derive(Clone, Copy, Debug, MemoryUsage)]
+struct GrowGlobals {
+    remaining: GlobalIndex,
+    exhausted: GlobalIndex,
+    /// Holds the number of requested pages while the cost is charged.
+    pages: GlobalIndex,
+}
+
+/// A middleware charging `memory.grow` per requested page on top of the static price of the
+/// operator.
+///
+/// The charge is taken from the points of `Metering` before the memory is grown, running out of
+/// points fails the call exactly like `Metering` does. Failed grows are charged as well. Must be
+/// pushed after `Metering`, so that the injected operators are not metered themselves.
+#[derive(Debug, MemoryUsage)]
+pub struct GrowMetering {
+    page_cost: u64,
+    globals: Mutex<Option<GrowGlobals>>,
+}
+
+impl GrowMetering {
+    pub fn new(page_cost: u64) -> Self {
+        GrowMetering {
+            page_cost,
+            globals: Mutex::new(None),
+        }
+    }
+}
+
+impl ModuleMiddleware for GrowMetering {
+    fn generate_function_middleware(&self, _: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
+        Box::new(FunctionGrowMetering {
+            page_cost: self.page_cost,
+            globals: self.globals.lock().unwrap().expect("GrowMetering::transform_module_info must be called first"),
+        })
+    }
+
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) {
+        let mut globals = self.globals.lock().unwrap();
+        if globals.is_some() {
+            panic!("GrowMetering::transform_module_info: the middleware can't be used for several modules");
+        }
+        let metering_global = |name: &str| match module_info.exports.get(name) {
+            Some(ExportIndex::Global(index)) => *index,
+            _ => panic!("GrowMetering::transform_module_info: Metering must be pushed first"),
+        };
+        let remaining = metering_global(REMAINING_POINTS_EXPORT);
+        let exhausted = metering_global(POINTS_EXHAUSTED_EXPORT);
+        let pages = module_info.globals.push(GlobalType::new(Type::I32, Mutability::Var));
+        module_info.global_initializers.push(GlobalInit::I32Const(0));
+        *globals = Some(GrowGlobals { remaining, exhausted, pages });
+    }
+}
+
+#[derive(Debug)]
+struct FunctionGrowMetering {
+    page_cost: u64,
+    globals: GrowGlobals,
+}
+
+impl FunctionMiddleware for FunctionGrowMetering {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        if let Operator::MemoryGrow { .. } = operator {
+            let remaining = self.globals.remaining.as_u32();
+            let pages = self.globals.pages.as_u32();
+            let cost = [
+                Operator::GlobalGet { global_index: pages },
+                Operator::I64ExtendI32U,
+                Operator::I64Const { value: self.page_cost as i64 },
+                Operator::I64Mul,
+            ];
+            state.extend(&[
+                Operator::GlobalSet { global_index: pages },
+                Operator::GlobalGet { global_index: remaining },
+            ]);
+            state.extend(&cost);
+            state.extend(&[
+                Operator::I64LtU,
+                Operator::If { ty: TypeOrFuncType::Type(WpType::EmptyBlockType) },
+                Operator::I32Const { value: 1 },
+                Operator::GlobalSet { global_index: self.globals.exhausted.as_u32() },
+                Operator::Unreachable,
+                Operator::End,
+                Operator::GlobalGet { global_index: remaining },
+            ]);
+            state.extend(&cost);
+            state.extend(&[
+                Operator::I64Sub,
+                Operator::GlobalSet { global_index: remaining },
+                Operator::GlobalGet { global_index: pages },
+            ]);
+        }
+        state.push_operator(operator);
+        Ok(())
+    }
+}
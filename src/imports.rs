@@ -1,74 +1,261 @@
+use blake2::Blake2b;
+use blake2::digest::consts::U32;
+use ripemd::Ripemd160;
+use sha2::{Digest, Sha256};
+use sha3::{Keccak256, Sha3_256};
+use wasmer::{Function, Store, Type};
+use wasmer::Type::{I32, I64};
+
 use crate::backend::Backend;
-use crate::costs::BASE_BYTES_TO_HEX_COST;
+use crate::costs::{GasSchedule, BASE_BYTES_TO_HEX_COST};
+use crate::crypto::{verify_ed25519 as ed25519_is_valid, verify_secp256k1 as secp256k1_is_valid, ADDRESS_SIZE, ED25519_PUBKEY_SIZE, ED25519_SIGNATURE_SIZE, HASH_SIZE, SECP256K1_SIGNATURE_SIZE};
 use crate::environment::Env;
 use crate::errors::VmError;
-use crate::memory::{read_region, read_u32, read_utf16_string, ref_to_u32, to_u32, VmResult, write_region};
-use crate::types::{GetIdentityAction, PromiseResult, ReadContractDataAction, ReadShardedDataAction};
+use crate::memory::{read_region, read_u32, read_utf16_string, ref_to_u32, region_length, to_u32, VmResult, write_region};
+use crate::types::{Action, Address, DeployContractAction, Event, FunctionCallAction, GetIdentityAction, LogLevel, PromiseResult, ReadContractDataAction, ReadShardedDataAction, StorageWrite, TransferAction};
 
 const MAX_STORAGE_KEY_SIZE: usize = 128 * 1024;
 const MAX_ADDRESS_SIZE: usize = 20;
-const MAX_CODE_SIZE: usize = 1024 * 1024;
+pub const MAX_CODE_SIZE: usize = 1024 * 1024;
 const MAX_IDNA_SIZE: usize = 32;
 const MAX_STORAGE_VALUE_SIZE: usize = 128 * 1024;
 const MAX_STRING_SIZE: usize = 4 * 1024;
 const MAX_ARGS_SIZE: usize = 10 * 1024;
 const MAX_SIGNATURE_SIZE: usize = 65;
+const MAX_HASH_DATA_SIZE: usize = 64 * 1024;
+/// Maximum number of signatures checked by `verify_secp256k1_batch` and `verify_ed25519_batch`.
+const MAX_BATCH_SIGNATURES: usize = 64;
+/// `address | hash | sig` of `verify_secp256k1_batch`.
+const SECP256K1_BATCH_ENTRY_SIZE: usize = ADDRESS_SIZE + HASH_SIZE + SECP256K1_SIGNATURE_SIZE;
+/// `pubkey | sig | message length`, followed by the message, of `verify_ed25519_batch`.
+const ED25519_BATCH_HEADER_SIZE: usize = ED25519_PUBKEY_SIZE + ED25519_SIGNATURE_SIZE + 4;
+/// Maximum number of promises joined by `promise_and`.
+const MAX_JOINED_PROMISES: usize = 64;
 pub const MAX_RETURN_VALUE_SIZE: usize = 64 * 1024;
 
+/// Name, signature (params, results) and availability of a host function.
+pub type HostFunction = (&'static str, &'static [Type], &'static [Type], fn(&GasSchedule) -> bool);
+
+/// The host functions provided to contracts in the `env` module, except for `debug`, which is
+/// only available in debug mode. Both the runner and the validation take them from here. The
+/// native hashes and signature checks are only provided by gas schedules which price them.
+pub const HOST_FUNCTIONS: &[HostFunction] = &[
+    ("abort", &[I32, I32, I32, I32], &[], always),
+    ("panic", &[I32], &[], always),
+    ("set_storage", &[I32, I32], &[], always),
+    ("get_storage", &[I32], &[I32], always),
+    ("remove_storage", &[I32], &[], always),
+    ("block_timestamp", &[], &[I64], always),
+    ("block_number", &[], &[I64], always),
+    ("block_seed", &[], &[I32], always),
+    ("min_fee_per_gas", &[], &[I32], always),
+    ("network_size", &[], &[I64], always),
+    ("caller", &[], &[I32], always),
+    ("original_caller", &[], &[I32], always),
+    ("create_call_function_promise", &[I32, I32, I32, I32, I32], &[I32], always),
+    ("create_deploy_contract_promise", &[I32, I32, I32, I32, I32], &[I32], always),
+    ("create_read_contract_data_promise", &[I32, I32, I32], &[I32], always),
+    ("create_get_identity_promise", &[I32, I32], &[I32], always),
+    ("create_transfer_promise", &[I32, I32], &[], always),
+    ("promise_result", &[I32], &[I32], always),
+    ("promise_then", &[I32, I32, I32, I32, I32], &[], always),
+    ("promise_and", &[I32], &[I32], always),
+    ("promise_results_count", &[], &[I32], always),
+    ("promise_result_at", &[I32, I32], &[I32], always),
+    ("promise_batch_create", &[I32], &[I32], always),
+    ("promise_batch_action_transfer", &[I32, I32], &[], always),
+    ("promise_batch_action_function_call", &[I32, I32, I32, I32, I32], &[], always),
+    ("promise_batch_action_deploy", &[I32, I32, I32, I32, I32, I32], &[], always),
+    ("own_addr", &[], &[I32], always),
+    ("own_code", &[], &[I32], always),
+    ("contract_addr", &[I32, I32, I32], &[I32], always),
+    ("contract_addr_by_hash", &[I32, I32, I32], &[I32], always),
+    ("code_hash", &[], &[I32], always),
+    ("emit_event", &[I32, I32], &[], always),
+    ("epoch", &[], &[I32], always),
+    ("pay_amount", &[], &[I32], always),
+    ("bytes_to_hex", &[I32], &[I32], always),
+    ("block_header", &[I64], &[I32], always),
+    ("keccak256", &[I32], &[I32], always),
+    ("global_state", &[], &[I32], always),
+    ("gas_limit", &[], &[I64], always),
+    ("gas_left", &[], &[I64], always),
+    ("balance", &[], &[I32], always),
+    ("burn", &[I32], &[], always),
+    ("ecrecover", &[I32, I32], &[I32], always),
+    ("sha256", &[I32], &[I32], GasSchedule::has_native_hashes),
+    ("sha3_256", &[I32], &[I32], GasSchedule::has_native_hashes),
+    ("blake2b_256", &[I32], &[I32], GasSchedule::has_native_hashes),
+    ("ripemd160", &[I32], &[I32], GasSchedule::has_native_hashes),
+    ("verify_secp256k1", &[I32, I32, I32], &[I32], GasSchedule::has_signature_verification),
+    ("verify_secp256k1_batch", &[I32], &[I32], GasSchedule::has_signature_verification),
+    ("verify_ed25519", &[I32, I32, I32], &[I32], GasSchedule::has_signature_verification),
+    ("verify_ed25519_batch", &[I32], &[I32], GasSchedule::has_signature_verification),
+];
+
+fn always(_: &GasSchedule) -> bool {
+    true
+}
+
+/// Host functions provided under the gas schedule.
+pub fn host_functions(schedule: &GasSchedule) -> impl Iterator<Item=&'static HostFunction> + '_ {
+    HOST_FUNCTIONS.iter().filter(move |(_, _, _, provided)| provided(schedule))
+}
+
+/// Implementation of the host function listed in `HOST_FUNCTIONS` under the name.
+pub fn host_function<B: Backend + 'static>(store: &Store, env: &Env<B>, name: &str) -> Option<Function> {
+    let function = match name {
+        "abort" => Function::new_native_with_env(store, env.clone(), abort),
+        "panic" => Function::new_native_with_env(store, env.clone(), panic),
+        "set_storage" => Function::new_native_with_env(store, env.clone(), set_storage),
+        "get_storage" => Function::new_native_with_env(store, env.clone(), get_storage),
+        "remove_storage" => Function::new_native_with_env(store, env.clone(), remove_storage),
+        "block_timestamp" => Function::new_native_with_env(store, env.clone(), block_timestamp),
+        "block_number" => Function::new_native_with_env(store, env.clone(), block_number),
+        "block_seed" => Function::new_native_with_env(store, env.clone(), block_seed),
+        "min_fee_per_gas" => Function::new_native_with_env(store, env.clone(), min_fee_per_gas),
+        "network_size" => Function::new_native_with_env(store, env.clone(), network_size),
+        "caller" => Function::new_native_with_env(store, env.clone(), caller),
+        "original_caller" => Function::new_native_with_env(store, env.clone(), original_caller),
+        "create_call_function_promise" => Function::new_native_with_env(store, env.clone(), create_call_function_promise),
+        "create_deploy_contract_promise" => Function::new_native_with_env(store, env.clone(), create_deploy_contract_promise),
+        "create_read_contract_data_promise" => Function::new_native_with_env(store, env.clone(), create_read_contract_data_promise),
+        "create_get_identity_promise" => Function::new_native_with_env(store, env.clone(), create_get_identity_promise),
+        "create_transfer_promise" => Function::new_native_with_env(store, env.clone(), create_transfer_promise),
+        "promise_result" => Function::new_native_with_env(store, env.clone(), promise_result),
+        "promise_then" => Function::new_native_with_env(store, env.clone(), promise_then),
+        "promise_and" => Function::new_native_with_env(store, env.clone(), promise_and),
+        "promise_results_count" => Function::new_native_with_env(store, env.clone(), promise_results_count),
+        "promise_result_at" => Function::new_native_with_env(store, env.clone(), promise_result_at),
+        "promise_batch_create" => Function::new_native_with_env(store, env.clone(), promise_batch_create),
+        "promise_batch_action_transfer" => Function::new_native_with_env(store, env.clone(), promise_batch_action_transfer),
+        "promise_batch_action_function_call" => Function::new_native_with_env(store, env.clone(), promise_batch_action_function_call),
+        "promise_batch_action_deploy" => Function::new_native_with_env(store, env.clone(), promise_batch_action_deploy),
+        "own_addr" => Function::new_native_with_env(store, env.clone(), own_addr),
+        "own_code" => Function::new_native_with_env(store, env.clone(), own_code),
+        "contract_addr" => Function::new_native_with_env(store, env.clone(), contract_addr),
+        "contract_addr_by_hash" => Function::new_native_with_env(store, env.clone(), contract_addr_by_hash),
+        "code_hash" => Function::new_native_with_env(store, env.clone(), code_hash),
+        "emit_event" => Function::new_native_with_env(store, env.clone(), event),
+        "epoch" => Function::new_native_with_env(store, env.clone(), epoch),
+        "pay_amount" => Function::new_native_with_env(store, env.clone(), pay_amount),
+        "bytes_to_hex" => Function::new_native_with_env(store, env.clone(), bytes_to_hex),
+        "block_header" => Function::new_native_with_env(store, env.clone(), block_header),
+        "keccak256" => Function::new_native_with_env(store, env.clone(), keccak256),
+        "global_state" => Function::new_native_with_env(store, env.clone(), global_state),
+        "gas_limit" => Function::new_native_with_env(store, env.clone(), gas_limit),
+        "gas_left" => Function::new_native_with_env(store, env.clone(), gas_left),
+        "balance" => Function::new_native_with_env(store, env.clone(), balance),
+        "burn" => Function::new_native_with_env(store, env.clone(), burn),
+        "ecrecover" => Function::new_native_with_env(store, env.clone(), ecrecover),
+        "sha256" => Function::new_native_with_env(store, env.clone(), sha256),
+        "sha3_256" => Function::new_native_with_env(store, env.clone(), sha3_256),
+        "blake2b_256" => Function::new_native_with_env(store, env.clone(), blake2b_256),
+        "ripemd160" => Function::new_native_with_env(store, env.clone(), ripemd160),
+        "verify_secp256k1" => Function::new_native_with_env(store, env.clone(), verify_secp256k1),
+        "verify_secp256k1_batch" => Function::new_native_with_env(store, env.clone(), verify_secp256k1_batch),
+        "verify_ed25519" => Function::new_native_with_env(store, env.clone(), verify_ed25519),
+        "verify_ed25519_batch" => Function::new_native_with_env(store, env.clone(), verify_ed25519_batch),
+        _ => return None,
+    };
+    Some(function)
+}
+
 pub fn process_gas_info<B: Backend>(
     env: &Env<B>,
     used_gas: u64,
 ) -> VmResult<()> {
     let gas_left = env.get_gas_left();
-    let gas_left = gas_left.saturating_sub(used_gas);
+    let exhausted = if env.gas_schedule().exact_host_gas {
+        used_gas > gas_left
+    } else {
+        used_gas >= gas_left
+    };
 
     // This tells wasmer how much more gas it can consume from this point in time.
-    env.set_gas_left(gas_left);
-    if gas_left == 0 {
+    env.set_gas_left(gas_left.saturating_sub(used_gas));
+    if exhausted {
         Err(VmError::out_of_gas())
     } else {
         Ok(())
     }
 }
 
+/// Charges copying `len` bytes between the contract memory and the host.
+fn charge_copy<B: Backend>(env: &Env<B>, len: usize) -> VmResult<()> {
+    let cost = env.gas_schedule().copy_cost(len);
+    if cost == 0 {
+        return Ok(());
+    }
+    process_gas_info(env, cost)
+}
+
+/// Reads the Region at ptr, the copy is charged before it is made.
+pub fn read_from_contract<B: Backend>(
+    env: &Env<B>,
+    ptr: u32,
+    max_length: usize,
+) -> VmResult<Vec<u8>> {
+    let len = region_length(&env.memory(), ptr)?;
+    // Oversized regions are rejected by read_region without copying.
+    if len <= max_length {
+        charge_copy(env, len)?;
+    }
+    read_region(&env.memory(), ptr, max_length)
+}
+
 /// Creates a Region in the contract, writes the given data to it and returns the memory location
 pub fn write_to_contract<B: Backend>(
     env: &Env<B>,
     input: &[u8],
 ) -> VmResult<u32> {
+    charge_copy(env, input.len())?;
     let out_size = to_u32(input.len())?;
     let result = env.call_function1("allocate", &[out_size.into()])?;
     let target_ptr = ref_to_u32(&result)?;
     if target_ptr == 0 {
-        return Err(VmError::custom("target pointer is zero"));
+        return Err(VmError::host_misuse("target pointer is zero"));
     }
     write_region(&env.memory(), target_ptr, input)?;
     Ok(target_ptr)
 }
 
 
+/// Storage writes are journaled and applied by `flush` once the call succeeds, unless the
+/// gas schedule predates the journal.
 pub fn set_storage<B: Backend>(env: &Env<B>, key: u32, value: u32) -> VmResult<()> {
-    let key = read_region(&env.memory(), key, MAX_STORAGE_KEY_SIZE)?;
-    let value = read_region(&env.memory(), value, MAX_STORAGE_VALUE_SIZE)?;
-
-    set_left_gas_to_backend(env)?;
-
-    let (result, gas) = env.backend.set_storage(key, value);
-    process_gas_info(env, gas)?;
-
-    result?;
-
-    Ok(())
+    env.check_writable()?;
+    let key = read_from_contract(env, key, MAX_STORAGE_KEY_SIZE)?;
+    let value = read_from_contract(env, value, MAX_STORAGE_VALUE_SIZE)?;
+    let schedule = env.gas_schedule();
+    if !schedule.has_storage_journal() {
+        set_left_gas_to_backend(env)?;
+        let (result, gas) = env.backend.set_storage(key, value);
+        process_gas_info(env, gas)?;
+        return Ok(result?);
+    }
+    process_gas_info(env, schedule.storage_cost(schedule.storage_write, key.len() + value.len()))?;
+    env.journal_write(key, Some(value))
 }
 
+/// Keys written during the call are read from the journal without asking the backend.
 pub fn get_storage<B: Backend>(env: &Env<B>, key: u32) -> VmResult<u32> {
-    let key = read_region(&env.memory(), key, MAX_STORAGE_KEY_SIZE)?;
-    set_left_gas_to_backend(env)?;
-
-    let (result, gas) = env.backend.get_storage(key);
-
-    process_gas_info(env, gas)?;
-    let value = result?;
+    let key = read_from_contract(env, key, MAX_STORAGE_KEY_SIZE)?;
+
+    let value = match env.journal_read(&key) {
+        Some(value) => {
+            let schedule = env.gas_schedule();
+            let len = key.len() + value.as_ref().map_or(0, |v| v.len());
+            process_gas_info(env, schedule.storage_cost(schedule.storage_read, len))?;
+            value
+        }
+        None => {
+            set_left_gas_to_backend(env)?;
+            let (result, gas) = env.backend.get_storage(key);
+            process_gas_info(env, gas)?;
+            result?
+        }
+    };
 
     let out_data = match value {
         Some(data) => data,
@@ -78,15 +265,48 @@ pub fn get_storage<B: Backend>(env: &Env<B>, key: u32) -> VmResult<u32> {
 }
 
 pub fn remove_storage<B: Backend>(env: &Env<B>, key: u32) -> VmResult<()> {
-    let key = read_region(&env.memory(), key, MAX_STORAGE_KEY_SIZE)?;
-    set_left_gas_to_backend(env)?;
-
-    let (result, gas) = env.backend.remove_storage(key);
-
-    process_gas_info(env, gas)?;
-
-    result?;
+    env.check_writable()?;
+    let key = read_from_contract(env, key, MAX_STORAGE_KEY_SIZE)?;
+    let schedule = env.gas_schedule();
+    if !schedule.has_storage_journal() {
+        set_left_gas_to_backend(env)?;
+        let (result, gas) = env.backend.remove_storage(key);
+        process_gas_info(env, gas)?;
+        return Ok(result?);
+    }
+    process_gas_info(env, schedule.storage_cost(schedule.storage_remove, key.len()))?;
+    env.journal_write(key, None)
+}
+
+/// Applies the journaled writes and passes the buffered events to the backend, returns both. They
+/// were charged when they were made. If the backend rejects one, everything applied before is
+/// reverted.
+pub fn flush<B: Backend>(env: &Env<B>, contract: &Address) -> VmResult<(Vec<StorageWrite>, Vec<Event>)> {
+    let writes = env.take_journal();
+    let events = env.take_events();
+    // Older schedules passed the events to the backend when they were emitted.
+    let buffered = if env.gas_schedule().has_event_buffer() { events.as_slice() } else { &[] };
+    if !writes.is_empty() || !buffered.is_empty() {
+        let snapshot = env.backend.snapshot().0?;
+        if let Err(err) = apply(env, &writes, buffered) {
+            env.backend.revert_to_snapshot(snapshot).0?;
+            return Err(err);
+        }
+    }
+    let events = events.into_iter().map(|(topic, data)| Event { contract: contract.clone(), topic, data }).collect();
+    Ok((writes, events))
+}
 
+fn apply<B: Backend>(env: &Env<B>, writes: &[StorageWrite], events: &[(Vec<u8>, Vec<u8>)]) -> VmResult<()> {
+    for write in writes.iter() {
+        match &write.value {
+            Some(value) => env.backend.set_storage(write.key.clone(), value.clone()).0?,
+            None => env.backend.remove_storage(write.key.clone()).0?,
+        }
+    }
+    for (topic, data) in events.iter() {
+        env.backend.event(topic, data).0?;
+    }
     Ok(())
 }
 
@@ -150,16 +370,25 @@ pub fn network_size<B: Backend>(env: &Env<B>) -> VmResult<u64> {
     Ok(result?)
 }
 
+/// Events are charged and buffered, `flush` passes them to the backend once the call succeeds.
+/// Older schedules pass them to the backend right away, which charges them.
 pub fn event<B: Backend>(env: &Env<B>, event_name: u32, args: u32) -> VmResult<()> {
-    let event_name = read_region(&env.memory(), event_name, MAX_STRING_SIZE)?;
+    env.check_writable()?;
+    let event_name = read_from_contract(env, event_name, MAX_STRING_SIZE)?;
 
-    let args = if args > 0 { read_region(&env.memory(), args, MAX_ARGS_SIZE)? } else { vec![] };
-    set_left_gas_to_backend(env)?;
+    let args = if args > 0 { read_from_contract(env, args, MAX_ARGS_SIZE)? } else { vec![] };
+    let schedule = env.gas_schedule();
+    if schedule.has_event_buffer() {
+        process_gas_info(env, schedule.event_cost(event_name.len() + args.len()))?;
+    } else {
+        set_left_gas_to_backend(env)?;
 
-    let (result, gas) = env.backend.event(&event_name, &args);
+        let (result, gas) = env.backend.event(&event_name, &args);
 
-    process_gas_info(env, gas)?;
-    result?;
+        process_gas_info(env, gas)?;
+        result?;
+    }
+    env.push_event(event_name, args);
     Ok(())
 }
 
@@ -195,9 +424,8 @@ pub fn original_caller<B: Backend>(env: &Env<B>) -> VmResult<u32> {
 
 
 pub fn debug<B: Backend>(env: &Env<B>, ptr: u32) -> VmResult<()> {
-    let message_data = read_region(&env.memory(), ptr, MAX_STRING_SIZE)?;
-    let msg = String::from_utf8_lossy(&message_data);
-    println!("{}", msg);
+    let message_data = read_from_contract(env, ptr, MAX_STRING_SIZE)?;
+    env.log(LogLevel::Debug, String::from_utf8_lossy(&message_data));
     Ok(())
 }
 
@@ -209,23 +437,41 @@ pub fn abort<B: Backend>(env: &Env<B>, msg: u32, file_ptr: u32, line: u32, col:
         let str = read_utf16_string(&mem, msg, msg_len?)?;
         let file = read_utf16_string(&mem, file_ptr, file_len?)?;
         let message = format!("{}, filename: \"{}\" line: {} col: {}", str, file, line, col);
-        println!("called abort fn: {} ", message);
+        env.log(LogLevel::Error, format!("called abort fn: {}", message));
         return Err(VmError::wasm_err(message));
     }
-    Err(VmError::custom("bad utf16 format"))
+    Err(VmError::host_misuse("bad utf16 format"))
 }
 
 pub fn panic<B: Backend>(env: &Env<B>, msg: u32) -> VmResult<()> {
-    let message_data = read_region(&env.memory(), msg, MAX_STRING_SIZE)?;
+    let message_data = read_from_contract(env, msg, MAX_STRING_SIZE)?;
     let msg = String::from_utf8_lossy(&message_data);
-
-    println!("wasm panicked: {}", msg);
-
+    env.log(LogLevel::Error, format!("wasm panicked: {}", msg));
     Err(VmError::wasm_err(msg))
 }
 
+/// Returns the first result the callback waits for, see `write_promise_result`.
 pub fn promise_result<B: Backend>(env: &Env<B>, status: u32) -> VmResult<u32> {
-    Ok(match &env.promise_result {
+    write_promise_result(env, env.promise_results.first(), status)
+}
+
+pub fn promise_results_count<B: Backend>(env: &Env<B>) -> VmResult<u32> {
+    Ok(env.promise_results.len() as u32)
+}
+
+/// Returns the result of the joined promise at `idx`, in the order they were passed to
+/// `promise_and`.
+pub fn promise_result_at<B: Backend>(env: &Env<B>, idx: u32, status: u32) -> VmResult<u32> {
+    match env.promise_results.get(idx as usize) {
+        Some(result) => write_promise_result(env, Some(result), status),
+        None => Err(VmError::host_misuse("invalid promise result index")),
+    }
+}
+
+/// Writes the status of the result to `status`: 0 if the promise failed, 1 if it returned nothing
+/// and 2 if it returned a value, which is written to the contract and returned.
+fn write_promise_result<B: Backend>(env: &Env<B>, result: Option<&PromiseResult>, status: u32) -> VmResult<u32> {
+    Ok(match result {
         Some(v) => {
             match v {
                 PromiseResult::Empty => {
@@ -250,10 +496,11 @@ pub fn promise_result<B: Backend>(env: &Env<B>, status: u32) -> VmResult<u32> {
 }
 
 pub fn create_call_function_promise<B: Backend>(env: &Env<B>, addr: u32, method: u32, args: u32, amount: u32, gas_limit: u32) -> VmResult<u32> {
-    let to = read_region(&env.memory(), addr, MAX_ADDRESS_SIZE)?;
-    let method = read_region(&env.memory(), method, MAX_STRING_SIZE)?;
-    let args = if args > 0 { read_region(&env.memory(), args, MAX_ARGS_SIZE)? } else { vec![] };
-    let amount_value = if amount > 0 { read_region(&env.memory(), amount, MAX_IDNA_SIZE)? } else { vec![] };
+    env.check_writable()?;
+    let to = read_from_contract(env, addr, MAX_ADDRESS_SIZE)?;
+    let method = read_from_contract(env, method, MAX_STRING_SIZE)?;
+    let args = if args > 0 { read_from_contract(env, args, MAX_ARGS_SIZE)? } else { vec![] };
+    let amount_value = if amount > 0 { read_from_contract(env, amount, MAX_IDNA_SIZE)? } else { vec![] };
 
     deduct_balance_if_needed(env, &amount_value)?;
 
@@ -265,10 +512,11 @@ pub fn create_call_function_promise<B: Backend>(env: &Env<B>, addr: u32, method:
 }
 
 pub fn create_deploy_contract_promise<B: Backend>(env: &Env<B>, code: u32, args: u32, nonce: u32, amount: u32, gas_limit: u32) -> VmResult<u32> {
-    let code = read_region(&env.memory(), code, MAX_CODE_SIZE)?;
-    let args = if args > 0 { read_region(&env.memory(), args, MAX_ARGS_SIZE)? } else { vec![] };
-    let nonce = if nonce > 0 { read_region(&env.memory(), nonce, MAX_STRING_SIZE)? } else { vec![] };
-    let amount_value = if amount > 0 { read_region(&env.memory(), amount, MAX_IDNA_SIZE)? } else { vec![] };
+    env.check_writable()?;
+    let code = read_from_contract(env, code, MAX_CODE_SIZE)?;
+    let args = if args > 0 { read_from_contract(env, args, MAX_ARGS_SIZE)? } else { vec![] };
+    let nonce = if nonce > 0 { read_from_contract(env, nonce, MAX_STRING_SIZE)? } else { vec![] };
+    let amount_value = if amount > 0 { read_from_contract(env, amount, MAX_IDNA_SIZE)? } else { vec![] };
 
     deduct_balance_if_needed(env, &amount_value)?;
     let idx_res = env.create_deploy_contract_promise(code, args, nonce, amount_value, gas_limit as u64);
@@ -289,9 +537,10 @@ fn deduct_balance_if_needed<B: Backend>(env: &Env<B>, amount_value: &Vec<u8>) ->
 }
 
 pub fn promise_then<B: Backend>(env: &Env<B>, promise_idx: u32, method: u32, args: u32, amount: u32, gas_limit: u32) -> VmResult<()> {
-    let method = read_region(&env.memory(), method, MAX_STRING_SIZE)?;
-    let args = if args > 0 { read_region(&env.memory(), args, MAX_ARGS_SIZE)? } else { vec![] };
-    let amount = if amount > 0 { read_region(&env.memory(), amount, MAX_IDNA_SIZE)? } else { vec![] };
+    env.check_writable()?;
+    let method = read_from_contract(env, method, MAX_STRING_SIZE)?;
+    let args = if args > 0 { read_from_contract(env, args, MAX_ARGS_SIZE)? } else { vec![] };
+    let amount = if amount > 0 { read_from_contract(env, amount, MAX_IDNA_SIZE)? } else { vec![] };
 
     deduct_balance_if_needed(env, &amount)?;
 
@@ -301,9 +550,81 @@ pub fn promise_then<B: Backend>(env: &Env<B>, promise_idx: u32, method: u32, arg
     process_gas_info(env, gas_limit as u64)
 }
 
+/// `indices` holds the indices of the joined promises as little-endian u32 values. Returns the
+/// index of the joint promise, which takes a callback through `promise_then`.
+pub fn promise_and<B: Backend>(env: &Env<B>, indices: u32) -> VmResult<u32> {
+    env.check_writable()?;
+    let data = read_from_contract(env, indices, 4 * MAX_JOINED_PROMISES)?;
+    if data.len() % 4 != 0 {
+        return Err(VmError::host_misuse("malformed promise indices"));
+    }
+    let indices = data.chunks(4).map(|idx| u32::from_le_bytes(idx.try_into().unwrap()) as usize).collect();
+    let idx_res = env.promise_and(indices);
+    process_gas_info(env, idx_res.1)?;
+    Ok(idx_res.0?)
+}
+
+/// Creates a batch promise to `addr`. Its actions run in order once the execution is over, the
+/// callback attached through `promise_then` gets a single result for the whole batch.
+pub fn promise_batch_create<B: Backend>(env: &Env<B>, addr: u32) -> VmResult<u32> {
+    env.check_writable()?;
+    let to = read_from_contract(env, addr, MAX_ADDRESS_SIZE)?;
+    let idx_res = env.create_batch_promise(to);
+    process_gas_info(env, idx_res.1)?;
+    Ok(idx_res.0?)
+}
+
+pub fn promise_batch_action_transfer<B: Backend>(env: &Env<B>, promise_idx: u32, amount: u32) -> VmResult<()> {
+    env.check_writable()?;
+    let amount = read_from_contract(env, amount, MAX_IDNA_SIZE)?;
+    deduct_balance_if_needed(env, &amount)?;
+    add_batch_action(env, promise_idx, Action::Transfer(TransferAction { amount }))
+}
+
+pub fn promise_batch_action_function_call<B: Backend>(env: &Env<B>, promise_idx: u32, method: u32, args: u32, amount: u32, gas_limit: u32) -> VmResult<()> {
+    env.check_writable()?;
+    let method = read_from_contract(env, method, MAX_STRING_SIZE)?;
+    let args = if args > 0 { read_from_contract(env, args, MAX_ARGS_SIZE)? } else { vec![] };
+    let amount = if amount > 0 { read_from_contract(env, amount, MAX_IDNA_SIZE)? } else { vec![] };
+
+    deduct_balance_if_needed(env, &amount)?;
+    add_batch_action(env, promise_idx, Action::FunctionCall(FunctionCallAction {
+        gas_limit: gas_limit as u64,
+        args,
+        method_name: String::from_utf8_lossy(&method).to_string(),
+        deposit: amount,
+    }))?;
+    process_gas_info(env, gas_limit as u64)
+}
+
+pub fn promise_batch_action_deploy<B: Backend>(env: &Env<B>, promise_idx: u32, code: u32, args: u32, nonce: u32, amount: u32, gas_limit: u32) -> VmResult<()> {
+    env.check_writable()?;
+    let code = read_from_contract(env, code, MAX_CODE_SIZE)?;
+    let args = if args > 0 { read_from_contract(env, args, MAX_ARGS_SIZE)? } else { vec![] };
+    let nonce = if nonce > 0 { read_from_contract(env, nonce, MAX_STRING_SIZE)? } else { vec![] };
+    let amount = if amount > 0 { read_from_contract(env, amount, MAX_IDNA_SIZE)? } else { vec![] };
+
+    deduct_balance_if_needed(env, &amount)?;
+    add_batch_action(env, promise_idx, Action::DeployContract(DeployContractAction {
+        code,
+        nonce,
+        gas_limit: gas_limit as u64,
+        args,
+        deposit: amount,
+    }))?;
+    process_gas_info(env, gas_limit as u64)
+}
+
+fn add_batch_action<B: Backend>(env: &Env<B>, promise_idx: u32, action: Action) -> VmResult<()> {
+    let res = env.add_batch_action(promise_idx as usize, action);
+    process_gas_info(env, res.1)?;
+    Ok(res.0?)
+}
+
 pub fn create_transfer_promise<B: Backend>(env: &Env<B>, addr: u32, amount: u32) -> VmResult<()> {
-    let to = read_region(&env.memory(), addr, MAX_ADDRESS_SIZE)?;
-    let amount = read_region(&env.memory(), amount, MAX_IDNA_SIZE)?;
+    env.check_writable()?;
+    let to = read_from_contract(env, addr, MAX_ADDRESS_SIZE)?;
+    let amount = read_from_contract(env, amount, MAX_IDNA_SIZE)?;
     set_left_gas_to_backend(env)?;
     let (res, gas) = env.backend.deduct_balance(amount.to_vec());
     process_gas_info(env, gas)?;
@@ -326,9 +647,9 @@ pub fn own_addr<B: Backend>(env: &Env<B>) -> VmResult<u32> {
 }
 
 pub fn contract_addr<B: Backend>(env: &Env<B>, code: u32, args: u32, nonce: u32) -> VmResult<u32> {
-    let code = read_region(&env.memory(), code, MAX_CODE_SIZE)?;
-    let args = if args > 0 { read_region(&env.memory(), args, MAX_ARGS_SIZE)? } else { vec![] };
-    let nonce = if nonce > 0 { read_region(&env.memory(), nonce, MAX_STRING_SIZE)? } else { vec![] };
+    let code = read_from_contract(env, code, MAX_CODE_SIZE)?;
+    let args = if args > 0 { read_from_contract(env, args, MAX_ARGS_SIZE)? } else { vec![] };
+    let nonce = if nonce > 0 { read_from_contract(env, nonce, MAX_STRING_SIZE)? } else { vec![] };
 
     set_left_gas_to_backend(env)?;
     let (res, gas) = env.backend.contract_addr(&code, &args, &nonce);
@@ -341,9 +662,9 @@ pub fn contract_addr<B: Backend>(env: &Env<B>, code: u32, args: u32, nonce: u32)
 }
 
 pub fn contract_addr_by_hash<B: Backend>(env: &Env<B>, hash: u32, args: u32, nonce: u32) -> VmResult<u32> {
-    let hash = read_region(&env.memory(), hash, MAX_CODE_SIZE)?;
-    let args = if args > 0 { read_region(&env.memory(), args, MAX_ARGS_SIZE)? } else { vec![] };
-    let nonce = if nonce > 0 { read_region(&env.memory(), nonce, MAX_STRING_SIZE)? } else { vec![] };
+    let hash = read_from_contract(env, hash, MAX_CODE_SIZE)?;
+    let args = if args > 0 { read_from_contract(env, args, MAX_ARGS_SIZE)? } else { vec![] };
+    let nonce = if nonce > 0 { read_from_contract(env, nonce, MAX_STRING_SIZE)? } else { vec![] };
 
     set_left_gas_to_backend(env)?;
     let (res, gas) = env.backend.contract_addr_by_hash(&hash, &args, &nonce);
@@ -379,8 +700,9 @@ pub fn code_hash<B: Backend>(env: &Env<B>) -> VmResult<u32> {
 }
 
 pub fn create_read_contract_data_promise<B: Backend>(env: &Env<B>, addr: u32, key: u32, gas_limit: u32) -> VmResult<u32> {
-    let to = read_region(&env.memory(), addr, MAX_ADDRESS_SIZE)?;
-    let key = read_region(&env.memory(), key, MAX_STORAGE_KEY_SIZE)?;
+    env.check_writable()?;
+    let to = read_from_contract(env, addr, MAX_ADDRESS_SIZE)?;
+    let key = read_from_contract(env, key, MAX_STORAGE_KEY_SIZE)?;
 
     let idx_res = env.create_read_sharded_data_promise(to, ReadShardedDataAction::ReadContractData(ReadContractDataAction {
         key,
@@ -393,7 +715,8 @@ pub fn create_read_contract_data_promise<B: Backend>(env: &Env<B>, addr: u32, ke
 }
 
 pub fn create_get_identity_promise<B: Backend>(env: &Env<B>, addr: u32, gas_limit: u32) -> VmResult<u32> {
-    let to = read_region(&env.memory(), addr, MAX_ADDRESS_SIZE)?;
+    env.check_writable()?;
+    let to = read_from_contract(env, addr, MAX_ADDRESS_SIZE)?;
 
     let idx_res = env.create_read_sharded_data_promise(to.clone(), ReadShardedDataAction::GetIdentity(GetIdentityAction {
         addr: to,
@@ -407,7 +730,7 @@ pub fn create_get_identity_promise<B: Backend>(env: &Env<B>, addr: u32, gas_limi
 
 pub fn bytes_to_hex<B: Backend>(env: &Env<B>, ptr: u32) -> VmResult<u32> {
     set_left_gas_to_backend(env)?;
-    let data = read_region(&env.memory(), ptr, MAX_ARGS_SIZE)?;
+    let data = read_from_contract(env, ptr, MAX_ARGS_SIZE)?;
     let str = hex::encode(&data);
     process_gas_info(env, (data.len() as u64) + BASE_BYTES_TO_HEX_COST)?;
     write_to_contract(&env, str.as_bytes())
@@ -425,7 +748,10 @@ pub fn block_header<B: Backend>(env: &Env<B>, height: u64) -> VmResult<u32> {
 }
 
 pub fn keccak256<B: Backend>(env: &Env<B>, ptr: u32) -> VmResult<u32> {
-    let data = read_region(&env.memory(), ptr, MAX_ARGS_SIZE)?;
+    if env.gas_schedule().has_native_hashes() {
+        return native_hash::<B, Keccak256>(env, ptr);
+    }
+    let data = read_from_contract(env, ptr, MAX_ARGS_SIZE)?;
     set_left_gas_to_backend(env)?;
     let hash = env.backend.keccak256(&data);
     process_gas_info(env, hash.1)?;
@@ -433,6 +759,29 @@ pub fn keccak256<B: Backend>(env: &Env<B>, ptr: u32) -> VmResult<u32> {
     write_to_contract(env, &hash_value)
 }
 
+/// Hashes the region at `ptr` in the VM, the cost only depends on the length of the data.
+fn native_hash<B: Backend, D: Digest>(env: &Env<B>, ptr: u32) -> VmResult<u32> {
+    let data = read_from_contract(env, ptr, MAX_HASH_DATA_SIZE)?;
+    process_gas_info(env, env.gas_schedule().hash_cost(data.len()))?;
+    write_to_contract(env, &D::digest(&data))
+}
+
+pub fn sha256<B: Backend>(env: &Env<B>, ptr: u32) -> VmResult<u32> {
+    native_hash::<B, Sha256>(env, ptr)
+}
+
+pub fn sha3_256<B: Backend>(env: &Env<B>, ptr: u32) -> VmResult<u32> {
+    native_hash::<B, Sha3_256>(env, ptr)
+}
+
+pub fn blake2b_256<B: Backend>(env: &Env<B>, ptr: u32) -> VmResult<u32> {
+    native_hash::<B, Blake2b<U32>>(env, ptr)
+}
+
+pub fn ripemd160<B: Backend>(env: &Env<B>, ptr: u32) -> VmResult<u32> {
+    native_hash::<B, Ripemd160>(env, ptr)
+}
+
 pub fn global_state<B: Backend>(env: &Env<B>) -> VmResult<u32> {
     set_left_gas_to_backend(env)?;
     let global = env.backend.global_state();
@@ -451,7 +800,8 @@ pub fn gas_left<B: Backend>(env: &Env<B>) -> VmResult<u64> {
 }
 
 pub fn burn<B: Backend>(env: &Env<B>, amount: u32) -> VmResult<()> {
-    let amount = read_region(&env.memory(), amount, MAX_IDNA_SIZE)?;
+    env.check_writable()?;
+    let amount = read_from_contract(env, amount, MAX_IDNA_SIZE)?;
     set_left_gas_to_backend(env)?;
     let (res, gas) = env.backend.burn(amount.to_vec());
     process_gas_info(env, gas)?;
@@ -461,8 +811,8 @@ pub fn burn<B: Backend>(env: &Env<B>, amount: u32) -> VmResult<()> {
 
 
 pub fn ecrecover<B: Backend>(env: &Env<B>, data: u32, sig: u32) -> VmResult<u32> {
-    let data = read_region(&env.memory(), data, MAX_ARGS_SIZE)?;
-    let signature = read_region(&env.memory(), sig, MAX_SIGNATURE_SIZE)?;
+    let data = read_from_contract(env, data, MAX_ARGS_SIZE)?;
+    let signature = read_from_contract(env, sig, MAX_SIGNATURE_SIZE)?;
     set_left_gas_to_backend(env)?;
     let pubkey = env.backend.ecrecover(&data, &signature);
     process_gas_info(env, pubkey.1)?;
@@ -473,6 +823,71 @@ pub fn ecrecover<B: Backend>(env: &Env<B>, data: u32, sig: u32) -> VmResult<u32>
     write_to_contract(env, &pb)
 }
 
+/// Returns 1 if `sig` is a signature of the 32-byte `hash` made by `address`, 0 otherwise.
+pub fn verify_secp256k1<B: Backend>(env: &Env<B>, address: u32, hash: u32, sig: u32) -> VmResult<u32> {
+    let address = read_from_contract(env, address, ADDRESS_SIZE)?;
+    let hash = read_from_contract(env, hash, HASH_SIZE)?;
+    let sig = read_from_contract(env, sig, SECP256K1_SIGNATURE_SIZE)?;
+    process_gas_info(env, env.gas_schedule().verify_secp256k1)?;
+    Ok(secp256k1_is_valid(&address, &hash, &sig) as u32)
+}
+
+/// Checks N signatures at once. `entries` are N concatenated `address | hash | sig` tuples laid
+/// out as for `verify_secp256k1`. Returns 1 if all of them are valid, 0 otherwise.
+pub fn verify_secp256k1_batch<B: Backend>(env: &Env<B>, entries: u32) -> VmResult<u32> {
+    let entries = read_from_contract(env, entries, SECP256K1_BATCH_ENTRY_SIZE * MAX_BATCH_SIGNATURES)?;
+    if entries.is_empty() || entries.len() % SECP256K1_BATCH_ENTRY_SIZE != 0 {
+        return Err(VmError::host_misuse("malformed signature batch"));
+    }
+    let count = entries.len() / SECP256K1_BATCH_ENTRY_SIZE;
+    process_gas_info(env, env.gas_schedule().verify_secp256k1.saturating_mul(count as u64))?;
+    let valid = entries.chunks(SECP256K1_BATCH_ENTRY_SIZE).all(|entry| {
+        let (address, rest) = entry.split_at(ADDRESS_SIZE);
+        let (hash, sig) = rest.split_at(HASH_SIZE);
+        secp256k1_is_valid(address, hash, sig)
+    });
+    Ok(valid as u32)
+}
+
+/// Returns 1 if `sig` is a signature of `msg` made by `pubkey`, 0 otherwise.
+pub fn verify_ed25519<B: Backend>(env: &Env<B>, pubkey: u32, msg: u32, sig: u32) -> VmResult<u32> {
+    let pubkey = read_from_contract(env, pubkey, ED25519_PUBKEY_SIZE)?;
+    let msg = read_from_contract(env, msg, MAX_ARGS_SIZE)?;
+    let sig = read_from_contract(env, sig, ED25519_SIGNATURE_SIZE)?;
+    process_gas_info(env, env.gas_schedule().verify_ed25519)?;
+    Ok(ed25519_is_valid(&pubkey, &msg, &sig) as u32)
+}
+
+/// Checks N signatures at once. `entries` are N concatenated `pubkey | sig | len | msg` tuples
+/// with `len` being the little endian u32 length of `msg`. Returns 1 if all of them are valid, 0
+/// otherwise.
+pub fn verify_ed25519_batch<B: Backend>(env: &Env<B>, entries: u32) -> VmResult<u32> {
+    let entries = read_from_contract(env, entries, ED25519_BATCH_HEADER_SIZE * MAX_BATCH_SIGNATURES + MAX_ARGS_SIZE)?;
+    let mut batch = Vec::new();
+    let mut rest = entries.as_slice();
+    while !rest.is_empty() {
+        if rest.len() < ED25519_BATCH_HEADER_SIZE || batch.len() == MAX_BATCH_SIGNATURES {
+            return Err(VmError::host_misuse("malformed signature batch"));
+        }
+        let (pubkey, tail) = rest.split_at(ED25519_PUBKEY_SIZE);
+        let (sig, tail) = tail.split_at(ED25519_SIGNATURE_SIZE);
+        let (len, tail) = tail.split_at(4);
+        let len = u32::from_le_bytes(len.try_into().unwrap()) as usize;
+        if tail.len() < len {
+            return Err(VmError::host_misuse("malformed signature batch"));
+        }
+        let (msg, tail) = tail.split_at(len);
+        batch.push((pubkey, sig, msg));
+        rest = tail;
+    }
+    if batch.is_empty() {
+        return Err(VmError::host_misuse("malformed signature batch"));
+    }
+    process_gas_info(env, env.gas_schedule().verify_ed25519.saturating_mul(batch.len() as u64))?;
+    let valid = batch.iter().all(|(pubkey, sig, msg)| ed25519_is_valid(pubkey, msg, sig));
+    Ok(valid as u32)
+}
+
 fn set_left_gas_to_backend<B: Backend>(env: &Env<B>) -> VmResult<()> {
     let gas_left = env.get_gas_left();
     env.backend.set_remaining_gas(gas_left).0?;
@@ -7,11 +7,25 @@ mod imports;
 mod backend;
 mod go;
 mod gatekeeper;
+mod grow_metering;
 mod proto;
 mod costs;
+mod crypto;
 mod limiting_tunables;
-mod types;
-mod args;
+pub mod types;
+pub mod args;
+#[cfg(test)]
 mod tests;
 mod macros;
+mod cache;
+mod config;
+mod artifact;
+mod deferred_start;
+mod stack_limiter;
+mod signature_alignment;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod validation;
+pub mod abi;
+pub mod estimation;
 
@@ -7,7 +7,7 @@ use wasmer::{
     MemoryType, Pages, TableType, Tunables,
 };
 
-/// A custom tunables that allows you to set a memory limit.
+/// A custom tunables that allows you to set a memory and a table limit.
 ///
 /// After adjusting the memory limits, it delegates all other logic
 /// to the base tunables.
@@ -17,13 +17,15 @@ pub struct LimitingTunables<T: Tunables> {
     /// Since Wasmer ensures there is only none or one memory, this is practically
     /// an upper limit for the guest memory.
     limit: Pages,
+    /// The maximum number of elements of a table.
+    table_limit: u32,
     /// The base implementation we delegate all the logic to
     base: T,
 }
 
 impl<T: Tunables> LimitingTunables<T> {
-    pub fn new(base: T, limit: Pages) -> Self {
-        Self { limit, base }
+    pub fn new(base: T, limit: Pages, table_limit: u32) -> Self {
+        Self { limit, table_limit, base }
     }
 
     /// Takes in input memory type as requested by the guest and sets
@@ -59,6 +61,24 @@ impl<T: Tunables> LimitingTunables<T> {
 
         Ok(())
     }
+
+    /// Sets the table limit as the maximum if it is missing or greater.
+    fn adjust_table(&self, requested: &TableType) -> TableType {
+        let mut adjusted = *requested;
+        if requested.maximum.map_or(true, |max| max > self.table_limit) {
+            adjusted.maximum = Some(self.table_limit);
+        }
+        adjusted
+    }
+
+    /// Ensures the a given table type does not exceed the table limit.
+    /// Call this after adjusting the table.
+    fn validate_table(&self, ty: &TableType) -> Result<(), String> {
+        if ty.minimum > self.table_limit {
+            return Err("Minimum exceeds the allowed table limit".to_string());
+        }
+        Ok(())
+    }
 }
 
 impl<T: Tunables> Tunables for LimitingTunables<T> {
@@ -74,7 +94,8 @@ impl<T: Tunables> Tunables for LimitingTunables<T> {
     ///
     /// Delegated to base.
     fn table_style(&self, table: &TableType) -> TableStyle {
-        self.base.table_style(table)
+        let adjusted = self.adjust_table(table);
+        self.base.table_style(&adjusted)
     }
 
     /// Create a memory owned by the host given a [`MemoryType`] and a [`MemoryStyle`].
@@ -107,37 +128,41 @@ impl<T: Tunables> Tunables for LimitingTunables<T> {
 
     /// Create a table owned by the host given a [`TableType`] and a [`TableStyle`].
     ///
-    /// Delegated to base.
+    /// The requested table type is validated, adjusted to the limited and then passed to base.
     fn create_host_table(
         &self,
         ty: &TableType,
         style: &TableStyle,
     ) -> Result<Arc<dyn vm::Table>, String> {
-        self.base.create_host_table(ty, style)
+        let adjusted = self.adjust_table(ty);
+        self.validate_table(&adjusted)?;
+        self.base.create_host_table(&adjusted, style)
     }
 
     /// Create a table owned by the VM given a [`TableType`] and a [`TableStyle`].
     ///
-    /// Delegated to base.
+    /// The requested table type is validated, adjusted to the limited and then passed to base.
     unsafe fn create_vm_table(
         &self,
         ty: &TableType,
         style: &TableStyle,
         vm_definition_location: NonNull<VMTableDefinition>,
     ) -> Result<Arc<dyn vm::Table>, String> {
-        self.base.create_vm_table(ty, style, vm_definition_location)
+        let adjusted = self.adjust_table(ty);
+        self.validate_table(&adjusted)?;
+        self.base.create_vm_table(&adjusted, style, vm_definition_location)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use wasmer::{BaseTunables, Target};
+    use wasmer::{BaseTunables, Target, Type};
 
     #[test]
     fn adjust_memory_works() {
         let limit = Pages(12);
-        let limiting = LimitingTunables::new(BaseTunables::for_target(&Target::default()), limit);
+        let limiting = LimitingTunables::new(BaseTunables::for_target(&Target::default()), limit, 100);
 
         // No maximum
         let requested = MemoryType::new(3, None, true);
@@ -173,7 +198,7 @@ mod tests {
     #[test]
     fn validate_memory_works() {
         let limit = Pages(12);
-        let limiting = LimitingTunables::new(BaseTunables::for_target(&Target::default()), limit);
+        let limiting = LimitingTunables::new(BaseTunables::for_target(&Target::default()), limit, 100);
 
         // Maximum smaller than limit
         let memory = MemoryType::new(3, Some(7), true);
@@ -215,4 +240,27 @@ mod tests {
             err => panic!("Unexpected error: {:?}", err),
         }
     }
+
+    #[test]
+    fn tables_are_limited() {
+        let limiting = LimitingTunables::new(BaseTunables::for_target(&Target::default()), Pages(12), 100);
+
+        // No maximum
+        let requested = TableType::new(Type::FuncRef, 10, None);
+        assert_eq!(limiting.adjust_table(&requested), TableType::new(Type::FuncRef, 10, Some(100)));
+
+        // Maximum greater than limit
+        let requested = TableType::new(Type::FuncRef, 10, Some(1000));
+        assert_eq!(limiting.adjust_table(&requested), TableType::new(Type::FuncRef, 10, Some(100)));
+
+        // Maximum smaller than limit
+        let requested = TableType::new(Type::FuncRef, 10, Some(50));
+        assert_eq!(limiting.adjust_table(&requested), requested);
+        limiting.validate_table(&requested).unwrap();
+
+        // Minimum greater than limit
+        let requested = TableType::new(Type::FuncRef, 200, None);
+        let result = limiting.validate_table(&limiting.adjust_table(&requested));
+        assert_eq!(result.unwrap_err(), "Minimum exceeds the allowed table limit");
+    }
 }
@@ -27,6 +27,7 @@ macro_rules! unwrap_or_action_res {
             Ok(x) => x,
             Err(err) => return ActionResult {
             error: err.to_string(),
+            error_code: err.code(),
             success: false,
             gas_used: $gas_used,
             remaining_gas: $gas_limit.saturating_sub($gas_used),
@@ -34,6 +35,9 @@ macro_rules! unwrap_or_action_res {
             sub_action_results: vec![],
             output_data: vec![],
             contract : $contract,
+            write_set: vec![],
+            events: vec![],
+            logs: vec![],
         },
         }
     }
@@ -28,22 +28,27 @@ unsafe impl ValueType for Region {}
 /// Errors with a cosmwasm_vm::errors::VmError::ConversionErr if conversion cannot be done.
 pub fn ref_to_u32<T: TryInto<u32> + ToString + Clone>(input: &T) -> VmResult<u32> {
     input.clone().try_into().map_err(|_| {
-        VmError::custom(format!("Couldn't convert from {} to {}. Input: {}", type_name::<T>(), type_name::<u32>(), input.to_string()))
+        VmError::host_misuse(format!("Couldn't convert from {} to {}. Input: {}", type_name::<T>(), type_name::<u32>(), input.to_string()))
     })
 }
 
 pub fn to_u32<T: std::convert::TryInto<u32> + ToString + Copy>(input: T) -> VmResult<u32> {
     input.try_into().map_err(|_| {
-        VmError::custom("conversion err")
+        VmError::host_misuse("conversion err")
     })
 }
 
+/// Returns the number of bytes `read_region` would copy from the Region at ptr.
+pub fn region_length(memory: &wasmer::Memory, ptr: u32) -> VmResult<usize> {
+    Ok(get_region(memory, ptr)?.length as usize)
+}
+
 pub fn read_region(memory: &wasmer::Memory, ptr: u32, max_length: usize) -> VmResult<Vec<u8>> {
     let region = get_region(memory, ptr)?;
 
     if region.length > to_u32(max_length)? {
         return Err(
-            VmError::custom(format!("region_length_too_big: ptr={} expected max = {}, actual={}", ptr, max_length, region.length))
+            VmError::host_misuse(format!("region_length_too_big: ptr={} expected max = {}, actual={}", ptr, max_length, region.length))
         );
     }
 
@@ -58,7 +63,7 @@ pub fn read_region(memory: &wasmer::Memory, ptr: u32, max_length: usize) -> VmRe
             }
             Ok(result)
         }
-        None => Err(VmError::custom(format!(
+        None => Err(VmError::host_misuse(format!(
             "Tried to access memory of region {:?} in wasm memory of size {} bytes. This typically happens when the given Region pointer does not point to a proper Region struct.",
             region,
             memory.size().bytes().0
@@ -75,7 +80,7 @@ pub fn write_region(memory: &wasmer::Memory, ptr: u32, data: &[u8]) -> VmResult<
 
     let region_capacity = region.capacity as usize;
     if data.len() > region_capacity {
-        return Err(VmError::custom("region_too_small"));
+        return Err(VmError::host_misuse("region_too_small"));
     }
     match WasmPtr::<u8, Array>::new(region.offset).deref(memory, 0, region.capacity) {
         Some(cells) => {
@@ -88,7 +93,7 @@ pub fn write_region(memory: &wasmer::Memory, ptr: u32, data: &[u8]) -> VmResult<
             set_region(memory, ptr, region)?;
             Ok(())
         }
-        None => Err(VmError::custom(format!(
+        None => Err(VmError::host_misuse(format!(
             "Tried to access memory of region {:?} in wasm memory of size {} bytes. This typically happens when the given Region pointer does not point to a proper Region struct.",
             region,
             memory.size().bytes().0
@@ -105,7 +110,7 @@ fn get_region(memory: &wasmer::Memory, ptr: u32) -> CommunicationResult<Region>
             validate_region(&region)?;
             Ok(region)
         }
-        None => Err(VmError::custom("Could not dereference this pointer to a Region"))
+        None => Err(VmError::host_misuse("Could not dereference this pointer to a Region"))
     }
 }
 
@@ -115,7 +120,7 @@ pub fn read_u32(memory: &wasmer::Memory, ptr: u32) -> CommunicationResult<u32> {
         Some(cell) => {
             Ok(cell.get())
         }
-        None => Err(VmError::custom("Could not dereference this pointer to u32"))
+        None => Err(VmError::host_misuse("Could not dereference this pointer to u32"))
     }
 }
 
@@ -151,7 +156,7 @@ pub fn read_utf16_string(memory: &wasmer::Memory, ptr: u32, len: u32) -> Communi
         Some(v) => {
             Ok(v)
         }
-        None => Err(VmError::custom("Could not dereference this pointer to [u8]"))
+        None => Err(VmError::host_misuse("Could not dereference this pointer to [u8]"))
     }
 }
 
@@ -160,13 +165,13 @@ pub fn read_utf16_string(memory: &wasmer::Memory, ptr: u32, len: u32) -> Communi
 /// contract and this can be used to detect problems in the standard library of the contract.
 fn validate_region(region: &Region) -> RegionValidationResult<()> {
     if region.offset == 0 {
-        return Err(VmError::custom("zero offset"));
+        return Err(VmError::host_misuse("zero offset"));
     }
     if region.length > region.capacity {
-        return Err(VmError::custom("length > capacity"));
+        return Err(VmError::host_misuse("length > capacity"));
     }
     if region.capacity > (u32::MAX - region.offset) {
-        return Err(VmError::custom("out of range"));
+        return Err(VmError::host_misuse("out of range"));
     }
     Ok(())
 }
@@ -180,7 +185,7 @@ fn set_region(memory: &wasmer::Memory, ptr: u32, data: Region) -> CommunicationR
             cell.set(data);
             Ok(())
         }
-        None => Err(VmError::custom(
+        None => Err(VmError::host_misuse(
             "Could not dereference this pointer to a Region"
         )),
     }
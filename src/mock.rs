@@ -0,0 +1,617 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, Mutex};
+
+use protobuf::Message;
+use sha3::{Digest, Keccak256};
+
+use crate::backend::{Backend, BackendError, BackendResult};
+use crate::config::{vm_config, VmConfig, VM_CONFIG_VERSION};
+use crate::costs::{gas_schedule, GasSchedule, GAS_SCHEDULE_VERSION};
+use crate::proto::models::InvocationContext as protoContext;
+use crate::runner::VmRunner;
+use crate::types::{idna_to_u128, u128_to_idna, ActionResult, Address, InvocationContext, LogRecord, IDNA};
+use crate::unwrap_or_return;
+
+/// Gas charged by [`MockBackend`] for host calls.
+#[derive(Clone, Debug)]
+pub struct MockGasCosts {
+    /// Cost of every call without a dedicated price.
+    pub base: u64,
+    pub read_storage: u64,
+    pub write_storage: u64,
+    pub remove_storage: u64,
+    /// Charged on top for each byte of keys, values and other data passed to the backend.
+    pub per_byte: u64,
+    pub event: u64,
+    pub transfer: u64,
+}
+
+impl Default for MockGasCosts {
+    fn default() -> Self {
+        MockGasCosts {
+            base: 10,
+            read_storage: 100,
+            write_storage: 200,
+            remove_storage: 100,
+            per_byte: 1,
+            event: 100,
+            transfer: 100,
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct MockContract {
+    pub code: Vec<u8>,
+    pub storage: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MockEvent {
+    pub contract: Address,
+    pub name: Vec<u8>,
+    pub args: Vec<u8>,
+}
+
+/// Context of a single contract execution.
+#[derive(Clone, Debug, Default)]
+pub struct MockFrame {
+    pub contract: Address,
+    pub caller: Address,
+    pub original_caller: Address,
+    pub pay_amount: IDNA,
+}
+
+/// The whole chain state seen by [`MockBackend`].
+#[derive(Clone, Debug, Default)]
+pub struct MockState {
+    pub block_number: u64,
+    pub block_timestamp: i64,
+    pub block_seed: Vec<u8>,
+    pub epoch: u16,
+    pub network_size: u64,
+    pub min_fee_per_gas: IDNA,
+    pub global_state: Vec<u8>,
+    pub identities: HashMap<Address, Vec<u8>>,
+    pub block_headers: HashMap<u64, Vec<u8>>,
+    pub balances: HashMap<Address, u128>,
+    pub contracts: HashMap<Address, MockContract>,
+    pub events: Vec<MockEvent>,
+    /// Log of the executions run without the debug mode, kept when they are reverted.
+    pub logs: Vec<LogRecord>,
+    pub burnt: u128,
+    pub artifacts: HashMap<Vec<u8>, Vec<u8>>,
+    /// Gas left as reported by the VM before each host call.
+    pub remaining_gas: u64,
+    pub gas: MockGasCosts,
+    /// Gas schedule of nested executions, `None` means the latest one.
+    pub gas_schedule_version: Option<u32>,
+    /// VM config of nested executions, `None` means the latest one.
+    pub vm_config_version: Option<u32>,
+    pub is_debug: bool,
+    /// Executions in progress, the last one is the current contract.
+    pub call_stack: Vec<MockFrame>,
+    /// States remembered by `Backend::snapshot` without their own snapshots, the id of a snapshot
+    /// is its index.
+    pub snapshots: Vec<MockState>,
+}
+
+impl MockState {
+    pub fn balance_of(&self, addr: &[u8]) -> u128 {
+        self.balances.get(addr).copied().unwrap_or_default()
+    }
+
+    pub fn storage_of(&self, addr: &[u8]) -> Option<&BTreeMap<Vec<u8>, Vec<u8>>> {
+        self.contracts.get(addr).map(|contract| &contract.storage)
+    }
+
+    fn frame(&self) -> Result<&MockFrame, BackendError> {
+        self.call_stack.last().ok_or_else(|| BackendError::new("no contract is executed"))
+    }
+
+    fn contract_mut(&mut self) -> Result<&mut MockContract, BackendError> {
+        let addr = self.frame()?.contract.clone();
+        Ok(self.contracts.entry(addr).or_default())
+    }
+
+    fn deduct(&mut self, addr: &[u8], amount: u128) -> Result<(), BackendError> {
+        let balance = self.balance_of(addr);
+        if balance < amount {
+            return Err(BackendError::new("insufficient balance"));
+        }
+        self.balances.insert(addr.to_vec(), balance - amount);
+        Ok(())
+    }
+
+    fn add(&mut self, addr: &[u8], amount: u128) {
+        let balance = self.balance_of(addr).saturating_add(amount);
+        self.balances.insert(addr.to_vec(), balance);
+    }
+
+    fn gas_schedule(&self) -> Result<&'static GasSchedule, BackendError> {
+        gas_schedule(self.gas_schedule_version.unwrap_or(GAS_SCHEDULE_VERSION))
+            .ok_or_else(|| BackendError::new("unknown gas schedule version"))
+    }
+
+    fn vm_config(&self) -> Result<&'static VmConfig, BackendError> {
+        vm_config(self.vm_config_version.unwrap_or(VM_CONFIG_VERSION))
+            .ok_or_else(|| BackendError::new("unknown vm config version"))
+    }
+}
+
+/// A contract execution requested from [`MockBackend`].
+enum MockExecution<'a> {
+    Call { method: &'a str, args: &'a [u8], ctx: InvocationContext },
+    Deploy { args: &'a [u8] },
+}
+
+pub fn keccak256(data: &[u8]) -> Vec<u8> {
+    Keccak256::digest(data).to_vec()
+}
+
+/// An in-memory `Backend` for testing contracts and the VM without a node.
+///
+/// The backend is a handle to the state, its clones held by runners and environments share it.
+#[derive(Clone)]
+pub struct MockBackend {
+    state: Arc<Mutex<MockState>>,
+}
+
+impl MockBackend {
+    /// Creates a backend executing `contract` on behalf of `caller`.
+    pub fn new(mut state: MockState, contract: Address, caller: Address) -> Self {
+        state.call_stack.push(MockFrame {
+            contract,
+            original_caller: caller.clone(),
+            caller,
+            pay_amount: vec![],
+        });
+        MockBackend {
+            state: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    pub fn with_state<C, R>(&self, callback: C) -> R
+        where
+            C: FnOnce(&mut MockState) -> R,
+    {
+        let mut guard = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        callback(&mut guard)
+    }
+
+    fn gas<C>(&self, callback: C) -> u64
+        where
+            C: FnOnce(&MockGasCosts) -> u64,
+    {
+        self.with_state(|state| callback(&state.gas))
+    }
+
+    fn per_byte(&self, data_len: usize) -> u64 {
+        self.gas(|gas| gas.per_byte.saturating_mul(data_len as u64))
+    }
+
+    fn base_gas(&self) -> u64 {
+        self.gas(|gas| gas.base)
+    }
+
+    /// Calls a deployed contract on behalf of an account, as a transaction would.
+    pub fn run_call(&self, caller: Address, contract: Address, method: &str, args: &[u8], amount: IDNA, gas_limit: u64) -> Result<ActionResult, BackendError> {
+        let frame = MockFrame {
+            contract,
+            original_caller: caller.clone(),
+            caller,
+            pay_amount: amount,
+        };
+        let execution = MockExecution::Call { method, args, ctx: InvocationContext::default() };
+        self.run(frame, true, None, execution, gas_limit)
+    }
+
+    /// Deploys a contract on behalf of an account, as a transaction would. The address of the new
+    /// contract is returned in `ActionResult::contract`.
+    pub fn run_deploy(&self, caller: Address, code: &[u8], args: &[u8], nonce: &[u8], amount: IDNA, gas_limit: u64) -> Result<ActionResult, BackendError> {
+        let frame = MockFrame {
+            contract: self.contract_addr(code, args, nonce).0?,
+            original_caller: caller.clone(),
+            caller,
+            pay_amount: amount,
+        };
+        self.run(frame, true, Some(code), MockExecution::Deploy { args }, gas_limit)
+    }
+
+    /// Executes a contract in a new frame through `VmRunner`. All changes made by a failed
+    /// execution, including the transferred amount, are reverted.
+    ///
+    /// If `charge_caller` is set, the amount is taken from the caller. Otherwise it was already
+    /// deducted by the contract creating the promise.
+    fn run(&self, frame: MockFrame, charge_caller: bool, deploy_code: Option<&[u8]>, execution: MockExecution, gas_limit: u64) -> Result<ActionResult, BackendError> {
+        // The node rejects these before reaching the runner as well.
+        let args = match &execution {
+            MockExecution::Call { args, .. } | MockExecution::Deploy { args } => args,
+        };
+        if args.is_empty() {
+            return Err(BackendError::new("invalid arguments format"));
+        }
+        let (snapshot, code, gas_schedule, vm_config, is_debug) = self.with_state(|state| {
+            // Everything that can fail is checked before the state is touched.
+            let amount = idna_to_u128(&frame.pay_amount)?;
+            let gas_schedule = state.gas_schedule()?;
+            let vm_config = state.vm_config()?;
+            let code = match deploy_code {
+                Some(_) if state.contracts.contains_key(&frame.contract) => {
+                    return Err(BackendError::new("contract already exists"));
+                }
+                Some(code) => code.to_vec(),
+                None => match state.contracts.get(&frame.contract) {
+                    Some(contract) => contract.code.clone(),
+                    None => return Err(BackendError::new("contract is not found")),
+                },
+            };
+
+            let snapshot = state.clone();
+            // The first change, it leaves the state intact if it fails.
+            if charge_caller {
+                state.deduct(&frame.caller, amount)?;
+            }
+            if deploy_code.is_some() {
+                state.contracts.insert(frame.contract.clone(), MockContract {
+                    code: code.clone(),
+                    storage: BTreeMap::new(),
+                });
+            }
+            state.add(&frame.contract, amount);
+            state.call_stack.push(frame.clone());
+            Ok((snapshot, code, gas_schedule, vm_config, state.is_debug))
+        })?;
+
+        let mut gas_used = 0;
+        let result = match execution {
+            MockExecution::Call { method, args, ctx } => {
+                VmRunner::new(self.clone(), frame.contract, gas_limit, gas_schedule, vm_config, Some(ctx), is_debug)
+                    .execute(code, &method.to_string(), args, &mut gas_used)
+            }
+            MockExecution::Deploy { args } => {
+                VmRunner::new(self.clone(), frame.contract, gas_limit, gas_schedule, vm_config, None, is_debug)
+                    .deploy(code, args, &mut gas_used)
+            }
+        };
+
+        self.with_state(|state| {
+            if result.success {
+                state.call_stack.pop();
+            } else {
+                let logs = std::mem::take(&mut state.logs);
+                *state = snapshot;
+                state.logs = logs;
+            }
+        });
+        Ok(result)
+    }
+}
+
+impl Backend for MockBackend {
+    fn set_remaining_gas(&self, gas_limit: u64) -> BackendResult<()> {
+        self.with_state(|state| state.remaining_gas = gas_limit);
+        (Ok(()), 0)
+    }
+
+    fn set_storage(&self, key: Vec<u8>, value: Vec<u8>) -> BackendResult<()> {
+        let gas = self.gas(|gas| gas.write_storage) + self.per_byte(key.len() + value.len());
+        let res = self.with_state(|state| {
+            state.contract_mut()?.storage.insert(key, value);
+            Ok(())
+        });
+        (res, gas)
+    }
+
+    fn get_storage(&self, key: Vec<u8>) -> BackendResult<Option<Vec<u8>>> {
+        let res = self.with_state(|state| Ok(state.contract_mut()?.storage.get(&key).cloned()));
+        let value_len = match &res {
+            Ok(Some(value)) => value.len(),
+            _ => 0,
+        };
+        (res, self.gas(|gas| gas.read_storage) + self.per_byte(key.len() + value_len))
+    }
+
+    fn remove_storage(&self, key: Vec<u8>) -> BackendResult<()> {
+        let gas = self.gas(|gas| gas.remove_storage) + self.per_byte(key.len());
+        let res = self.with_state(|state| {
+            state.contract_mut()?.storage.remove(&key);
+            Ok(())
+        });
+        (res, gas)
+    }
+
+    fn block_timestamp(&self) -> BackendResult<i64> {
+        (Ok(self.with_state(|state| state.block_timestamp)), self.base_gas())
+    }
+
+    fn block_number(&self) -> BackendResult<u64> {
+        (Ok(self.with_state(|state| state.block_number)), self.base_gas())
+    }
+
+    fn min_fee_per_gas(&self) -> BackendResult<IDNA> {
+        (Ok(self.with_state(|state| state.min_fee_per_gas.clone())), self.base_gas())
+    }
+
+    fn balance(&self) -> BackendResult<IDNA> {
+        let res = self.with_state(|state| {
+            let addr = &state.frame()?.contract;
+            Ok(u128_to_idna(state.balance_of(addr)))
+        });
+        (res, self.base_gas())
+    }
+
+    fn block_seed(&self) -> BackendResult<Vec<u8>> {
+        (Ok(self.with_state(|state| state.block_seed.clone())), self.base_gas())
+    }
+
+    fn network_size(&self) -> BackendResult<u64> {
+        (Ok(self.with_state(|state| state.network_size)), self.base_gas())
+    }
+
+    fn burn(&self, amount: IDNA) -> BackendResult<()> {
+        let res = self.with_state(|state| {
+            let amount = idna_to_u128(&amount)?;
+            let addr = state.frame()?.contract.clone();
+            state.deduct(&addr, amount)?;
+            state.burnt = state.burnt.saturating_add(amount);
+            Ok(())
+        });
+        (res, self.gas(|gas| gas.transfer))
+    }
+
+    fn read_contract_data(&self, addr: Address, key: Vec<u8>) -> BackendResult<Option<Vec<u8>>> {
+        let res = self.with_state(|state| state.storage_of(&addr).and_then(|storage| storage.get(&key).cloned()));
+        let value_len = res.as_ref().map(|value| value.len()).unwrap_or_default();
+        (Ok(res), self.gas(|gas| gas.read_storage) + self.per_byte(key.len() + value_len))
+    }
+
+    fn epoch(&self) -> BackendResult<u16> {
+        (Ok(self.with_state(|state| state.epoch)), self.base_gas())
+    }
+
+    fn identity(&self, addr: Address) -> BackendResult<Option<Vec<u8>>> {
+        (Ok(self.with_state(|state| state.identities.get(&addr).cloned())), self.base_gas())
+    }
+
+    fn call(&self, addr: Address, method: &[u8], args: &[u8], amount: &[u8], gas_limit: u64, invocation_ctx: &[u8]) -> BackendResult<ActionResult> {
+        let caller = match self.with_state(|state| state.frame().cloned()) {
+            Ok(frame) => frame,
+            Err(err) => return (Err(err), 0),
+        };
+        let mut ctx = InvocationContext::default();
+        if !invocation_ctx.is_empty() {
+            ctx = protoContext::parse_from_bytes(invocation_ctx).unwrap_or_default().into();
+        }
+        let frame = MockFrame {
+            contract: addr,
+            caller: caller.contract,
+            original_caller: caller.original_caller,
+            pay_amount: amount.to_vec(),
+        };
+        let method = String::from_utf8_lossy(method);
+        let execution = MockExecution::Call { method: &method, args, ctx };
+        match self.run(frame, false, None, execution, gas_limit) {
+            Ok(res) => {
+                let gas_used = res.gas_used;
+                (Ok(res), gas_used)
+            }
+            Err(err) => (Err(err), 0),
+        }
+    }
+
+    fn caller(&self) -> BackendResult<Vec<u8>> {
+        (self.with_state(|state| Ok(state.frame()?.caller.clone())), self.base_gas())
+    }
+
+    fn original_caller(&self) -> BackendResult<Vec<u8>> {
+        (self.with_state(|state| Ok(state.frame()?.original_caller.clone())), self.base_gas())
+    }
+
+    fn deduct_balance(&self, amount: IDNA) -> BackendResult<()> {
+        let res = self.with_state(|state| {
+            let amount = idna_to_u128(&amount)?;
+            let addr = state.frame()?.contract.clone();
+            state.deduct(&addr, amount)
+        });
+        (res, self.gas(|gas| gas.transfer))
+    }
+
+    fn add_balance(&self, to: Address, amount: IDNA) -> BackendResult<()> {
+        let res = idna_to_u128(&amount).map(|amount| self.with_state(|state| state.add(&to, amount)));
+        (res, self.gas(|gas| gas.transfer))
+    }
+
+    fn own_addr(&self) -> BackendResult<Address> {
+        (self.with_state(|state| Ok(state.frame()?.contract.clone())), self.base_gas())
+    }
+
+    fn contract_addr(&self, code: &[u8], args: &[u8], nonce: &[u8]) -> BackendResult<Address> {
+        self.contract_addr_by_hash(&keccak256(code), args, nonce)
+    }
+
+    fn deploy(&self, code: &[u8], args: &[u8], nonce: &[u8], amount: &[u8], gas_limit: u64) -> BackendResult<ActionResult> {
+        let caller = match self.with_state(|state| state.frame().cloned()) {
+            Ok(frame) => frame,
+            Err(err) => return (Err(err), 0),
+        };
+        let (addr, gas) = self.contract_addr(code, args, nonce);
+        let addr = unwrap_or_return!(addr, gas);
+        let frame = MockFrame {
+            contract: addr,
+            caller: caller.contract,
+            original_caller: caller.original_caller,
+            pay_amount: amount.to_vec(),
+        };
+        match self.run(frame, false, Some(code), MockExecution::Deploy { args }, gas_limit) {
+            Ok(res) => {
+                let gas_used = res.gas_used;
+                (Ok(res), gas_used)
+            }
+            Err(err) => (Err(err), gas),
+        }
+    }
+
+    fn contract_addr_by_hash(&self, hash: &[u8], args: &[u8], nonce: &[u8]) -> BackendResult<Address> {
+        let data = [hash, args, nonce].concat();
+        let gas = self.base_gas() + self.per_byte(data.len());
+        (Ok(keccak256(&data)[12..].to_vec()), gas)
+    }
+
+    fn own_code(&self) -> BackendResult<Vec<u8>> {
+        let res = self.with_state(|state| Ok(state.contract_mut()?.code.clone()));
+        let code_len = res.as_ref().map(|code| code.len()).unwrap_or_default();
+        (res, self.base_gas() + self.per_byte(code_len))
+    }
+
+    fn code_hash(&self) -> BackendResult<Vec<u8>> {
+        (self.with_state(|state| Ok(keccak256(&state.contract_mut()?.code))), self.base_gas())
+    }
+
+    fn event(&self, event_name: &[u8], args: &[u8]) -> BackendResult<()> {
+        let gas = self.gas(|gas| gas.event) + self.per_byte(event_name.len() + args.len());
+        let res = self.with_state(|state| {
+            let contract = state.frame()?.contract.clone();
+            state.events.push(MockEvent {
+                contract,
+                name: event_name.to_vec(),
+                args: args.to_vec(),
+            });
+            Ok(())
+        });
+        (res, gas)
+    }
+
+    fn pay_amount(&self) -> BackendResult<IDNA> {
+        (self.with_state(|state| Ok(state.frame()?.pay_amount.clone())), self.base_gas())
+    }
+
+    fn block_header(&self, height: u64) -> BackendResult<Option<Vec<u8>>> {
+        (Ok(self.with_state(|state| state.block_headers.get(&height).cloned())), self.base_gas())
+    }
+
+    fn keccak256(&self, data: &[u8]) -> BackendResult<Vec<u8>> {
+        (Ok(keccak256(data)), self.base_gas() + self.per_byte(data.len()))
+    }
+
+    fn global_state(&self) -> BackendResult<Vec<u8>> {
+        (Ok(self.with_state(|state| state.global_state.clone())), self.base_gas())
+    }
+
+    fn ecrecover(&self, _data: &[u8], _sig: &[u8]) -> BackendResult<Vec<u8>> {
+        (Err(BackendError::new("ecrecover is not supported by the mock backend")), self.base_gas())
+    }
+
+    fn store_artifact(&self, code_hash: &[u8], artifact: &[u8]) -> BackendResult<()> {
+        self.with_state(|state| state.artifacts.insert(code_hash.to_vec(), artifact.to_vec()));
+        (Ok(()), 0)
+    }
+
+    fn load_artifact(&self, code_hash: &[u8]) -> BackendResult<Option<Vec<u8>>> {
+        (Ok(self.with_state(|state| state.artifacts.get(code_hash).cloned())), 0)
+    }
+
+    fn log(&self, record: &LogRecord) -> BackendResult<()> {
+        self.with_state(|state| state.logs.push(record.clone()));
+        (Ok(()), 0)
+    }
+
+    fn snapshot(&self) -> BackendResult<u32> {
+        let id = self.with_state(|state| {
+            let mut snapshots = std::mem::take(&mut state.snapshots);
+            snapshots.push(state.clone());
+            state.snapshots = snapshots;
+            state.snapshots.len() as u32 - 1
+        });
+        (Ok(id), self.base_gas())
+    }
+
+    fn revert_to_snapshot(&self, id: u32) -> BackendResult<()> {
+        let res = self.with_state(|state| {
+            if id as usize >= state.snapshots.len() {
+                return Err(BackendError::new("unknown snapshot"));
+            }
+            let mut snapshots = std::mem::take(&mut state.snapshots);
+            let logs = std::mem::take(&mut state.logs);
+            *state = snapshots.swap_remove(id as usize);
+            snapshots.truncate(id as usize);
+            state.snapshots = snapshots;
+            state.logs = logs;
+            Ok(())
+        });
+        (res, self.base_gas())
+    }
+
+    fn contract_code(&self, addr: Address) -> BackendResult<Option<Vec<u8>>> {
+        let code = self.with_state(|state| state.contracts.get(&addr).map(|contract| contract.code.clone()));
+        let code_len = code.as_ref().map(|code| code.len()).unwrap_or_default();
+        (Ok(code), self.base_gas() + self.per_byte(code_len))
+    }
+
+    fn balance_of(&self, addr: Address) -> BackendResult<IDNA> {
+        (Ok(self.with_state(|state| u128_to_idna(state.balance_of(&addr)))), self.base_gas())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn backend() -> MockBackend {
+        let mut state = MockState::default();
+        state.balances.insert(b"contract".to_vec(), 100);
+        MockBackend::new(state, b"contract".to_vec(), b"caller".to_vec())
+    }
+
+    #[test]
+    fn idna_conversion() {
+        assert_eq!(u128_to_idna(0), Vec::<u8>::new());
+        assert_eq!(u128_to_idna(0x0102), vec![1, 2]);
+        assert_eq!(idna_to_u128(&[0, 0, 1, 2]).unwrap(), 0x0102);
+        assert_eq!(idna_to_u128(&[]).unwrap(), 0);
+        assert!(idna_to_u128(&[1; 17]).is_err());
+    }
+
+    #[test]
+    fn storage_is_per_contract() {
+        let backend = backend();
+        let (res, gas) = backend.set_storage(b"key".to_vec(), b"value".to_vec());
+        assert!(res.is_ok());
+        assert_eq!(gas, 200 + 8);
+        assert_eq!(backend.get_storage(b"key".to_vec()).0.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(backend.read_contract_data(b"contract".to_vec(), b"key".to_vec()).0.unwrap(), Some(b"value".to_vec()));
+        assert_eq!(backend.read_contract_data(b"other".to_vec(), b"key".to_vec()).0.unwrap(), None);
+
+        assert!(backend.remove_storage(b"key".to_vec()).0.is_ok());
+        assert_eq!(backend.get_storage(b"key".to_vec()).0.unwrap(), None);
+    }
+
+    #[test]
+    fn balances() {
+        let backend = backend();
+        assert!(backend.deduct_balance(u128_to_idna(30)).0.is_ok());
+        assert!(backend.burn(u128_to_idna(20)).0.is_ok());
+        assert!(backend.deduct_balance(u128_to_idna(51)).0.is_err());
+        assert!(backend.add_balance(b"receiver".to_vec(), u128_to_idna(30)).0.is_ok());
+        assert!(backend.add_balance(b"receiver".to_vec(), vec![1; 17]).0.is_err());
+
+        assert_eq!(backend.balance().0.unwrap(), u128_to_idna(50));
+        backend.with_state(|state| {
+            assert_eq!(state.balance_of(b"receiver"), 30);
+            assert_eq!(state.burnt, 20);
+        });
+    }
+
+    #[test]
+    fn events_are_logged() {
+        let backend = backend();
+        assert!(backend.event(b"transfer", b"args").0.is_ok());
+        backend.with_state(|state| {
+            assert_eq!(state.events, vec![MockEvent {
+                contract: b"contract".to_vec(),
+                name: b"transfer".to_vec(),
+                args: b"args".to_vec(),
+            }]);
+        });
+    }
+}
@@ -194,6 +194,7 @@ pub struct ProtoArgs_Argument {
     // message fields
     pub value: ::std::vec::Vec<u8>,
     pub is_nil: bool,
+    pub field_type: ArgType,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
@@ -250,6 +251,21 @@ impl ProtoArgs_Argument {
     pub fn set_is_nil(&mut self, v: bool) {
         self.is_nil = v;
     }
+
+    // .models.ArgType type = 3;
+
+
+    pub fn get_field_type(&self) -> ArgType {
+        self.field_type
+    }
+    pub fn clear_field_type(&mut self) {
+        self.field_type = ArgType::ARG_UNTYPED;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_field_type(&mut self, v: ArgType) {
+        self.field_type = v;
+    }
 }
 
 impl ::protobuf::Message for ProtoArgs_Argument {
@@ -271,6 +287,9 @@ impl ::protobuf::Message for ProtoArgs_Argument {
                     let tmp = is.read_bool()?;
                     self.is_nil = tmp;
                 },
+                3 => {
+                    ::protobuf::rt::read_proto3_enum_with_unknown_fields_into(wire_type, is, &mut self.field_type, 3, &mut self.unknown_fields)?
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -289,6 +308,9 @@ impl ::protobuf::Message for ProtoArgs_Argument {
         if self.is_nil != false {
             my_size += 2;
         }
+        if self.field_type != ArgType::ARG_UNTYPED {
+            my_size += ::protobuf::rt::enum_size(3, self.field_type);
+        }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -301,6 +323,9 @@ impl ::protobuf::Message for ProtoArgs_Argument {
         if self.is_nil != false {
             os.write_bool(2, self.is_nil)?;
         }
+        if self.field_type != ArgType::ARG_UNTYPED {
+            os.write_enum(3, ::protobuf::ProtobufEnum::value(&self.field_type))?;
+        }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -349,6 +374,11 @@ impl ::protobuf::Message for ProtoArgs_Argument {
                 |m: &ProtoArgs_Argument| { &m.is_nil },
                 |m: &mut ProtoArgs_Argument| { &mut m.is_nil },
             ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeEnum<ArgType>>(
+                "type",
+                |m: &ProtoArgs_Argument| { &m.field_type },
+                |m: &mut ProtoArgs_Argument| { &mut m.field_type },
+            ));
             ::protobuf::reflect::MessageDescriptor::new_pb_name::<ProtoArgs_Argument>(
                 "ProtoArgs.Argument",
                 fields,
@@ -367,6 +397,7 @@ impl ::protobuf::Clear for ProtoArgs_Argument {
     fn clear(&mut self) {
         self.value.clear();
         self.is_nil = false;
+        self.field_type = ArgType::ARG_UNTYPED;
         self.unknown_fields.clear();
     }
 }
@@ -394,6 +425,7 @@ pub struct Action {
     pub code: ::std::vec::Vec<u8>,
     pub nonce: ::std::vec::Vec<u8>,
     pub key: ::std::vec::Vec<u8>,
+    pub actions: ::protobuf::RepeatedField<Action>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
@@ -595,10 +627,40 @@ impl Action {
     pub fn take_key(&mut self) -> ::std::vec::Vec<u8> {
         ::std::mem::replace(&mut self.key, ::std::vec::Vec::new())
     }
+
+    // repeated .models.Action actions = 9;
+
+
+    pub fn get_actions(&self) -> &[Action] {
+        &self.actions
+    }
+    pub fn clear_actions(&mut self) {
+        self.actions.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_actions(&mut self, v: ::protobuf::RepeatedField<Action>) {
+        self.actions = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_actions(&mut self) -> &mut ::protobuf::RepeatedField<Action> {
+        &mut self.actions
+    }
+
+    // Take field
+    pub fn take_actions(&mut self) -> ::protobuf::RepeatedField<Action> {
+        ::std::mem::replace(&mut self.actions, ::protobuf::RepeatedField::new())
+    }
 }
 
 impl ::protobuf::Message for Action {
     fn is_initialized(&self) -> bool {
+        for v in &self.actions {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
         true
     }
 
@@ -638,6 +700,9 @@ impl ::protobuf::Message for Action {
                 8 => {
                     ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.key)?;
                 },
+                9 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.actions)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -674,6 +739,10 @@ impl ::protobuf::Message for Action {
         if !self.key.is_empty() {
             my_size += ::protobuf::rt::bytes_size(8, &self.key);
         }
+        for value in &self.actions {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -704,6 +773,11 @@ impl ::protobuf::Message for Action {
         if !self.key.is_empty() {
             os.write_bytes(8, &self.key)?;
         }
+        for v in &self.actions {
+            os.write_tag(9, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -782,6 +856,11 @@ impl ::protobuf::Message for Action {
                 |m: &Action| { &m.key },
                 |m: &mut Action| { &mut m.key },
             ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Action>>(
+                "actions",
+                |m: &Action| { &m.actions },
+                |m: &mut Action| { &mut m.actions },
+            ));
             ::protobuf::reflect::MessageDescriptor::new_pb_name::<Action>(
                 "Action",
                 fields,
@@ -806,6 +885,7 @@ impl ::protobuf::Clear for Action {
         self.code.clear();
         self.nonce.clear();
         self.key.clear();
+        self.actions.clear();
         self.unknown_fields.clear();
     }
 }
@@ -833,6 +913,10 @@ pub struct ActionResult {
     pub output_data: ::std::vec::Vec<u8>,
     pub sub_action_results: ::protobuf::RepeatedField<ActionResult>,
     pub contract: ::std::vec::Vec<u8>,
+    pub write_set: ::protobuf::RepeatedField<StorageWrite>,
+    pub error_code: u32,
+    pub events: ::protobuf::RepeatedField<Event>,
+    pub logs: ::protobuf::RepeatedField<LogRecord>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
@@ -1029,6 +1113,96 @@ impl ActionResult {
     pub fn take_contract(&mut self) -> ::std::vec::Vec<u8> {
         ::std::mem::replace(&mut self.contract, ::std::vec::Vec::new())
     }
+
+    // repeated .models.StorageWrite write_set = 9;
+
+
+    pub fn get_write_set(&self) -> &[StorageWrite] {
+        &self.write_set
+    }
+    pub fn clear_write_set(&mut self) {
+        self.write_set.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_write_set(&mut self, v: ::protobuf::RepeatedField<StorageWrite>) {
+        self.write_set = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_write_set(&mut self) -> &mut ::protobuf::RepeatedField<StorageWrite> {
+        &mut self.write_set
+    }
+
+    // Take field
+    pub fn take_write_set(&mut self) -> ::protobuf::RepeatedField<StorageWrite> {
+        ::std::mem::replace(&mut self.write_set, ::protobuf::RepeatedField::new())
+    }
+
+    // uint32 error_code = 10;
+
+
+    pub fn get_error_code(&self) -> u32 {
+        self.error_code
+    }
+    pub fn clear_error_code(&mut self) {
+        self.error_code = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_error_code(&mut self, v: u32) {
+        self.error_code = v;
+    }
+
+    // repeated .models.Event events = 11;
+
+
+    pub fn get_events(&self) -> &[Event] {
+        &self.events
+    }
+    pub fn clear_events(&mut self) {
+        self.events.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_events(&mut self, v: ::protobuf::RepeatedField<Event>) {
+        self.events = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_events(&mut self) -> &mut ::protobuf::RepeatedField<Event> {
+        &mut self.events
+    }
+
+    // Take field
+    pub fn take_events(&mut self) -> ::protobuf::RepeatedField<Event> {
+        ::std::mem::replace(&mut self.events, ::protobuf::RepeatedField::new())
+    }
+
+    // repeated .models.LogRecord logs = 12;
+
+
+    pub fn get_logs(&self) -> &[LogRecord] {
+        &self.logs
+    }
+    pub fn clear_logs(&mut self) {
+        self.logs.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_logs(&mut self, v: ::protobuf::RepeatedField<LogRecord>) {
+        self.logs = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_logs(&mut self) -> &mut ::protobuf::RepeatedField<LogRecord> {
+        &mut self.logs
+    }
+
+    // Take field
+    pub fn take_logs(&mut self) -> ::protobuf::RepeatedField<LogRecord> {
+        ::std::mem::replace(&mut self.logs, ::protobuf::RepeatedField::new())
+    }
 }
 
 impl ::protobuf::Message for ActionResult {
@@ -1043,6 +1217,21 @@ impl ::protobuf::Message for ActionResult {
                 return false;
             }
         };
+        for v in &self.write_set {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.events {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.logs {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
         true
     }
 
@@ -1086,6 +1275,22 @@ impl ::protobuf::Message for ActionResult {
                 8 => {
                     ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.contract)?;
                 },
+                9 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.write_set)?;
+                },
+                10 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.error_code = tmp;
+                },
+                11 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.events)?;
+                },
+                12 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.logs)?;
+                },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
                 },
@@ -1124,6 +1329,21 @@ impl ::protobuf::Message for ActionResult {
         if !self.contract.is_empty() {
             my_size += ::protobuf::rt::bytes_size(8, &self.contract);
         }
+        for value in &self.write_set {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        if self.error_code != 0 {
+            my_size += ::protobuf::rt::value_size(10, self.error_code, ::protobuf::wire_format::WireTypeVarint);
+        }
+        for value in &self.events {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        for value in &self.logs {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
         my_size
@@ -1158,6 +1378,24 @@ impl ::protobuf::Message for ActionResult {
         if !self.contract.is_empty() {
             os.write_bytes(8, &self.contract)?;
         }
+        for v in &self.write_set {
+            os.write_tag(9, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        if self.error_code != 0 {
+            os.write_uint32(10, self.error_code)?;
+        }
+        for v in &self.events {
+            os.write_tag(11, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        for v in &self.logs {
+            os.write_tag(12, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
     }
@@ -1236,6 +1474,26 @@ impl ::protobuf::Message for ActionResult {
                 |m: &ActionResult| { &m.contract },
                 |m: &mut ActionResult| { &mut m.contract },
             ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<StorageWrite>>(
+                "write_set",
+                |m: &ActionResult| { &m.write_set },
+                |m: &mut ActionResult| { &mut m.write_set },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "error_code",
+                |m: &ActionResult| { &m.error_code },
+                |m: &mut ActionResult| { &mut m.error_code },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<Event>>(
+                "events",
+                |m: &ActionResult| { &m.events },
+                |m: &mut ActionResult| { &mut m.events },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<LogRecord>>(
+                "logs",
+                |m: &ActionResult| { &m.logs },
+                |m: &mut ActionResult| { &mut m.logs },
+            ));
             ::protobuf::reflect::MessageDescriptor::new_pb_name::<ActionResult>(
                 "ActionResult",
                 fields,
@@ -1260,6 +1518,10 @@ impl ::protobuf::Clear for ActionResult {
         self.output_data.clear();
         self.sub_action_results.clear();
         self.contract.clear();
+        self.write_set.clear();
+        self.error_code = 0;
+        self.events.clear();
+        self.logs.clear();
         self.unknown_fields.clear();
     }
 }
@@ -1277,69 +1539,96 @@ impl ::protobuf::reflect::ProtobufValue for ActionResult {
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct PromiseResult {
+pub struct StorageWrite {
     // message fields
-    pub success: bool,
-    pub data: ::std::vec::Vec<u8>,
+    pub key: ::std::vec::Vec<u8>,
+    pub value: ::std::vec::Vec<u8>,
+    pub removed: bool,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
 }
 
-impl<'a> ::std::default::Default for &'a PromiseResult {
-    fn default() -> &'a PromiseResult {
-        <PromiseResult as ::protobuf::Message>::default_instance()
+impl<'a> ::std::default::Default for &'a StorageWrite {
+    fn default() -> &'a StorageWrite {
+        <StorageWrite as ::protobuf::Message>::default_instance()
     }
 }
 
-impl PromiseResult {
-    pub fn new() -> PromiseResult {
+impl StorageWrite {
+    pub fn new() -> StorageWrite {
         ::std::default::Default::default()
     }
 
-    // bool success = 1;
+    // bytes key = 1;
 
 
-    pub fn get_success(&self) -> bool {
-        self.success
+    pub fn get_key(&self) -> &[u8] {
+        &self.key
     }
-    pub fn clear_success(&mut self) {
-        self.success = false;
+    pub fn clear_key(&mut self) {
+        self.key.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_success(&mut self, v: bool) {
-        self.success = v;
+    pub fn set_key(&mut self, v: ::std::vec::Vec<u8>) {
+        self.key = v;
     }
 
-    // bytes data = 2;
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_key(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.key
+    }
 
+    // Take field
+    pub fn take_key(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.key, ::std::vec::Vec::new())
+    }
 
-    pub fn get_data(&self) -> &[u8] {
-        &self.data
+    // bytes value = 2;
+
+
+    pub fn get_value(&self) -> &[u8] {
+        &self.value
     }
-    pub fn clear_data(&mut self) {
-        self.data.clear();
+    pub fn clear_value(&mut self) {
+        self.value.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_data(&mut self, v: ::std::vec::Vec<u8>) {
-        self.data = v;
+    pub fn set_value(&mut self, v: ::std::vec::Vec<u8>) {
+        self.value = v;
     }
 
     // Mutable pointer to the field.
     // If field is not initialized, it is initialized with default value first.
-    pub fn mut_data(&mut self) -> &mut ::std::vec::Vec<u8> {
-        &mut self.data
+    pub fn mut_value(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.value
     }
 
     // Take field
-    pub fn take_data(&mut self) -> ::std::vec::Vec<u8> {
-        ::std::mem::replace(&mut self.data, ::std::vec::Vec::new())
+    pub fn take_value(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.value, ::std::vec::Vec::new())
+    }
+
+    // bool removed = 3;
+
+
+    pub fn get_removed(&self) -> bool {
+        self.removed
+    }
+    pub fn clear_removed(&mut self) {
+        self.removed = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_removed(&mut self, v: bool) {
+        self.removed = v;
     }
 }
 
-impl ::protobuf::Message for PromiseResult {
+impl ::protobuf::Message for StorageWrite {
     fn is_initialized(&self) -> bool {
         true
     }
@@ -1349,14 +1638,17 @@ impl ::protobuf::Message for PromiseResult {
             let (field_number, wire_type) = is.read_tag_unpack()?;
             match field_number {
                 1 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.key)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.value)?;
+                },
+                3 => {
                     if wire_type != ::protobuf::wire_format::WireTypeVarint {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
                     let tmp = is.read_bool()?;
-                    self.success = tmp;
-                },
-                2 => {
-                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.data)?;
+                    self.removed = tmp;
                 },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
@@ -1370,11 +1662,14 @@ impl ::protobuf::Message for PromiseResult {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
-        if self.success != false {
-            my_size += 2;
+        if !self.key.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(1, &self.key);
         }
-        if !self.data.is_empty() {
-            my_size += ::protobuf::rt::bytes_size(2, &self.data);
+        if !self.value.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(2, &self.value);
+        }
+        if self.removed != false {
+            my_size += 2;
         }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
@@ -1382,11 +1677,14 @@ impl ::protobuf::Message for PromiseResult {
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
-        if self.success != false {
-            os.write_bool(1, self.success)?;
+        if !self.key.is_empty() {
+            os.write_bytes(1, &self.key)?;
         }
-        if !self.data.is_empty() {
-            os.write_bytes(2, &self.data)?;
+        if !self.value.is_empty() {
+            os.write_bytes(2, &self.value)?;
+        }
+        if self.removed != false {
+            os.write_bool(3, self.removed)?;
         }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
@@ -1418,135 +1716,1268 @@ impl ::protobuf::Message for PromiseResult {
         Self::descriptor_static()
     }
 
-    fn new() -> PromiseResult {
-        PromiseResult::new()
+    fn new() -> StorageWrite {
+        StorageWrite::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
         static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
         descriptor.get(|| {
             let mut fields = ::std::vec::Vec::new();
-            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
-                "success",
-                |m: &PromiseResult| { &m.success },
-                |m: &mut PromiseResult| { &mut m.success },
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "key",
+                |m: &StorageWrite| { &m.key },
+                |m: &mut StorageWrite| { &mut m.key },
             ));
             fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
-                "data",
-                |m: &PromiseResult| { &m.data },
-                |m: &mut PromiseResult| { &mut m.data },
+                "value",
+                |m: &StorageWrite| { &m.value },
+                |m: &mut StorageWrite| { &mut m.value },
             ));
-            ::protobuf::reflect::MessageDescriptor::new_pb_name::<PromiseResult>(
-                "PromiseResult",
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "removed",
+                |m: &StorageWrite| { &m.removed },
+                |m: &mut StorageWrite| { &mut m.removed },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<StorageWrite>(
+                "StorageWrite",
                 fields,
                 file_descriptor_proto()
             )
         })
     }
 
-    fn default_instance() -> &'static PromiseResult {
-        static instance: ::protobuf::rt::LazyV2<PromiseResult> = ::protobuf::rt::LazyV2::INIT;
-        instance.get(PromiseResult::new)
+    fn default_instance() -> &'static StorageWrite {
+        static instance: ::protobuf::rt::LazyV2<StorageWrite> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(StorageWrite::new)
     }
 }
 
-impl ::protobuf::Clear for PromiseResult {
+impl ::protobuf::Clear for StorageWrite {
     fn clear(&mut self) {
-        self.success = false;
-        self.data.clear();
+        self.key.clear();
+        self.value.clear();
+        self.removed = false;
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for PromiseResult {
+impl ::std::fmt::Debug for StorageWrite {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for PromiseResult {
+impl ::protobuf::reflect::ProtobufValue for StorageWrite {
     fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
         ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
 #[derive(PartialEq,Clone,Default)]
-pub struct InvocationContext {
+pub struct Event {
     // message fields
-    pub is_callback: bool,
-    pub promise_result: ::protobuf::SingularPtrField<PromiseResult>,
+    pub contract: ::std::vec::Vec<u8>,
+    pub topic: ::std::vec::Vec<u8>,
+    pub data: ::std::vec::Vec<u8>,
     // special fields
     pub unknown_fields: ::protobuf::UnknownFields,
     pub cached_size: ::protobuf::CachedSize,
 }
 
-impl<'a> ::std::default::Default for &'a InvocationContext {
-    fn default() -> &'a InvocationContext {
+impl<'a> ::std::default::Default for &'a Event {
+    fn default() -> &'a Event {
+        <Event as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl Event {
+    pub fn new() -> Event {
+        ::std::default::Default::default()
+    }
+
+    // bytes contract = 1;
+
+
+    pub fn get_contract(&self) -> &[u8] {
+        &self.contract
+    }
+    pub fn clear_contract(&mut self) {
+        self.contract.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_contract(&mut self, v: ::std::vec::Vec<u8>) {
+        self.contract = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_contract(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.contract
+    }
+
+    // Take field
+    pub fn take_contract(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.contract, ::std::vec::Vec::new())
+    }
+
+    // bytes topic = 2;
+
+
+    pub fn get_topic(&self) -> &[u8] {
+        &self.topic
+    }
+    pub fn clear_topic(&mut self) {
+        self.topic.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_topic(&mut self, v: ::std::vec::Vec<u8>) {
+        self.topic = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_topic(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.topic
+    }
+
+    // Take field
+    pub fn take_topic(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.topic, ::std::vec::Vec::new())
+    }
+
+    // bytes data = 3;
+
+
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+    pub fn clear_data(&mut self) {
+        self.data.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data(&mut self, v: ::std::vec::Vec<u8>) {
+        self.data = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_data(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.data
+    }
+
+    // Take field
+    pub fn take_data(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.data, ::std::vec::Vec::new())
+    }
+}
+
+impl ::protobuf::Message for Event {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.contract)?;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.topic)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.data)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if !self.contract.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(1, &self.contract);
+        }
+        if !self.topic.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(2, &self.topic);
+        }
+        if !self.data.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(3, &self.data);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if !self.contract.is_empty() {
+            os.write_bytes(1, &self.contract)?;
+        }
+        if !self.topic.is_empty() {
+            os.write_bytes(2, &self.topic)?;
+        }
+        if !self.data.is_empty() {
+            os.write_bytes(3, &self.data)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> Event {
+        Event::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "contract",
+                |m: &Event| { &m.contract },
+                |m: &mut Event| { &mut m.contract },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "topic",
+                |m: &Event| { &m.topic },
+                |m: &mut Event| { &mut m.topic },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "data",
+                |m: &Event| { &m.data },
+                |m: &mut Event| { &mut m.data },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<Event>(
+                "Event",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static Event {
+        static instance: ::protobuf::rt::LazyV2<Event> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(Event::new)
+    }
+}
+
+impl ::protobuf::Clear for Event {
+    fn clear(&mut self) {
+        self.contract.clear();
+        self.topic.clear();
+        self.data.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for Event {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for Event {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct LogRecord {
+    // message fields
+    pub level: u32,
+    pub contract: ::std::vec::Vec<u8>,
+    pub method: ::std::string::String,
+    pub message: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a LogRecord {
+    fn default() -> &'a LogRecord {
+        <LogRecord as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl LogRecord {
+    pub fn new() -> LogRecord {
+        ::std::default::Default::default()
+    }
+
+    // uint32 level = 1;
+
+
+    pub fn get_level(&self) -> u32 {
+        self.level
+    }
+    pub fn clear_level(&mut self) {
+        self.level = 0;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_level(&mut self, v: u32) {
+        self.level = v;
+    }
+
+    // bytes contract = 2;
+
+
+    pub fn get_contract(&self) -> &[u8] {
+        &self.contract
+    }
+    pub fn clear_contract(&mut self) {
+        self.contract.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_contract(&mut self, v: ::std::vec::Vec<u8>) {
+        self.contract = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_contract(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.contract
+    }
+
+    // Take field
+    pub fn take_contract(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.contract, ::std::vec::Vec::new())
+    }
+
+    // string method = 3;
+
+
+    pub fn get_method(&self) -> &str {
+        &self.method
+    }
+    pub fn clear_method(&mut self) {
+        self.method.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_method(&mut self, v: ::std::string::String) {
+        self.method = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_method(&mut self) -> &mut ::std::string::String {
+        &mut self.method
+    }
+
+    // Take field
+    pub fn take_method(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.method, ::std::string::String::new())
+    }
+
+    // string message = 4;
+
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+    pub fn clear_message(&mut self) {
+        self.message.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_message(&mut self, v: ::std::string::String) {
+        self.message = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_message(&mut self) -> &mut ::std::string::String {
+        &mut self.message
+    }
+
+    // Take field
+    pub fn take_message(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.message, ::std::string::String::new())
+    }
+}
+
+impl ::protobuf::Message for LogRecord {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_uint32()?;
+                    self.level = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.contract)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.method)?;
+                },
+                4 => {
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.message)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.level != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.level, ::protobuf::wire_format::WireTypeVarint);
+        }
+        if !self.contract.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(2, &self.contract);
+        }
+        if !self.method.is_empty() {
+            my_size += ::protobuf::rt::string_size(3, &self.method);
+        }
+        if !self.message.is_empty() {
+            my_size += ::protobuf::rt::string_size(4, &self.message);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.level != 0 {
+            os.write_uint32(1, self.level)?;
+        }
+        if !self.contract.is_empty() {
+            os.write_bytes(2, &self.contract)?;
+        }
+        if !self.method.is_empty() {
+            os.write_string(3, &self.method)?;
+        }
+        if !self.message.is_empty() {
+            os.write_string(4, &self.message)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> LogRecord {
+        LogRecord::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "level",
+                |m: &LogRecord| { &m.level },
+                |m: &mut LogRecord| { &mut m.level },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "contract",
+                |m: &LogRecord| { &m.contract },
+                |m: &mut LogRecord| { &mut m.contract },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "method",
+                |m: &LogRecord| { &m.method },
+                |m: &mut LogRecord| { &mut m.method },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "message",
+                |m: &LogRecord| { &m.message },
+                |m: &mut LogRecord| { &mut m.message },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<LogRecord>(
+                "LogRecord",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static LogRecord {
+        static instance: ::protobuf::rt::LazyV2<LogRecord> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(LogRecord::new)
+    }
+}
+
+impl ::protobuf::Clear for LogRecord {
+    fn clear(&mut self) {
+        self.level = 0;
+        self.contract.clear();
+        self.method.clear();
+        self.message.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for LogRecord {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for LogRecord {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct PromiseResult {
+    // message fields
+    pub success: bool,
+    pub data: ::std::vec::Vec<u8>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a PromiseResult {
+    fn default() -> &'a PromiseResult {
+        <PromiseResult as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl PromiseResult {
+    pub fn new() -> PromiseResult {
+        ::std::default::Default::default()
+    }
+
+    // bool success = 1;
+
+
+    pub fn get_success(&self) -> bool {
+        self.success
+    }
+    pub fn clear_success(&mut self) {
+        self.success = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_success(&mut self, v: bool) {
+        self.success = v;
+    }
+
+    // bytes data = 2;
+
+
+    pub fn get_data(&self) -> &[u8] {
+        &self.data
+    }
+    pub fn clear_data(&mut self) {
+        self.data.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_data(&mut self, v: ::std::vec::Vec<u8>) {
+        self.data = v;
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_data(&mut self) -> &mut ::std::vec::Vec<u8> {
+        &mut self.data
+    }
+
+    // Take field
+    pub fn take_data(&mut self) -> ::std::vec::Vec<u8> {
+        ::std::mem::replace(&mut self.data, ::std::vec::Vec::new())
+    }
+}
+
+impl ::protobuf::Message for PromiseResult {
+    fn is_initialized(&self) -> bool {
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.success = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_proto3_bytes_into(wire_type, is, &mut self.data)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.success != false {
+            my_size += 2;
+        }
+        if !self.data.is_empty() {
+            my_size += ::protobuf::rt::bytes_size(2, &self.data);
+        }
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.success != false {
+            os.write_bool(1, self.success)?;
+        }
+        if !self.data.is_empty() {
+            os.write_bytes(2, &self.data)?;
+        }
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> PromiseResult {
+        PromiseResult::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "success",
+                |m: &PromiseResult| { &m.success },
+                |m: &mut PromiseResult| { &mut m.success },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBytes>(
+                "data",
+                |m: &PromiseResult| { &m.data },
+                |m: &mut PromiseResult| { &mut m.data },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<PromiseResult>(
+                "PromiseResult",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static PromiseResult {
+        static instance: ::protobuf::rt::LazyV2<PromiseResult> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(PromiseResult::new)
+    }
+}
+
+impl ::protobuf::Clear for PromiseResult {
+    fn clear(&mut self) {
+        self.success = false;
+        self.data.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for PromiseResult {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for PromiseResult {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct InvocationContext {
+    // message fields
+    pub is_callback: bool,
+    pub promise_result: ::protobuf::SingularPtrField<PromiseResult>,
+    pub promise_results: ::protobuf::RepeatedField<PromiseResult>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a InvocationContext {
+    fn default() -> &'a InvocationContext {
         <InvocationContext as ::protobuf::Message>::default_instance()
     }
 }
 
-impl InvocationContext {
-    pub fn new() -> InvocationContext {
+impl InvocationContext {
+    pub fn new() -> InvocationContext {
+        ::std::default::Default::default()
+    }
+
+    // bool is_callback = 1;
+
+
+    pub fn get_is_callback(&self) -> bool {
+        self.is_callback
+    }
+    pub fn clear_is_callback(&mut self) {
+        self.is_callback = false;
+    }
+
+    // Param is passed by value, moved
+    pub fn set_is_callback(&mut self, v: bool) {
+        self.is_callback = v;
+    }
+
+    // .models.PromiseResult promise_result = 2;
+
+
+    pub fn get_promise_result(&self) -> &PromiseResult {
+        self.promise_result.as_ref().unwrap_or_else(|| <PromiseResult as ::protobuf::Message>::default_instance())
+    }
+    pub fn clear_promise_result(&mut self) {
+        self.promise_result.clear();
+    }
+
+    pub fn has_promise_result(&self) -> bool {
+        self.promise_result.is_some()
+    }
+
+    // Param is passed by value, moved
+    pub fn set_promise_result(&mut self, v: PromiseResult) {
+        self.promise_result = ::protobuf::SingularPtrField::some(v);
+    }
+
+    // Mutable pointer to the field.
+    // If field is not initialized, it is initialized with default value first.
+    pub fn mut_promise_result(&mut self) -> &mut PromiseResult {
+        if self.promise_result.is_none() {
+            self.promise_result.set_default();
+        }
+        self.promise_result.as_mut().unwrap()
+    }
+
+    // Take field
+    pub fn take_promise_result(&mut self) -> PromiseResult {
+        self.promise_result.take().unwrap_or_else(|| PromiseResult::new())
+    }
+
+    // repeated .models.PromiseResult promise_results = 3;
+
+
+    pub fn get_promise_results(&self) -> &[PromiseResult] {
+        &self.promise_results
+    }
+    pub fn clear_promise_results(&mut self) {
+        self.promise_results.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_promise_results(&mut self, v: ::protobuf::RepeatedField<PromiseResult>) {
+        self.promise_results = v;
+    }
+
+    // Mutable pointer to the field.
+    pub fn mut_promise_results(&mut self) -> &mut ::protobuf::RepeatedField<PromiseResult> {
+        &mut self.promise_results
+    }
+
+    // Take field
+    pub fn take_promise_results(&mut self) -> ::protobuf::RepeatedField<PromiseResult> {
+        ::std::mem::replace(&mut self.promise_results, ::protobuf::RepeatedField::new())
+    }
+}
+
+impl ::protobuf::Message for InvocationContext {
+    fn is_initialized(&self) -> bool {
+        for v in &self.promise_result {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        for v in &self.promise_results {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
+    }
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    if wire_type != ::protobuf::wire_format::WireTypeVarint {
+                        return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
+                    }
+                    let tmp = is.read_bool()?;
+                    self.is_callback = tmp;
+                },
+                2 => {
+                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.promise_result)?;
+                },
+                3 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.promise_results)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
+    }
+
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        if self.is_callback != false {
+            my_size += 2;
+        }
+        if let Some(ref v) = self.promise_result.as_ref() {
+            let len = v.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        }
+        for value in &self.promise_results {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        if self.is_callback != false {
+            os.write_bool(1, self.is_callback)?;
+        }
+        if let Some(ref v) = self.promise_result.as_ref() {
+            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        }
+        for v in &self.promise_results {
+            os.write_tag(3, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> InvocationContext {
+        InvocationContext::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
+                "is_callback",
+                |m: &InvocationContext| { &m.is_callback },
+                |m: &mut InvocationContext| { &mut m.is_callback },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<PromiseResult>>(
+                "promise_result",
+                |m: &InvocationContext| { &m.promise_result },
+                |m: &mut InvocationContext| { &mut m.promise_result },
+            ));
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<PromiseResult>>(
+                "promise_results",
+                |m: &InvocationContext| { &m.promise_results },
+                |m: &mut InvocationContext| { &mut m.promise_results },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<InvocationContext>(
+                "InvocationContext",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static InvocationContext {
+        static instance: ::protobuf::rt::LazyV2<InvocationContext> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(InvocationContext::new)
+    }
+}
+
+impl ::protobuf::Clear for InvocationContext {
+    fn clear(&mut self) {
+        self.is_callback = false;
+        self.promise_result.clear();
+        self.promise_results.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for InvocationContext {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for InvocationContext {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ValidationReport {
+    // message fields
+    pub violations: ::protobuf::RepeatedField<ValidationReport_Violation>,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ValidationReport {
+    fn default() -> &'a ValidationReport {
+        <ValidationReport as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ValidationReport {
+    pub fn new() -> ValidationReport {
         ::std::default::Default::default()
     }
 
-    // bool is_callback = 1;
+    // repeated .models.ValidationReport.Violation violations = 1;
 
 
-    pub fn get_is_callback(&self) -> bool {
-        self.is_callback
+    pub fn get_violations(&self) -> &[ValidationReport_Violation] {
+        &self.violations
     }
-    pub fn clear_is_callback(&mut self) {
-        self.is_callback = false;
+    pub fn clear_violations(&mut self) {
+        self.violations.clear();
     }
 
     // Param is passed by value, moved
-    pub fn set_is_callback(&mut self, v: bool) {
-        self.is_callback = v;
+    pub fn set_violations(&mut self, v: ::protobuf::RepeatedField<ValidationReport_Violation>) {
+        self.violations = v;
     }
 
-    // .models.PromiseResult promise_result = 2;
+    // Mutable pointer to the field.
+    pub fn mut_violations(&mut self) -> &mut ::protobuf::RepeatedField<ValidationReport_Violation> {
+        &mut self.violations
+    }
 
+    // Take field
+    pub fn take_violations(&mut self) -> ::protobuf::RepeatedField<ValidationReport_Violation> {
+        ::std::mem::replace(&mut self.violations, ::protobuf::RepeatedField::new())
+    }
+}
 
-    pub fn get_promise_result(&self) -> &PromiseResult {
-        self.promise_result.as_ref().unwrap_or_else(|| <PromiseResult as ::protobuf::Message>::default_instance())
+impl ::protobuf::Message for ValidationReport {
+    fn is_initialized(&self) -> bool {
+        for v in &self.violations {
+            if !v.is_initialized() {
+                return false;
+            }
+        };
+        true
     }
-    pub fn clear_promise_result(&mut self) {
-        self.promise_result.clear();
+
+    fn merge_from(&mut self, is: &mut ::protobuf::CodedInputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        while !is.eof()? {
+            let (field_number, wire_type) = is.read_tag_unpack()?;
+            match field_number {
+                1 => {
+                    ::protobuf::rt::read_repeated_message_into(wire_type, is, &mut self.violations)?;
+                },
+                _ => {
+                    ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
+                },
+            };
+        }
+        ::std::result::Result::Ok(())
     }
 
-    pub fn has_promise_result(&self) -> bool {
-        self.promise_result.is_some()
+    // Compute sizes of nested messages
+    #[allow(unused_variables)]
+    fn compute_size(&self) -> u32 {
+        let mut my_size = 0;
+        for value in &self.violations {
+            let len = value.compute_size();
+            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        };
+        my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
+        self.cached_size.set(my_size);
+        my_size
+    }
+
+    fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
+        for v in &self.violations {
+            os.write_tag(1, ::protobuf::wire_format::WireTypeLengthDelimited)?;
+            os.write_raw_varint32(v.get_cached_size())?;
+            v.write_to_with_cached_sizes(os)?;
+        };
+        os.write_unknown_fields(self.get_unknown_fields())?;
+        ::std::result::Result::Ok(())
+    }
+
+    fn get_cached_size(&self) -> u32 {
+        self.cached_size.get()
+    }
+
+    fn get_unknown_fields(&self) -> &::protobuf::UnknownFields {
+        &self.unknown_fields
+    }
+
+    fn mut_unknown_fields(&mut self) -> &mut ::protobuf::UnknownFields {
+        &mut self.unknown_fields
+    }
+
+    fn as_any(&self) -> &dyn (::std::any::Any) {
+        self as &dyn (::std::any::Any)
+    }
+    fn as_any_mut(&mut self) -> &mut dyn (::std::any::Any) {
+        self as &mut dyn (::std::any::Any)
+    }
+    fn into_any(self: ::std::boxed::Box<Self>) -> ::std::boxed::Box<dyn (::std::any::Any)> {
+        self
+    }
+
+    fn descriptor(&self) -> &'static ::protobuf::reflect::MessageDescriptor {
+        Self::descriptor_static()
+    }
+
+    fn new() -> ValidationReport {
+        ValidationReport::new()
+    }
+
+    fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            let mut fields = ::std::vec::Vec::new();
+            fields.push(::protobuf::reflect::accessor::make_repeated_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<ValidationReport_Violation>>(
+                "violations",
+                |m: &ValidationReport| { &m.violations },
+                |m: &mut ValidationReport| { &mut m.violations },
+            ));
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ValidationReport>(
+                "ValidationReport",
+                fields,
+                file_descriptor_proto()
+            )
+        })
+    }
+
+    fn default_instance() -> &'static ValidationReport {
+        static instance: ::protobuf::rt::LazyV2<ValidationReport> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ValidationReport::new)
+    }
+}
+
+impl ::protobuf::Clear for ValidationReport {
+    fn clear(&mut self) {
+        self.violations.clear();
+        self.unknown_fields.clear();
+    }
+}
+
+impl ::std::fmt::Debug for ValidationReport {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
+        ::protobuf::text_format::fmt(self, f)
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ValidationReport {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Message(self)
+    }
+}
+
+#[derive(PartialEq,Clone,Default)]
+pub struct ValidationReport_Violation {
+    // message fields
+    pub kind: u32,
+    pub message: ::std::string::String,
+    // special fields
+    pub unknown_fields: ::protobuf::UnknownFields,
+    pub cached_size: ::protobuf::CachedSize,
+}
+
+impl<'a> ::std::default::Default for &'a ValidationReport_Violation {
+    fn default() -> &'a ValidationReport_Violation {
+        <ValidationReport_Violation as ::protobuf::Message>::default_instance()
+    }
+}
+
+impl ValidationReport_Violation {
+    pub fn new() -> ValidationReport_Violation {
+        ::std::default::Default::default()
+    }
+
+    // uint32 kind = 1;
+
+
+    pub fn get_kind(&self) -> u32 {
+        self.kind
+    }
+    pub fn clear_kind(&mut self) {
+        self.kind = 0;
     }
 
     // Param is passed by value, moved
-    pub fn set_promise_result(&mut self, v: PromiseResult) {
-        self.promise_result = ::protobuf::SingularPtrField::some(v);
+    pub fn set_kind(&mut self, v: u32) {
+        self.kind = v;
+    }
+
+    // string message = 2;
+
+
+    pub fn get_message(&self) -> &str {
+        &self.message
+    }
+    pub fn clear_message(&mut self) {
+        self.message.clear();
+    }
+
+    // Param is passed by value, moved
+    pub fn set_message(&mut self, v: ::std::string::String) {
+        self.message = v;
     }
 
     // Mutable pointer to the field.
     // If field is not initialized, it is initialized with default value first.
-    pub fn mut_promise_result(&mut self) -> &mut PromiseResult {
-        if self.promise_result.is_none() {
-            self.promise_result.set_default();
-        }
-        self.promise_result.as_mut().unwrap()
+    pub fn mut_message(&mut self) -> &mut ::std::string::String {
+        &mut self.message
     }
 
     // Take field
-    pub fn take_promise_result(&mut self) -> PromiseResult {
-        self.promise_result.take().unwrap_or_else(|| PromiseResult::new())
+    pub fn take_message(&mut self) -> ::std::string::String {
+        ::std::mem::replace(&mut self.message, ::std::string::String::new())
     }
 }
 
-impl ::protobuf::Message for InvocationContext {
+impl ::protobuf::Message for ValidationReport_Violation {
     fn is_initialized(&self) -> bool {
-        for v in &self.promise_result {
-            if !v.is_initialized() {
-                return false;
-            }
-        };
         true
     }
 
@@ -1558,11 +2989,11 @@ impl ::protobuf::Message for InvocationContext {
                     if wire_type != ::protobuf::wire_format::WireTypeVarint {
                         return ::std::result::Result::Err(::protobuf::rt::unexpected_wire_type(wire_type));
                     }
-                    let tmp = is.read_bool()?;
-                    self.is_callback = tmp;
+                    let tmp = is.read_uint32()?;
+                    self.kind = tmp;
                 },
                 2 => {
-                    ::protobuf::rt::read_singular_message_into(wire_type, is, &mut self.promise_result)?;
+                    ::protobuf::rt::read_singular_proto3_string_into(wire_type, is, &mut self.message)?;
                 },
                 _ => {
                     ::protobuf::rt::read_unknown_or_skip_group(field_number, wire_type, is, self.mut_unknown_fields())?;
@@ -1576,12 +3007,11 @@ impl ::protobuf::Message for InvocationContext {
     #[allow(unused_variables)]
     fn compute_size(&self) -> u32 {
         let mut my_size = 0;
-        if self.is_callback != false {
-            my_size += 2;
+        if self.kind != 0 {
+            my_size += ::protobuf::rt::value_size(1, self.kind, ::protobuf::wire_format::WireTypeVarint);
         }
-        if let Some(ref v) = self.promise_result.as_ref() {
-            let len = v.compute_size();
-            my_size += 1 + ::protobuf::rt::compute_raw_varint32_size(len) + len;
+        if !self.message.is_empty() {
+            my_size += ::protobuf::rt::string_size(2, &self.message);
         }
         my_size += ::protobuf::rt::unknown_fields_size(self.get_unknown_fields());
         self.cached_size.set(my_size);
@@ -1589,13 +3019,11 @@ impl ::protobuf::Message for InvocationContext {
     }
 
     fn write_to_with_cached_sizes(&self, os: &mut ::protobuf::CodedOutputStream<'_>) -> ::protobuf::ProtobufResult<()> {
-        if self.is_callback != false {
-            os.write_bool(1, self.is_callback)?;
+        if self.kind != 0 {
+            os.write_uint32(1, self.kind)?;
         }
-        if let Some(ref v) = self.promise_result.as_ref() {
-            os.write_tag(2, ::protobuf::wire_format::WireTypeLengthDelimited)?;
-            os.write_raw_varint32(v.get_cached_size())?;
-            v.write_to_with_cached_sizes(os)?;
+        if !self.message.is_empty() {
+            os.write_string(2, &self.message)?;
         }
         os.write_unknown_fields(self.get_unknown_fields())?;
         ::std::result::Result::Ok(())
@@ -1627,156 +3055,328 @@ impl ::protobuf::Message for InvocationContext {
         Self::descriptor_static()
     }
 
-    fn new() -> InvocationContext {
-        InvocationContext::new()
+    fn new() -> ValidationReport_Violation {
+        ValidationReport_Violation::new()
     }
 
     fn descriptor_static() -> &'static ::protobuf::reflect::MessageDescriptor {
         static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::MessageDescriptor> = ::protobuf::rt::LazyV2::INIT;
         descriptor.get(|| {
             let mut fields = ::std::vec::Vec::new();
-            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeBool>(
-                "is_callback",
-                |m: &InvocationContext| { &m.is_callback },
-                |m: &mut InvocationContext| { &mut m.is_callback },
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeUint32>(
+                "kind",
+                |m: &ValidationReport_Violation| { &m.kind },
+                |m: &mut ValidationReport_Violation| { &mut m.kind },
             ));
-            fields.push(::protobuf::reflect::accessor::make_singular_ptr_field_accessor::<_, ::protobuf::types::ProtobufTypeMessage<PromiseResult>>(
-                "promise_result",
-                |m: &InvocationContext| { &m.promise_result },
-                |m: &mut InvocationContext| { &mut m.promise_result },
+            fields.push(::protobuf::reflect::accessor::make_simple_field_accessor::<_, ::protobuf::types::ProtobufTypeString>(
+                "message",
+                |m: &ValidationReport_Violation| { &m.message },
+                |m: &mut ValidationReport_Violation| { &mut m.message },
             ));
-            ::protobuf::reflect::MessageDescriptor::new_pb_name::<InvocationContext>(
-                "InvocationContext",
+            ::protobuf::reflect::MessageDescriptor::new_pb_name::<ValidationReport_Violation>(
+                "ValidationReport.Violation",
                 fields,
                 file_descriptor_proto()
             )
         })
     }
 
-    fn default_instance() -> &'static InvocationContext {
-        static instance: ::protobuf::rt::LazyV2<InvocationContext> = ::protobuf::rt::LazyV2::INIT;
-        instance.get(InvocationContext::new)
+    fn default_instance() -> &'static ValidationReport_Violation {
+        static instance: ::protobuf::rt::LazyV2<ValidationReport_Violation> = ::protobuf::rt::LazyV2::INIT;
+        instance.get(ValidationReport_Violation::new)
     }
 }
 
-impl ::protobuf::Clear for InvocationContext {
+impl ::protobuf::Clear for ValidationReport_Violation {
     fn clear(&mut self) {
-        self.is_callback = false;
-        self.promise_result.clear();
+        self.kind = 0;
+        self.message.clear();
         self.unknown_fields.clear();
     }
 }
 
-impl ::std::fmt::Debug for InvocationContext {
+impl ::std::fmt::Debug for ValidationReport_Violation {
     fn fmt(&self, f: &mut ::std::fmt::Formatter<'_>) -> ::std::fmt::Result {
         ::protobuf::text_format::fmt(self, f)
     }
 }
 
-impl ::protobuf::reflect::ProtobufValue for InvocationContext {
+impl ::protobuf::reflect::ProtobufValue for ValidationReport_Violation {
     fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
         ::protobuf::reflect::ReflectValueRef::Message(self)
     }
 }
 
+#[derive(Clone,PartialEq,Eq,Debug,Hash)]
+pub enum ArgType {
+    ARG_UNTYPED = 0,
+    ARG_I32 = 1,
+    ARG_I64 = 2,
+    ARG_U64 = 3,
+    ARG_BOOL = 4,
+    ARG_BYTES = 5,
+    ARG_STRING = 6,
+    ARG_ADDRESS = 7,
+}
+
+impl ::protobuf::ProtobufEnum for ArgType {
+    fn value(&self) -> i32 {
+        *self as i32
+    }
+
+    fn from_i32(value: i32) -> ::std::option::Option<ArgType> {
+        match value {
+            0 => ::std::option::Option::Some(ArgType::ARG_UNTYPED),
+            1 => ::std::option::Option::Some(ArgType::ARG_I32),
+            2 => ::std::option::Option::Some(ArgType::ARG_I64),
+            3 => ::std::option::Option::Some(ArgType::ARG_U64),
+            4 => ::std::option::Option::Some(ArgType::ARG_BOOL),
+            5 => ::std::option::Option::Some(ArgType::ARG_BYTES),
+            6 => ::std::option::Option::Some(ArgType::ARG_STRING),
+            7 => ::std::option::Option::Some(ArgType::ARG_ADDRESS),
+            _ => ::std::option::Option::None
+        }
+    }
+
+    fn values() -> &'static [Self] {
+        static values: &'static [ArgType] = &[
+            ArgType::ARG_UNTYPED,
+            ArgType::ARG_I32,
+            ArgType::ARG_I64,
+            ArgType::ARG_U64,
+            ArgType::ARG_BOOL,
+            ArgType::ARG_BYTES,
+            ArgType::ARG_STRING,
+            ArgType::ARG_ADDRESS,
+        ];
+        values
+    }
+
+    fn enum_descriptor_static() -> &'static ::protobuf::reflect::EnumDescriptor {
+        static descriptor: ::protobuf::rt::LazyV2<::protobuf::reflect::EnumDescriptor> = ::protobuf::rt::LazyV2::INIT;
+        descriptor.get(|| {
+            ::protobuf::reflect::EnumDescriptor::new_pb_name::<ArgType>("ArgType", file_descriptor_proto())
+        })
+    }
+}
+
+impl ::std::marker::Copy for ArgType {
+}
+
+impl ::std::default::Default for ArgType {
+    fn default() -> Self {
+        ArgType::ARG_UNTYPED
+    }
+}
+
+impl ::protobuf::reflect::ProtobufValue for ArgType {
+    fn as_ref(&self) -> ::protobuf::reflect::ReflectValueRef {
+        ::protobuf::reflect::ReflectValueRef::Enum(::protobuf::ProtobufEnum::descriptor(self))
+    }
+}
+
 static file_descriptor_proto_data: &'static [u8] = b"\
-    \n\x0cmodels.proto\x12\x06models\"t\n\tProtoArgs\x12.\n\x04args\x18\x01\
-    \x20\x03(\x0b2\x1a.models.ProtoArgs.ArgumentR\x04args\x1a7\n\x08Argument\
-    \x12\x14\n\x05value\x18\x01\x20\x01(\x0cR\x05value\x12\x15\n\x06is_nil\
-    \x18\x02\x20\x01(\x08R\x05isNil\"\xc6\x01\n\x06Action\x12\x1f\n\x0bactio\
-    n_type\x18\x01\x20\x01(\rR\nactionType\x12\x16\n\x06amount\x18\x02\x20\
-    \x01(\x0cR\x06amount\x12\x16\n\x06method\x18\x03\x20\x01(\tR\x06method\
-    \x12\x12\n\x04args\x18\x04\x20\x01(\x0cR\x04args\x12\x1b\n\tgas_limit\
+    \n\x0cmodels.proto\x12\x06models\"\x99\x01\n\tProtoArgs\x12.\n\x04args\
+    \x18\x01\x20\x03(\x0b2\x1a.models.ProtoArgs.ArgumentR\x04args\x1a\\\n\
+    \x08Argument\x12\x14\n\x05value\x18\x01\x20\x01(\x0cR\x05value\x12\x15\n\
+    \x06is_nil\x18\x02\x20\x01(\x08R\x05isNil\x12#\n\x04type\x18\x03\x20\x01\
+    (\x0e2\x0f.models.ArgTypeR\x04type\"\xf0\x01\n\x06Action\x12\x1f\n\x0bac\
+    tion_type\x18\x01\x20\x01(\rR\nactionType\x12\x16\n\x06amount\x18\x02\
+    \x20\x01(\x0cR\x06amount\x12\x16\n\x06method\x18\x03\x20\x01(\tR\x06meth\
+    od\x12\x12\n\x04args\x18\x04\x20\x01(\x0cR\x04args\x12\x1b\n\tgas_limit\
     \x18\x05\x20\x01(\x04R\x08gasLimit\x12\x12\n\x04code\x18\x06\x20\x01(\
     \x0cR\x04code\x12\x14\n\x05nonce\x18\x07\x20\x01(\x0cR\x05nonce\x12\x10\
-    \n\x03key\x18\x08\x20\x01(\x0cR\x03key\"\xb2\x02\n\x0cActionResult\x121\
-    \n\x0cinput_action\x18\x01\x20\x01(\x0b2\x0e.models.ActionR\x0binputActi\
-    on\x12\x18\n\x07success\x18\x02\x20\x01(\x08R\x07success\x12\x14\n\x05er\
-    ror\x18\x03\x20\x01(\tR\x05error\x12\x19\n\x08gas_used\x18\x04\x20\x01(\
+    \n\x03key\x18\x08\x20\x01(\x0cR\x03key\x12(\n\x07actions\x18\t\x20\x03(\
+    \x0b2\x0e.models.ActionR\x07actions\"\xd2\x03\n\x0cActionResult\x121\n\
+    \x0cinput_action\x18\x01\x20\x01(\x0b2\x0e.models.ActionR\x0binputAction\
+    \x12\x18\n\x07success\x18\x02\x20\x01(\x08R\x07success\x12\x14\n\x05erro\
+    r\x18\x03\x20\x01(\tR\x05error\x12\x19\n\x08gas_used\x18\x04\x20\x01(\
     \x04R\x07gasUsed\x12#\n\rremaining_gas\x18\x05\x20\x01(\x04R\x0cremainin\
     gGas\x12\x1f\n\x0boutput_data\x18\x06\x20\x01(\x0cR\noutputData\x12B\n\
     \x12sub_action_results\x18\x07\x20\x03(\x0b2\x14.models.ActionResultR\
     \x10subActionResults\x12\x1a\n\x08contract\x18\x08\x20\x01(\x0cR\x08cont\
-    ract\"=\n\rPromiseResult\x12\x18\n\x07success\x18\x01\x20\x01(\x08R\x07s\
-    uccess\x12\x12\n\x04data\x18\x02\x20\x01(\x0cR\x04data\"r\n\x11Invocatio\
-    nContext\x12\x1f\n\x0bis_callback\x18\x01\x20\x01(\x08R\nisCallback\x12<\
-    \n\x0epromise_result\x18\x02\x20\x01(\x0b2\x15.models.PromiseResultR\rpr\
-    omiseResultJ\x9b\x0c\n\x06\x12\x04\x02\0-\x01\n.\n\x01\x0c\x12\x03\x02\0\
+    ract\x121\n\twrite_set\x18\t\x20\x03(\x0b2\x14.models.StorageWriteR\x08w\
+    riteSet\x12\x1d\n\nerror_code\x18\n\x20\x01(\rR\terrorCode\x12%\n\x06eve\
+    nts\x18\x0b\x20\x03(\x0b2\r.models.EventR\x06events\x12%\n\x04logs\x18\
+    \x0c\x20\x03(\x0b2\x11.models.LogRecordR\x04logs\"P\n\x0cStorageWrite\
+    \x12\x10\n\x03key\x18\x01\x20\x01(\x0cR\x03key\x12\x14\n\x05value\x18\
+    \x02\x20\x01(\x0cR\x05value\x12\x18\n\x07removed\x18\x03\x20\x01(\x08R\
+    \x07removed\"M\n\x05Event\x12\x1a\n\x08contract\x18\x01\x20\x01(\x0cR\
+    \x08contract\x12\x14\n\x05topic\x18\x02\x20\x01(\x0cR\x05topic\x12\x12\n\
+    \x04data\x18\x03\x20\x01(\x0cR\x04data\"o\n\tLogRecord\x12\x14\n\x05leve\
+    l\x18\x01\x20\x01(\rR\x05level\x12\x1a\n\x08contract\x18\x02\x20\x01(\
+    \x0cR\x08contract\x12\x16\n\x06method\x18\x03\x20\x01(\tR\x06method\x12\
+    \x18\n\x07message\x18\x04\x20\x01(\tR\x07message\"=\n\rPromiseResult\x12\
+    \x18\n\x07success\x18\x01\x20\x01(\x08R\x07success\x12\x12\n\x04data\x18\
+    \x02\x20\x01(\x0cR\x04data\"\xb2\x01\n\x11InvocationContext\x12\x1f\n\
+    \x0bis_callback\x18\x01\x20\x01(\x08R\nisCallback\x12<\n\x0epromise_resu\
+    lt\x18\x02\x20\x01(\x0b2\x15.models.PromiseResultR\rpromiseResult\x12>\n\
+    \x0fpromise_results\x18\x03\x20\x03(\x0b2\x15.models.PromiseResultR\x0ep\
+    romiseResults\"\x91\x01\n\x10ValidationReport\x12B\n\nviolations\x18\x01\
+    \x20\x03(\x0b2\".models.ValidationReport.ViolationR\nviolations\x1a9\n\t\
+    Violation\x12\x12\n\x04kind\x18\x01\x20\x01(\rR\x04kind\x12\x18\n\x07mes\
+    sage\x18\x02\x20\x01(\tR\x07message*\x7f\n\x07ArgType\x12\x0f\n\x0bARG_U\
+    NTYPED\x10\0\x12\x0b\n\x07ARG_I32\x10\x01\x12\x0b\n\x07ARG_I64\x10\x02\
+    \x12\x0b\n\x07ARG_U64\x10\x03\x12\x0c\n\x08ARG_BOOL\x10\x04\x12\r\n\tARG\
+    _BYTES\x10\x05\x12\x0e\n\nARG_STRING\x10\x06\x12\x0f\n\x0bARG_ADDRESS\
+    \x10\x07J\xc1\x1a\n\x06\x12\x04\x02\0]\x01\n.\n\x01\x0c\x12\x03\x02\0\
     \x122$\x20protoc\x20--rust_out\x20.\x20.\\models.proto\n\n\x08\n\x01\x02\
-    \x12\x03\x03\0\x0f\n\n\n\x02\x04\0\x12\x04\x05\0\x0c\x01\n\n\n\x03\x04\0\
+    \x12\x03\x03\0\x0f\n\n\n\x02\x04\0\x12\x04\x05\0\x0e\x01\n\n\n\x03\x04\0\
     \x01\x12\x03\x05\x08\x11\n\x0b\n\x04\x04\0\x02\0\x12\x03\x06\x02\x1d\n\
     \x0c\n\x05\x04\0\x02\0\x04\x12\x03\x06\x02\n\n\x0c\n\x05\x04\0\x02\0\x06\
     \x12\x03\x06\x0b\x13\n\x0c\n\x05\x04\0\x02\0\x01\x12\x03\x06\x14\x18\n\
     \x0c\n\x05\x04\0\x02\0\x03\x12\x03\x06\x1b\x1c\n\x0c\n\x04\x04\0\x03\0\
-    \x12\x04\x08\x02\x0b\x03\n\x0c\n\x05\x04\0\x03\0\x01\x12\x03\x08\n\x12\n\
-    \r\n\x06\x04\0\x03\0\x02\0\x12\x03\t\x04\x14\n\x0e\n\x07\x04\0\x03\0\x02\
-    \0\x05\x12\x03\t\x04\t\n\x0e\n\x07\x04\0\x03\0\x02\0\x01\x12\x03\t\n\x0f\
-    \n\x0e\n\x07\x04\0\x03\0\x02\0\x03\x12\x03\t\x12\x13\n\r\n\x06\x04\0\x03\
-    \0\x02\x01\x12\x03\n\x04\x14\n\x0e\n\x07\x04\0\x03\0\x02\x01\x05\x12\x03\
-    \n\x04\x08\n\x0e\n\x07\x04\0\x03\0\x02\x01\x01\x12\x03\n\t\x0f\n\x0e\n\
-    \x07\x04\0\x03\0\x02\x01\x03\x12\x03\n\x12\x13\n\n\n\x02\x04\x01\x12\x04\
-    \x0e\0\x17\x01\n\n\n\x03\x04\x01\x01\x12\x03\x0e\x08\x0e\n\x0b\n\x04\x04\
-    \x01\x02\0\x12\x03\x0f\x02\x19\n\x0c\n\x05\x04\x01\x02\0\x05\x12\x03\x0f\
-    \x02\x08\n\x0c\n\x05\x04\x01\x02\0\x01\x12\x03\x0f\t\x14\n\x0c\n\x05\x04\
-    \x01\x02\0\x03\x12\x03\x0f\x17\x18\n\x0b\n\x04\x04\x01\x02\x01\x12\x03\
-    \x10\x02\x13\n\x0c\n\x05\x04\x01\x02\x01\x05\x12\x03\x10\x02\x07\n\x0c\n\
-    \x05\x04\x01\x02\x01\x01\x12\x03\x10\x08\x0e\n\x0c\n\x05\x04\x01\x02\x01\
-    \x03\x12\x03\x10\x11\x12\n\x0b\n\x04\x04\x01\x02\x02\x12\x03\x11\x02\x14\
-    \n\x0c\n\x05\x04\x01\x02\x02\x05\x12\x03\x11\x02\x08\n\x0c\n\x05\x04\x01\
-    \x02\x02\x01\x12\x03\x11\t\x0f\n\x0c\n\x05\x04\x01\x02\x02\x03\x12\x03\
-    \x11\x12\x13\n\x0b\n\x04\x04\x01\x02\x03\x12\x03\x12\x02\x11\n\x0c\n\x05\
-    \x04\x01\x02\x03\x05\x12\x03\x12\x02\x07\n\x0c\n\x05\x04\x01\x02\x03\x01\
-    \x12\x03\x12\x08\x0c\n\x0c\n\x05\x04\x01\x02\x03\x03\x12\x03\x12\x0f\x10\
-    \n\x0b\n\x04\x04\x01\x02\x04\x12\x03\x13\x02\x17\n\x0c\n\x05\x04\x01\x02\
-    \x04\x05\x12\x03\x13\x02\x08\n\x0c\n\x05\x04\x01\x02\x04\x01\x12\x03\x13\
-    \t\x12\n\x0c\n\x05\x04\x01\x02\x04\x03\x12\x03\x13\x15\x16\n\x0b\n\x04\
-    \x04\x01\x02\x05\x12\x03\x14\x02\x11\n\x0c\n\x05\x04\x01\x02\x05\x05\x12\
-    \x03\x14\x02\x07\n\x0c\n\x05\x04\x01\x02\x05\x01\x12\x03\x14\x08\x0c\n\
-    \x0c\n\x05\x04\x01\x02\x05\x03\x12\x03\x14\x0f\x10\n\x0b\n\x04\x04\x01\
-    \x02\x06\x12\x03\x15\x02\x12\n\x0c\n\x05\x04\x01\x02\x06\x05\x12\x03\x15\
-    \x02\x07\n\x0c\n\x05\x04\x01\x02\x06\x01\x12\x03\x15\x08\r\n\x0c\n\x05\
-    \x04\x01\x02\x06\x03\x12\x03\x15\x10\x11\n3\n\x04\x04\x01\x02\x07\x12\
-    \x03\x16\x02\x10\"&\x20addr\x20or\x20key\x20for\x20reading\x20sharded\
-    \x20data\n\n\x0c\n\x05\x04\x01\x02\x07\x05\x12\x03\x16\x02\x07\n\x0c\n\
-    \x05\x04\x01\x02\x07\x01\x12\x03\x16\x08\x0b\n\x0c\n\x05\x04\x01\x02\x07\
-    \x03\x12\x03\x16\x0e\x0f\n\n\n\x02\x04\x02\x12\x04\x19\0\"\x01\n\n\n\x03\
-    \x04\x02\x01\x12\x03\x19\x08\x14\n\x0b\n\x04\x04\x02\x02\0\x12\x03\x1a\
-    \x02\x1a\n\x0c\n\x05\x04\x02\x02\0\x06\x12\x03\x1a\x02\x08\n\x0c\n\x05\
-    \x04\x02\x02\0\x01\x12\x03\x1a\t\x15\n\x0c\n\x05\x04\x02\x02\0\x03\x12\
-    \x03\x1a\x18\x19\n\x0b\n\x04\x04\x02\x02\x01\x12\x03\x1b\x02\x13\n\x0c\n\
-    \x05\x04\x02\x02\x01\x05\x12\x03\x1b\x02\x06\n\x0c\n\x05\x04\x02\x02\x01\
-    \x01\x12\x03\x1b\x07\x0e\n\x0c\n\x05\x04\x02\x02\x01\x03\x12\x03\x1b\x11\
-    \x12\n\x0b\n\x04\x04\x02\x02\x02\x12\x03\x1c\x02\x13\n\x0c\n\x05\x04\x02\
-    \x02\x02\x05\x12\x03\x1c\x02\x08\n\x0c\n\x05\x04\x02\x02\x02\x01\x12\x03\
-    \x1c\t\x0e\n\x0c\n\x05\x04\x02\x02\x02\x03\x12\x03\x1c\x11\x12\n\x0b\n\
-    \x04\x04\x02\x02\x03\x12\x03\x1d\x02\x16\n\x0c\n\x05\x04\x02\x02\x03\x05\
-    \x12\x03\x1d\x02\x08\n\x0c\n\x05\x04\x02\x02\x03\x01\x12\x03\x1d\t\x11\n\
-    \x0c\n\x05\x04\x02\x02\x03\x03\x12\x03\x1d\x14\x15\n\x0b\n\x04\x04\x02\
-    \x02\x04\x12\x03\x1e\x02\x1b\n\x0c\n\x05\x04\x02\x02\x04\x05\x12\x03\x1e\
-    \x02\x08\n\x0c\n\x05\x04\x02\x02\x04\x01\x12\x03\x1e\t\x16\n\x0c\n\x05\
-    \x04\x02\x02\x04\x03\x12\x03\x1e\x19\x1a\n\x0b\n\x04\x04\x02\x02\x05\x12\
-    \x03\x1f\x02\x18\n\x0c\n\x05\x04\x02\x02\x05\x05\x12\x03\x1f\x02\x07\n\
-    \x0c\n\x05\x04\x02\x02\x05\x01\x12\x03\x1f\x08\x13\n\x0c\n\x05\x04\x02\
-    \x02\x05\x03\x12\x03\x1f\x16\x17\n\x0b\n\x04\x04\x02\x02\x06\x12\x03\x20\
-    \x02/\n\x0c\n\x05\x04\x02\x02\x06\x04\x12\x03\x20\x02\n\n\x0c\n\x05\x04\
-    \x02\x02\x06\x06\x12\x03\x20\x0b\x17\n\x0c\n\x05\x04\x02\x02\x06\x01\x12\
-    \x03\x20\x18*\n\x0c\n\x05\x04\x02\x02\x06\x03\x12\x03\x20-.\n\x0b\n\x04\
-    \x04\x02\x02\x07\x12\x03!\x02\x15\n\x0c\n\x05\x04\x02\x02\x07\x05\x12\
-    \x03!\x02\x07\n\x0c\n\x05\x04\x02\x02\x07\x01\x12\x03!\x08\x10\n\x0c\n\
-    \x05\x04\x02\x02\x07\x03\x12\x03!\x13\x14\n\n\n\x02\x04\x03\x12\x04%\0(\
-    \x01\n\n\n\x03\x04\x03\x01\x12\x03%\x08\x15\n\x0b\n\x04\x04\x03\x02\0\
-    \x12\x03&\x02\x13\n\x0c\n\x05\x04\x03\x02\0\x05\x12\x03&\x02\x06\n\x0c\n\
-    \x05\x04\x03\x02\0\x01\x12\x03&\x07\x0e\n\x0c\n\x05\x04\x03\x02\0\x03\
-    \x12\x03&\x11\x12\n\x0b\n\x04\x04\x03\x02\x01\x12\x03'\x02\x11\n\x0c\n\
-    \x05\x04\x03\x02\x01\x05\x12\x03'\x02\x07\n\x0c\n\x05\x04\x03\x02\x01\
-    \x01\x12\x03'\x08\x0c\n\x0c\n\x05\x04\x03\x02\x01\x03\x12\x03'\x0f\x10\n\
-    \n\n\x02\x04\x04\x12\x04*\0-\x01\n\n\n\x03\x04\x04\x01\x12\x03*\x08\x19\
-    \n\x0b\n\x04\x04\x04\x02\0\x12\x03+\x02\x17\n\x0c\n\x05\x04\x04\x02\0\
-    \x05\x12\x03+\x02\x06\n\x0c\n\x05\x04\x04\x02\0\x01\x12\x03+\x07\x12\n\
-    \x0c\n\x05\x04\x04\x02\0\x03\x12\x03+\x15\x16\n\x0b\n\x04\x04\x04\x02\
-    \x01\x12\x03,\x02#\n\x0c\n\x05\x04\x04\x02\x01\x06\x12\x03,\x02\x0f\n\
-    \x0c\n\x05\x04\x04\x02\x01\x01\x12\x03,\x10\x1e\n\x0c\n\x05\x04\x04\x02\
-    \x01\x03\x12\x03,!\"b\x06proto3\
+    \x12\x04\x08\x02\r\x03\n\x0c\n\x05\x04\0\x03\0\x01\x12\x03\x08\n\x12\n\r\
+    \n\x06\x04\0\x03\0\x02\0\x12\x03\t\x04\x14\n\x0e\n\x07\x04\0\x03\0\x02\0\
+    \x05\x12\x03\t\x04\t\n\x0e\n\x07\x04\0\x03\0\x02\0\x01\x12\x03\t\n\x0f\n\
+    \x0e\n\x07\x04\0\x03\0\x02\0\x03\x12\x03\t\x12\x13\n\r\n\x06\x04\0\x03\0\
+    \x02\x01\x12\x03\n\x04\x14\n\x0e\n\x07\x04\0\x03\0\x02\x01\x05\x12\x03\n\
+    \x04\x08\n\x0e\n\x07\x04\0\x03\0\x02\x01\x01\x12\x03\n\t\x0f\n\x0e\n\x07\
+    \x04\0\x03\0\x02\x01\x03\x12\x03\n\x12\x13\nK\n\x06\x04\0\x03\0\x02\x02\
+    \x12\x03\x0c\x04\x15\x1a<\x20Required\x20by\x20the\x20typed\x20format,\
+    \x20ignored\x20by\x20the\x20protobuf\x20one.\n\n\x0e\n\x07\x04\0\x03\0\
+    \x02\x02\x06\x12\x03\x0c\x04\x0b\n\x0e\n\x07\x04\0\x03\0\x02\x02\x01\x12\
+    \x03\x0c\x0c\x10\n\x0e\n\x07\x04\0\x03\0\x02\x02\x03\x12\x03\x0c\x13\x14\
+    \nH\n\x02\x05\0\x12\x04\x11\0\x1a\x01\x1a<\x20Numbers\x20are\x20little-e\
+    ndian,\x20bools\x20are\x20a\x20single\x200\x20or\x201\x20byte.\n\n\n\n\
+    \x03\x05\0\x01\x12\x03\x11\x05\x0c\n\x0b\n\x04\x05\0\x02\0\x12\x03\x12\
+    \x02\x12\n\x0c\n\x05\x05\0\x02\0\x01\x12\x03\x12\x02\r\n\x0c\n\x05\x05\0\
+    \x02\0\x02\x12\x03\x12\x10\x11\n\x0b\n\x04\x05\0\x02\x01\x12\x03\x13\x02\
+    \x0e\n\x0c\n\x05\x05\0\x02\x01\x01\x12\x03\x13\x02\t\n\x0c\n\x05\x05\0\
+    \x02\x01\x02\x12\x03\x13\x0c\r\n\x0b\n\x04\x05\0\x02\x02\x12\x03\x14\x02\
+    \x0e\n\x0c\n\x05\x05\0\x02\x02\x01\x12\x03\x14\x02\t\n\x0c\n\x05\x05\0\
+    \x02\x02\x02\x12\x03\x14\x0c\r\n\x0b\n\x04\x05\0\x02\x03\x12\x03\x15\x02\
+    \x0e\n\x0c\n\x05\x05\0\x02\x03\x01\x12\x03\x15\x02\t\n\x0c\n\x05\x05\0\
+    \x02\x03\x02\x12\x03\x15\x0c\r\n\x0b\n\x04\x05\0\x02\x04\x12\x03\x16\x02\
+    \x0f\n\x0c\n\x05\x05\0\x02\x04\x01\x12\x03\x16\x02\n\n\x0c\n\x05\x05\0\
+    \x02\x04\x02\x12\x03\x16\r\x0e\n\x0b\n\x04\x05\0\x02\x05\x12\x03\x17\x02\
+    \x10\n\x0c\n\x05\x05\0\x02\x05\x01\x12\x03\x17\x02\x0b\n\x0c\n\x05\x05\0\
+    \x02\x05\x02\x12\x03\x17\x0e\x0f\n\x0b\n\x04\x05\0\x02\x06\x12\x03\x18\
+    \x02\x11\n\x0c\n\x05\x05\0\x02\x06\x01\x12\x03\x18\x02\x0c\n\x0c\n\x05\
+    \x05\0\x02\x06\x02\x12\x03\x18\x0f\x10\n\x0b\n\x04\x05\0\x02\x07\x12\x03\
+    \x19\x02\x12\n\x0c\n\x05\x05\0\x02\x07\x01\x12\x03\x19\x02\r\n\x0c\n\x05\
+    \x05\0\x02\x07\x02\x12\x03\x19\x10\x11\n\n\n\x02\x04\x01\x12\x04\x1c\0&\
+    \x01\n\n\n\x03\x04\x01\x01\x12\x03\x1c\x08\x0e\n\x0b\n\x04\x04\x01\x02\0\
+    \x12\x03\x1d\x02\x19\n\x0c\n\x05\x04\x01\x02\0\x05\x12\x03\x1d\x02\x08\n\
+    \x0c\n\x05\x04\x01\x02\0\x01\x12\x03\x1d\t\x14\n\x0c\n\x05\x04\x01\x02\0\
+    \x03\x12\x03\x1d\x17\x18\n\x0b\n\x04\x04\x01\x02\x01\x12\x03\x1e\x02\x13\
+    \n\x0c\n\x05\x04\x01\x02\x01\x05\x12\x03\x1e\x02\x07\n\x0c\n\x05\x04\x01\
+    \x02\x01\x01\x12\x03\x1e\x08\x0e\n\x0c\n\x05\x04\x01\x02\x01\x03\x12\x03\
+    \x1e\x11\x12\n\x0b\n\x04\x04\x01\x02\x02\x12\x03\x1f\x02\x14\n\x0c\n\x05\
+    \x04\x01\x02\x02\x05\x12\x03\x1f\x02\x08\n\x0c\n\x05\x04\x01\x02\x02\x01\
+    \x12\x03\x1f\t\x0f\n\x0c\n\x05\x04\x01\x02\x02\x03\x12\x03\x1f\x12\x13\n\
+    \x0b\n\x04\x04\x01\x02\x03\x12\x03\x20\x02\x11\n\x0c\n\x05\x04\x01\x02\
+    \x03\x05\x12\x03\x20\x02\x07\n\x0c\n\x05\x04\x01\x02\x03\x01\x12\x03\x20\
+    \x08\x0c\n\x0c\n\x05\x04\x01\x02\x03\x03\x12\x03\x20\x0f\x10\n\x0b\n\x04\
+    \x04\x01\x02\x04\x12\x03!\x02\x17\n\x0c\n\x05\x04\x01\x02\x04\x05\x12\
+    \x03!\x02\x08\n\x0c\n\x05\x04\x01\x02\x04\x01\x12\x03!\t\x12\n\x0c\n\x05\
+    \x04\x01\x02\x04\x03\x12\x03!\x15\x16\n\x0b\n\x04\x04\x01\x02\x05\x12\
+    \x03\"\x02\x11\n\x0c\n\x05\x04\x01\x02\x05\x05\x12\x03\"\x02\x07\n\x0c\n\
+    \x05\x04\x01\x02\x05\x01\x12\x03\"\x08\x0c\n\x0c\n\x05\x04\x01\x02\x05\
+    \x03\x12\x03\"\x0f\x10\n\x0b\n\x04\x04\x01\x02\x06\x12\x03#\x02\x12\n\
+    \x0c\n\x05\x04\x01\x02\x06\x05\x12\x03#\x02\x07\n\x0c\n\x05\x04\x01\x02\
+    \x06\x01\x12\x03#\x08\r\n\x0c\n\x05\x04\x01\x02\x06\x03\x12\x03#\x10\x11\
+    \n3\n\x04\x04\x01\x02\x07\x12\x03$\x02\x10\"&\x20addr\x20or\x20key\x20fo\
+    r\x20reading\x20sharded\x20data\n\n\x0c\n\x05\x04\x01\x02\x07\x05\x12\
+    \x03$\x02\x07\n\x0c\n\x05\x04\x01\x02\x07\x01\x12\x03$\x08\x0b\n\x0c\n\
+    \x05\x04\x01\x02\x07\x03\x12\x03$\x0e\x0f\n!\n\x04\x04\x01\x02\x08\x12\
+    \x03%\x02\x1e\"\x14\x20actions\x20of\x20a\x20batch\n\n\x0c\n\x05\x04\x01\
+    \x02\x08\x04\x12\x03%\x02\n\n\x0c\n\x05\x04\x01\x02\x08\x06\x12\x03%\x0b\
+    \x11\n\x0c\n\x05\x04\x01\x02\x08\x01\x12\x03%\x12\x19\n\x0c\n\x05\x04\
+    \x01\x02\x08\x03\x12\x03%\x1c\x1d\n\n\n\x02\x04\x02\x12\x04(\05\x01\n\n\
+    \n\x03\x04\x02\x01\x12\x03(\x08\x14\n\x0b\n\x04\x04\x02\x02\0\x12\x03)\
+    \x02\x1a\n\x0c\n\x05\x04\x02\x02\0\x06\x12\x03)\x02\x08\n\x0c\n\x05\x04\
+    \x02\x02\0\x01\x12\x03)\t\x15\n\x0c\n\x05\x04\x02\x02\0\x03\x12\x03)\x18\
+    \x19\n\x0b\n\x04\x04\x02\x02\x01\x12\x03*\x02\x13\n\x0c\n\x05\x04\x02\
+    \x02\x01\x05\x12\x03*\x02\x06\n\x0c\n\x05\x04\x02\x02\x01\x01\x12\x03*\
+    \x07\x0e\n\x0c\n\x05\x04\x02\x02\x01\x03\x12\x03*\x11\x12\n\x0b\n\x04\
+    \x04\x02\x02\x02\x12\x03+\x02\x13\n\x0c\n\x05\x04\x02\x02\x02\x05\x12\
+    \x03+\x02\x08\n\x0c\n\x05\x04\x02\x02\x02\x01\x12\x03+\t\x0e\n\x0c\n\x05\
+    \x04\x02\x02\x02\x03\x12\x03+\x11\x12\n\x0b\n\x04\x04\x02\x02\x03\x12\
+    \x03,\x02\x16\n\x0c\n\x05\x04\x02\x02\x03\x05\x12\x03,\x02\x08\n\x0c\n\
+    \x05\x04\x02\x02\x03\x01\x12\x03,\t\x11\n\x0c\n\x05\x04\x02\x02\x03\x03\
+    \x12\x03,\x14\x15\n\x0b\n\x04\x04\x02\x02\x04\x12\x03-\x02\x1b\n\x0c\n\
+    \x05\x04\x02\x02\x04\x05\x12\x03-\x02\x08\n\x0c\n\x05\x04\x02\x02\x04\
+    \x01\x12\x03-\t\x16\n\x0c\n\x05\x04\x02\x02\x04\x03\x12\x03-\x19\x1a\n\
+    \x0b\n\x04\x04\x02\x02\x05\x12\x03.\x02\x18\n\x0c\n\x05\x04\x02\x02\x05\
+    \x05\x12\x03.\x02\x07\n\x0c\n\x05\x04\x02\x02\x05\x01\x12\x03.\x08\x13\n\
+    \x0c\n\x05\x04\x02\x02\x05\x03\x12\x03.\x16\x17\n\x0b\n\x04\x04\x02\x02\
+    \x06\x12\x03/\x02/\n\x0c\n\x05\x04\x02\x02\x06\x04\x12\x03/\x02\n\n\x0c\
+    \n\x05\x04\x02\x02\x06\x06\x12\x03/\x0b\x17\n\x0c\n\x05\x04\x02\x02\x06\
+    \x01\x12\x03/\x18*\n\x0c\n\x05\x04\x02\x02\x06\x03\x12\x03/-.\n\x0b\n\
+    \x04\x04\x02\x02\x07\x12\x030\x02\x15\n\x0c\n\x05\x04\x02\x02\x07\x05\
+    \x12\x030\x02\x07\n\x0c\n\x05\x04\x02\x02\x07\x01\x12\x030\x08\x10\n\x0c\
+    \n\x05\x04\x02\x02\x07\x03\x12\x030\x13\x14\n\x0b\n\x04\x04\x02\x02\x08\
+    \x12\x031\x02&\n\x0c\n\x05\x04\x02\x02\x08\x04\x12\x031\x02\n\n\x0c\n\
+    \x05\x04\x02\x02\x08\x06\x12\x031\x0b\x17\n\x0c\n\x05\x04\x02\x02\x08\
+    \x01\x12\x031\x18!\n\x0c\n\x05\x04\x02\x02\x08\x03\x12\x031$%\n\x0b\n\
+    \x04\x04\x02\x02\t\x12\x032\x02\x19\n\x0c\n\x05\x04\x02\x02\t\x05\x12\
+    \x032\x02\x08\n\x0c\n\x05\x04\x02\x02\t\x01\x12\x032\t\x13\n\x0c\n\x05\
+    \x04\x02\x02\t\x03\x12\x032\x16\x18\n\x0b\n\x04\x04\x02\x02\n\x12\x033\
+    \x02\x1d\n\x0c\n\x05\x04\x02\x02\n\x04\x12\x033\x02\n\n\x0c\n\x05\x04\
+    \x02\x02\n\x06\x12\x033\x0b\x10\n\x0c\n\x05\x04\x02\x02\n\x01\x12\x033\
+    \x11\x17\n\x0c\n\x05\x04\x02\x02\n\x03\x12\x033\x1a\x1c\n\x0b\n\x04\x04\
+    \x02\x02\x0b\x12\x034\x02\x1f\n\x0c\n\x05\x04\x02\x02\x0b\x04\x12\x034\
+    \x02\n\n\x0c\n\x05\x04\x02\x02\x0b\x06\x12\x034\x0b\x14\n\x0c\n\x05\x04\
+    \x02\x02\x0b\x01\x12\x034\x15\x19\n\x0c\n\x05\x04\x02\x02\x0b\x03\x12\
+    \x034\x1c\x1e\n\n\n\x02\x04\x03\x12\x047\0;\x01\n\n\n\x03\x04\x03\x01\
+    \x12\x037\x08\x14\n\x0b\n\x04\x04\x03\x02\0\x12\x038\x02\x10\n\x0c\n\x05\
+    \x04\x03\x02\0\x05\x12\x038\x02\x07\n\x0c\n\x05\x04\x03\x02\0\x01\x12\
+    \x038\x08\x0b\n\x0c\n\x05\x04\x03\x02\0\x03\x12\x038\x0e\x0f\n\x0b\n\x04\
+    \x04\x03\x02\x01\x12\x039\x02\x12\n\x0c\n\x05\x04\x03\x02\x01\x05\x12\
+    \x039\x02\x07\n\x0c\n\x05\x04\x03\x02\x01\x01\x12\x039\x08\r\n\x0c\n\x05\
+    \x04\x03\x02\x01\x03\x12\x039\x10\x11\n\x0b\n\x04\x04\x03\x02\x02\x12\
+    \x03:\x02\x13\n\x0c\n\x05\x04\x03\x02\x02\x05\x12\x03:\x02\x06\n\x0c\n\
+    \x05\x04\x03\x02\x02\x01\x12\x03:\x07\x0e\n\x0c\n\x05\x04\x03\x02\x02\
+    \x03\x12\x03:\x11\x12\n\n\n\x02\x04\x04\x12\x04=\0A\x01\n\n\n\x03\x04\
+    \x04\x01\x12\x03=\x08\r\n\x0b\n\x04\x04\x04\x02\0\x12\x03>\x02\x15\n\x0c\
+    \n\x05\x04\x04\x02\0\x05\x12\x03>\x02\x07\n\x0c\n\x05\x04\x04\x02\0\x01\
+    \x12\x03>\x08\x10\n\x0c\n\x05\x04\x04\x02\0\x03\x12\x03>\x13\x14\n\x0b\n\
+    \x04\x04\x04\x02\x01\x12\x03?\x02\x12\n\x0c\n\x05\x04\x04\x02\x01\x05\
+    \x12\x03?\x02\x07\n\x0c\n\x05\x04\x04\x02\x01\x01\x12\x03?\x08\r\n\x0c\n\
+    \x05\x04\x04\x02\x01\x03\x12\x03?\x10\x11\n\x0b\n\x04\x04\x04\x02\x02\
+    \x12\x03@\x02\x11\n\x0c\n\x05\x04\x04\x02\x02\x05\x12\x03@\x02\x07\n\x0c\
+    \n\x05\x04\x04\x02\x02\x01\x12\x03@\x08\x0c\n\x0c\n\x05\x04\x04\x02\x02\
+    \x03\x12\x03@\x0f\x10\n\n\n\x02\x04\x05\x12\x04C\0H\x01\n\n\n\x03\x04\
+    \x05\x01\x12\x03C\x08\x11\n\x0b\n\x04\x04\x05\x02\0\x12\x03D\x02\x13\n\
+    \x0c\n\x05\x04\x05\x02\0\x05\x12\x03D\x02\x08\n\x0c\n\x05\x04\x05\x02\0\
+    \x01\x12\x03D\t\x0e\n\x0c\n\x05\x04\x05\x02\0\x03\x12\x03D\x11\x12\n\x0b\
+    \n\x04\x04\x05\x02\x01\x12\x03E\x02\x15\n\x0c\n\x05\x04\x05\x02\x01\x05\
+    \x12\x03E\x02\x07\n\x0c\n\x05\x04\x05\x02\x01\x01\x12\x03E\x08\x10\n\x0c\
+    \n\x05\x04\x05\x02\x01\x03\x12\x03E\x13\x14\n\x0b\n\x04\x04\x05\x02\x02\
+    \x12\x03F\x02\x14\n\x0c\n\x05\x04\x05\x02\x02\x05\x12\x03F\x02\x08\n\x0c\
+    \n\x05\x04\x05\x02\x02\x01\x12\x03F\t\x0f\n\x0c\n\x05\x04\x05\x02\x02\
+    \x03\x12\x03F\x12\x13\n\x0b\n\x04\x04\x05\x02\x03\x12\x03G\x02\x15\n\x0c\
+    \n\x05\x04\x05\x02\x03\x05\x12\x03G\x02\x08\n\x0c\n\x05\x04\x05\x02\x03\
+    \x01\x12\x03G\t\x10\n\x0c\n\x05\x04\x05\x02\x03\x03\x12\x03G\x13\x14\n\n\
+    \n\x02\x04\x06\x12\x04K\0N\x01\n\n\n\x03\x04\x06\x01\x12\x03K\x08\x15\n\
+    \x0b\n\x04\x04\x06\x02\0\x12\x03L\x02\x13\n\x0c\n\x05\x04\x06\x02\0\x05\
+    \x12\x03L\x02\x06\n\x0c\n\x05\x04\x06\x02\0\x01\x12\x03L\x07\x0e\n\x0c\n\
+    \x05\x04\x06\x02\0\x03\x12\x03L\x11\x12\n\x0b\n\x04\x04\x06\x02\x01\x12\
+    \x03M\x02\x11\n\x0c\n\x05\x04\x06\x02\x01\x05\x12\x03M\x02\x07\n\x0c\n\
+    \x05\x04\x06\x02\x01\x01\x12\x03M\x08\x0c\n\x0c\n\x05\x04\x06\x02\x01\
+    \x03\x12\x03M\x0f\x10\n\n\n\x02\x04\x07\x12\x04P\0T\x01\n\n\n\x03\x04\
+    \x07\x01\x12\x03P\x08\x19\n\x0b\n\x04\x04\x07\x02\0\x12\x03Q\x02\x17\n\
+    \x0c\n\x05\x04\x07\x02\0\x05\x12\x03Q\x02\x06\n\x0c\n\x05\x04\x07\x02\0\
+    \x01\x12\x03Q\x07\x12\n\x0c\n\x05\x04\x07\x02\0\x03\x12\x03Q\x15\x16\n\
+    \x0b\n\x04\x04\x07\x02\x01\x12\x03R\x02#\n\x0c\n\x05\x04\x07\x02\x01\x06\
+    \x12\x03R\x02\x0f\n\x0c\n\x05\x04\x07\x02\x01\x01\x12\x03R\x10\x1e\n\x0c\
+    \n\x05\x04\x07\x02\x01\x03\x12\x03R!\"\n\x0b\n\x04\x04\x07\x02\x02\x12\
+    \x03S\x02-\n\x0c\n\x05\x04\x07\x02\x02\x04\x12\x03S\x02\n\n\x0c\n\x05\
+    \x04\x07\x02\x02\x06\x12\x03S\x0b\x18\n\x0c\n\x05\x04\x07\x02\x02\x01\
+    \x12\x03S\x19(\n\x0c\n\x05\x04\x07\x02\x02\x03\x12\x03S+,\n\n\n\x02\x04\
+    \x08\x12\x04V\0]\x01\n\n\n\x03\x04\x08\x01\x12\x03V\x08\x18\n\x0b\n\x04\
+    \x04\x08\x02\0\x12\x03W\x02$\n\x0c\n\x05\x04\x08\x02\0\x04\x12\x03W\x02\
+    \n\n\x0c\n\x05\x04\x08\x02\0\x06\x12\x03W\x0b\x14\n\x0c\n\x05\x04\x08\
+    \x02\0\x01\x12\x03W\x15\x1f\n\x0c\n\x05\x04\x08\x02\0\x03\x12\x03W\"#\n\
+    \x0c\n\x04\x04\x08\x03\0\x12\x04Y\x02\\\x03\n\x0c\n\x05\x04\x08\x03\0\
+    \x01\x12\x03Y\n\x13\n\r\n\x06\x04\x08\x03\0\x02\0\x12\x03Z\x04\x14\n\x0e\
+    \n\x07\x04\x08\x03\0\x02\0\x05\x12\x03Z\x04\n\n\x0e\n\x07\x04\x08\x03\0\
+    \x02\0\x01\x12\x03Z\x0b\x0f\n\x0e\n\x07\x04\x08\x03\0\x02\0\x03\x12\x03Z\
+    \x12\x13\n\r\n\x06\x04\x08\x03\0\x02\x01\x12\x03[\x04\x17\n\x0e\n\x07\
+    \x04\x08\x03\0\x02\x01\x05\x12\x03[\x04\n\n\x0e\n\x07\x04\x08\x03\0\x02\
+    \x01\x01\x12\x03[\x0b\x12\n\x0e\n\x07\x04\x08\x03\0\x02\x01\x03\x12\x03[\
+    \x15\x16b\x06proto3\
 ";
 
 static file_descriptor_proto_lazy: ::protobuf::rt::LazyV2<::protobuf::descriptor::FileDescriptorProto> = ::protobuf::rt::LazyV2::INIT;
@@ -4,36 +4,53 @@ use std::sync::Arc;
 use indexmap::map::Iter;
 use protobuf::Message;
 use wasmer::{
-    imports, BaseTunables, ChainableNamedResolver, CompilerConfig, ExportIndex, Function, Instance,
-    Module, Pages, Singlepass, Store, Target, Val, Value,
+    BaseTunables, CompilerConfig, ExportIndex, Exports, Function, ImportObject, Instance, Module,
+    Singlepass, Store, Target, Type, Val, Value,
 };
+use wasmer::wasmparser::Operator;
 use wasmer_engine_universal::Universal;
 use wasmer_middlewares::Metering;
 use wasmer_types::ModuleInfo;
 
-use crate::args::convert_args;
+use crate::abi::{read_abi, returned_type, AbiType};
+use crate::args::{convert_args, Argument};
+use crate::artifact::{deserialize_module, serialize_module};
 use crate::backend::{Backend, BackendResult};
+use crate::cache::{get_or_compile, CacheKey};
+use crate::config::VmConfig;
 use crate::costs::*;
-use crate::environment::Env;
-use crate::errors::VmError;
+use crate::deferred_start::{DeferredStart, DEFERRED_START_EXPORT};
+use crate::environment::{Env, ExecutionLog};
+use crate::errors::{ERROR_NONE, VmError};
 use crate::gatekeeper::*;
+use crate::grow_metering::GrowMetering;
 use crate::imports::*;
 use crate::limiting_tunables::LimitingTunables;
-use crate::memory::{read_region, VmResult};
-use crate::proto::models::{InvocationContext as protoContext, ProtoArgs_Argument};
+use crate::memory::VmResult;
+use crate::proto::models::InvocationContext as protoContext;
+use crate::signature_alignment::SignatureAlignment;
+use crate::stack_limiter::StackLimiter;
+use crate::validation::check_limits;
 use crate::types::PromiseResult::Failed;
 use crate::types::{
-    Action, ActionResult, Address, DeployContractAction, FunctionCallAction, Gas,
-    InvocationContext, Promise, PromiseResult, ReadShardedDataAction, IDNA,
+    Action, ActionResult, Address, BatchAction, DeployContractAction, FunctionCallAction, Gas,
+    InvocationContext, LogLevel, LogRecord, Promise, PromiseResult, ReadShardedDataAction, IDNA,
 };
 use crate::unwrap_or_action_res;
 
+/// Compiler of the contracts, its name is a part of the artifact fingerprint.
+pub type Compiler = Singlepass;
+
 pub struct VmRunner<B: Backend + 'static> {
     pub contact_addr: Address,
     pub api: B,
     pub gas_limit: Gas,
+    pub gas_schedule: &'static GasSchedule,
+    pub vm_config: &'static VmConfig,
     ctx: Option<InvocationContext>,
     pub is_debug: bool,
+    read_only: bool,
+    log: ExecutionLog,
     _wasmer: Option<Box<Instance>>,
 }
 
@@ -42,6 +59,8 @@ impl<B: Backend + 'static> VmRunner<B> {
         api: B,
         contract_addr: Address,
         gas_limit: Gas,
+        gas_schedule: &'static GasSchedule,
+        vm_config: &'static VmConfig,
         ctx: Option<InvocationContext>,
         is_debug: bool,
     ) -> Self {
@@ -49,128 +68,176 @@ impl<B: Backend + 'static> VmRunner<B> {
             contact_addr: contract_addr,
             api,
             gas_limit,
+            gas_schedule,
+            vm_config,
             ctx,
             is_debug,
+            read_only: false,
+            log: ExecutionLog::default(),
             _wasmer: None,
         }
     }
+    /// Checks the arguments against the parameters of the method. Numbers are passed as they are,
+    /// other values are written to the contract memory. Missing arguments are zeros.
     fn prepare_arguments(
         &self,
         env: &Env<B>,
         info: &ModuleInfo,
         method: &String,
-        args: protobuf::RepeatedField<ProtoArgs_Argument>,
+        args: Vec<Argument>,
     ) -> VmResult<Vec<Val>> {
-        let params_cnt: usize;
         if self.is_debug {
             let exp_it: Iter<'_, String, ExportIndex> = info.exports.iter();
 
             for k in exp_it {
-                println!("export [{}]={:?}", k.0, k.1)
+                self.log.push(LogLevel::Debug, format!("export [{}]={:?}", k.0, k.1));
             }
         }
 
-        match info.exports.get(method) {
+        let params: Vec<Type> = match info.exports.get(method) {
             Some(ExportIndex::Function(index)) => {
                 let func = info.functions.get(index.clone()).unwrap();
                 let sign = info.signatures.get(func.clone()).unwrap();
-                params_cnt = sign.params().len();
+                sign.params().to_vec()
             }
-            None => return Err(VmError::custom("method is not found")),
-            _ => return Err(VmError::custom("method is not found")),
+            None => return Err(VmError::method_not_found(method)),
+            _ => return Err(VmError::method_not_found(method)),
         };
-        if params_cnt < args.len() {
-            return Err(VmError::custom("too many arguments"));
+        if params.len() < args.len() {
+            return Err(VmError::bad_args("too many arguments"));
         }
 
         let mut wasm_args = Vec::new();
-        for v in args.iter() {
-            if v.get_is_nil() {
-                wasm_args.push(Value::I32(0));
-                continue;
-            }
-            match write_to_contract(&env.clone(), v.get_value()) {
-                Ok(p) => wasm_args.push(Value::I32(i32::try_from(p).unwrap())),
-                Err(err) => return Err(err),
-            }
+        for (i, (arg, param)) in args.iter().zip(params.iter()).enumerate() {
+            let value = match (arg, param) {
+                (Argument::Nil, Type::I32) => Value::I32(0),
+                (Argument::I32(v), Type::I32) => Value::I32(*v),
+                (Argument::Bool(v), Type::I32) => Value::I32(*v as i32),
+                (Argument::I64(v), Type::I64) => Value::I64(*v),
+                (Argument::U64(v), Type::I64) => Value::I64(*v as i64),
+                (Argument::Raw(data), Type::I32)
+                | (Argument::Bytes(data), Type::I32)
+                | (Argument::Address(data), Type::I32) => Value::I32(write_to_contract(env, data)? as i32),
+                (Argument::String(data), Type::I32) => Value::I32(write_to_contract(env, data.as_bytes())? as i32),
+                _ => return Err(VmError::bad_args(format!("argument {} doesn't match the parameter of type {:?}", i, param))),
+            };
+            wasm_args.push(value);
         }
 
-        while wasm_args.len() < params_cnt {
-            wasm_args.push(Value::I32(0));
+        for param in params.iter().skip(wasm_args.len()) {
+            match param {
+                Type::I64 => wasm_args.push(Value::I64(0)),
+                _ => wasm_args.push(Value::I32(0)),
+            }
         }
         Ok(wasm_args)
     }
 
-    fn build_env(
-        &mut self,
-        code: Vec<u8>,
-        promise_result: Option<PromiseResult>,
-    ) -> VmResult<(Env<B>, Module)> {
-        let metering = Arc::new(Metering::new(self.gas_limit, cost_function));
-        let mut compiler_config = Singlepass::default();
+    fn cache_key(&self, code: &[u8]) -> CacheKey {
+        CacheKey::new(code, self.gas_schedule.version, Gatekeeper::default().config(), self.vm_config.version)
+    }
+
+    /// Returns a compiled module for the code. Modules are looked up in the in-memory cache first,
+    /// then in the artifacts persisted by the host, and compiled if both miss. Artifacts are stored
+    /// by `deploy` and replaced here once they are stale, e.g. after a gas schedule update.
+    fn load_module(&self, code: Vec<u8>) -> VmResult<Module> {
+        let key = self.cache_key(&code);
+        get_or_compile(key, || match self.load_artifact(&key) {
+            Some(Ok(module)) => Ok(module),
+            Some(Err(_)) => {
+                let module = Self::compile_module(code, Gatekeeper::default(), self.gas_schedule, self.vm_config)?;
+                self.store_artifact(&key, &module);
+                Ok(module)
+            }
+            None => Self::compile_module(code, Gatekeeper::default(), self.gas_schedule, self.vm_config),
+        })
+    }
+
+    fn compile_module(code: Vec<u8>, gatekeeper: Gatekeeper, gas_schedule: &'static GasSchedule, vm_config: &VmConfig) -> VmResult<Module> {
+        // Tables and memories are limited by the tunables, the rest has to be checked upfront.
+        if let Some(violation) = check_limits(&code, vm_config).into_iter().next() {
+            return Err(VmError::validation_err(violation.to_string()));
+        }
+        // The module is shared between calls, so the gas limit of the current call is set
+        // after instantiation, see `build_env`.
+        let metering = Arc::new(Metering::new(0, move |operator: &Operator| gas_schedule.cost(operator)));
+        let mut compiler_config = Compiler::default();
         compiler_config.push_middleware(metering);
-        compiler_config.push_middleware(Arc::new(Gatekeeper::default()));
+        compiler_config.push_middleware(Arc::new(gatekeeper));
+        // Older schedules have to compile to the same code as before.
+        if gas_schedule.memory_grow_page > 0 {
+            compiler_config.push_middleware(Arc::new(GrowMetering::new(gas_schedule.memory_grow_page)));
+        }
+        compiler_config.push_middleware(Arc::new(DeferredStart::default()));
+        // Added last, so that the instrumentation is neither metered nor checked by the gatekeeper.
+        // Older configs have to compile to the same code as before.
+        if vm_config.has_stack_limit() {
+            compiler_config.push_middleware(Arc::new(StackLimiter::new(&code, vm_config.max_stack_height)));
+        }
+        if vm_config.align_signatures {
+            compiler_config.push_middleware(Arc::new(SignatureAlignment::default()));
+        }
         let base = BaseTunables::for_target(&Target::default());
         let store = Store::new_with_tunables(
             &Universal::new(compiler_config).engine(),
-            LimitingTunables::new(base, Pages(100)),
+            LimitingTunables::new(base, vm_config.memory_limit(), vm_config.max_table_elements),
         );
-        let env = Env::new(self.api, promise_result, self.gas_limit);
-        let import_object = imports! {
-        "env" => {
-            "abort" => Function::new_native_with_env(&store, env.clone(), abort),
-            "panic" => Function::new_native_with_env(&store, env.clone(), panic),
-            "set_storage" => Function::new_native_with_env(&store, env.clone(), set_storage),
-            "get_storage" => Function::new_native_with_env(&store, env.clone(), get_storage),
-            "remove_storage" => Function::new_native_with_env(&store, env.clone(), remove_storage),
-            "block_timestamp" => Function::new_native_with_env(&store, env.clone(), block_timestamp),
-            "block_number" => Function::new_native_with_env(&store, env.clone(), block_number),
-            "block_seed" => Function::new_native_with_env(&store, env.clone(), block_seed),
-            "min_fee_per_gas" => Function::new_native_with_env(&store, env.clone(), min_fee_per_gas),
-            "network_size" => Function::new_native_with_env(&store, env.clone(), network_size),
-            "caller" => Function::new_native_with_env(&store, env.clone(), caller),
-            "original_caller" => Function::new_native_with_env(&store, env.clone(), original_caller),
-            "create_call_function_promise" => Function::new_native_with_env(&store, env.clone(), create_call_function_promise),
-            "create_deploy_contract_promise" => Function::new_native_with_env(&store, env.clone(), create_deploy_contract_promise),
-            "create_read_contract_data_promise" => Function::new_native_with_env(&store, env.clone(), create_read_contract_data_promise),
-            "create_get_identity_promise" => Function::new_native_with_env(&store, env.clone(), create_get_identity_promise),
-            "create_transfer_promise" => Function::new_native_with_env(&store, env.clone(), create_transfer_promise),
-            "promise_result" => Function::new_native_with_env(&store, env.clone(), crate::imports::promise_result),
-            "promise_then" => Function::new_native_with_env(&store, env.clone(), promise_then),
-            "own_addr" => Function::new_native_with_env(&store, env.clone(), own_addr),
-            "own_code" => Function::new_native_with_env(&store, env.clone(), own_code),
-            "contract_addr" => Function::new_native_with_env(&store, env.clone(), contract_addr),
-            "contract_addr_by_hash" => Function::new_native_with_env(&store, env.clone(), contract_addr_by_hash),
-            "code_hash" => Function::new_native_with_env(&store, env.clone(), code_hash),
-            "emit_event" => Function::new_native_with_env(&store, env.clone(), event),
-            "epoch" => Function::new_native_with_env(&store, env.clone(), epoch),
-            "pay_amount" => Function::new_native_with_env(&store, env.clone(), pay_amount),
-            "bytes_to_hex" => Function::new_native_with_env(&store, env.clone(), bytes_to_hex),
-            "block_header" =>  Function::new_native_with_env(&store, env.clone(), block_header),
-            "keccak256" =>  Function::new_native_with_env(&store, env.clone(), keccak256),
-            "global_state" =>  Function::new_native_with_env(&store, env.clone(), global_state),
-            "gas_limit" =>  Function::new_native_with_env(&store, env.clone(), gas_limit),
-            "gas_left" =>  Function::new_native_with_env(&store, env.clone(), gas_left),
-            "balance" =>  Function::new_native_with_env(&store, env.clone(), balance),
-            "burn" =>  Function::new_native_with_env(&store, env.clone(), burn),
-            "ecrecover" =>  Function::new_native_with_env(&store, env.clone(), ecrecover),
-            }
+        Ok(Module::new(&store, code)?)
+    }
+
+    /// Artifacts are only an optimization: any failure here falls back to compilation. Returns
+    /// `None` if there is no artifact and an error if the stored one can't be used.
+    fn load_artifact(&self, key: &CacheKey) -> Option<VmResult<Module>> {
+        let data = match self.api.load_artifact(&key.code_hash).0 {
+            Ok(Some(data)) => data,
+            _ => return None,
         };
-        let mut import_obj_debug = imports! {};
-        if self.is_debug {
-            import_obj_debug = imports! {
-                "env" => {
-                       "debug" =>  Function::new_native_with_env(&store, env.clone(), debug)
-                }
-            };
+        let base = BaseTunables::for_target(&Target::default());
+        let store = Store::new_with_tunables(
+            &Universal::headless().engine(),
+            LimitingTunables::new(base, self.vm_config.memory_limit(), self.vm_config.max_table_elements),
+        );
+        let res = deserialize_module(&store, key, &data);
+        if let Err(err) = &res {
+            if self.is_debug {
+                self.log.push(LogLevel::Warn, format!("artifact is rejected: {}", err));
+            }
         }
-        let resolver = import_obj_debug.chain_back(import_object);
-        let module = match Module::new(&store, code) {
-            Ok(v) => v,
+        Some(res)
+    }
+
+    fn store_artifact(&self, key: &CacheKey, module: &Module) {
+        match serialize_module(module, key) {
+            Ok(data) => {
+                let _ = self.api.store_artifact(&key.code_hash, &data);
+            }
             Err(err) => {
-                return Err(VmError::custom(format!("compilation error: {:?}", err)));
+                if self.is_debug {
+                    self.log.push(LogLevel::Warn, format!("failed to store artifact: {}", err));
+                }
             }
-        };
+        }
+    }
+
+    fn build_env(
+        &mut self,
+        code: Vec<u8>,
+        promise_results: Vec<PromiseResult>,
+    ) -> VmResult<(Env<B>, Module)> {
+        let module = self.load_module(code)?;
+        let store = module.store().clone();
+        let env = Env::new(self.api.clone(), promise_results, self.gas_limit, self.read_only, self.gas_schedule, self.log.clone());
+        let mut exports = Exports::new();
+        for (name, ..) in host_functions(self.gas_schedule) {
+            let function = host_function(&store, &env, name)
+                .ok_or_else(|| VmError::host_function_not_found(*name))?;
+            exports.insert(*name, function);
+        }
+        if self.is_debug {
+            exports.insert("debug", Function::new_native_with_env(&store, env.clone(), debug));
+        }
+        let mut resolver = ImportObject::new();
+        resolver.register("env", exports);
 
         let instance = Instance::new(&module, &resolver)?;
 
@@ -179,6 +246,11 @@ impl<B: Backend + 'static> VmRunner<B> {
         let instance_ptr = NonNull::from(wasmer_instance.as_ref());
         env.set_wasmer_instance(Some(instance_ptr));
         self._wasmer = Some(wasmer_instance);
+
+        env.set_gas_left(self.gas_limit);
+        if module.info().exports.contains_key(DEFERRED_START_EXPORT) {
+            env.call_function(DEFERRED_START_EXPORT, &[])?;
+        }
         Ok((env, module))
     }
 
@@ -186,13 +258,13 @@ impl<B: Backend + 'static> VmRunner<B> {
         &self,
         contract: Address,
         action: &FunctionCallAction,
-        promise_result: Option<PromiseResult>,
+        promise_results: Vec<PromiseResult>,
         gas_used: &mut u64,
         is_callback: bool,
     ) -> VmResult<ActionResult> {
         let ctx = InvocationContext {
             is_callback: is_callback,
-            promise_result: promise_result,
+            promise_results: promise_results,
         };
         let (res, gas) = self.api.call(
             contract,
@@ -210,7 +282,9 @@ impl<B: Backend + 'static> VmRunner<B> {
 
     pub fn refund_deposit(&self, dest: &Address, amount: &IDNA) -> () {
         if !amount.is_empty() {
-            self.api.add_balance(dest.to_vec(), amount.to_vec());
+            if let (Err(err), _) = self.api.add_balance(dest.to_vec(), amount.to_vec()) {
+                self.log.push(LogLevel::Error, format!("failed to refund deposit: {}", err));
+            }
         }
         ()
     }
@@ -227,6 +301,8 @@ impl<B: Backend + 'static> VmRunner<B> {
                 }
             }
             Action::Transfer(_) => 0,
+            Action::Batch(batch) => batch.actions.iter()
+                .fold(0, |sum: Gas, action| sum.saturating_add(self.get_gas_of_action(action))),
         };
     }
 
@@ -244,30 +320,34 @@ impl<B: Backend + 'static> VmRunner<B> {
         sum
     }
 
-    pub fn execute_promises(&self, env: Env<B>) -> Vec<ActionResult> {
+    /// Runs the promises of a call and returns their results. Fails only if the state can't be kept
+    /// consistent, in which case the whole call fails.
+    pub fn execute_promises(&self, env: Env<B>) -> VmResult<Vec<ActionResult>> {
         let promises = env.get_promises();
         if self.is_debug {
-            println!("execute promises cnt={}", promises.len());
+            self.log.push(LogLevel::Debug, format!("execute promises cnt={}", promises.len()));
         }
         if promises.is_empty() {
-            return Vec::new();
+            return Ok(Vec::new());
         }
         let iter = promises.iter();
         let mut result: Vec<ActionResult> = Vec::with_capacity(iter.len());
+        // Results by promise index, kept for joint promises.
+        let mut promise_results: Vec<Option<PromiseResult>> = Vec::with_capacity(iter.len());
 
         for p in iter {
-            match &p.action {
+            let promise_result = match &p.action {
                 Action::FunctionCall(call) => {
                     let mut gas_used = 0;
                     let action_result = self.apply_function_call(
                         p.receiver_id.to_vec(),
                         call,
-                        None,
+                        vec![],
                         &mut gas_used,
                         false,
                     );
 
-                    let promise_result = match action_result {
+                    match action_result {
                         Ok(action_res) => {
                             result.push(action_res.clone());
                             if !action_res.success {
@@ -290,9 +370,7 @@ impl<B: Backend + 'static> VmRunner<B> {
                             ));
                             Some(Failed)
                         }
-                    };
-
-                    self.run_callback(&mut result, p, promise_result)
+                    }
                 }
                 Action::DeployContract(deploy) => {
                     let (action_result, gas) = self.api.deploy(
@@ -304,7 +382,7 @@ impl<B: Backend + 'static> VmRunner<B> {
                     );
                     let gas_used = gas;
 
-                    let promise_result = match action_result {
+                    match action_result {
                         Ok(action_res) => {
                             result.push(action_res.clone());
                             if !action_res.success {
@@ -327,45 +405,168 @@ impl<B: Backend + 'static> VmRunner<B> {
                             ));
                             Some(Failed)
                         }
-                    };
-
-                    self.run_callback(&mut result, p, promise_result)
+                    }
                 }
                 Action::Transfer(t) => {
-                    //todo : handle result
-                    self.api
-                        .add_balance(p.receiver_id.clone(), t.amount.to_vec());
+                    if let (Err(err), gas_used) = self.api.add_balance(p.receiver_id.clone(), t.amount.to_vec()) {
+                        self.refund_deposit(&p.predecessor_id, &t.amount);
+                        result.push(Self::action_result_from_err(
+                            err.into(),
+                            p.receiver_id.clone(),
+                            p.action.clone(),
+                            gas_used,
+                            0,
+                        ));
+                    }
+                    None
                 }
                 Action::ReadShardedData(read_shared_data_action) => match read_shared_data_action {
                     ReadShardedDataAction::ReadContractData(req) => {
                         let action_result = self
                             .api
                             .read_contract_data(p.receiver_id.clone(), req.key.clone());
-                        let promise_result = self.execute_read_sharded_data(
+                        self.execute_read_sharded_data(
                             action_result,
                             p.receiver_id.clone(),
                             &mut result,
                             p.action.clone(),
                             req.gas_limit,
-                        );
-                        self.run_callback(&mut result, p, promise_result)
+                        )
                     }
                     ReadShardedDataAction::GetIdentity(req) => {
                         let action_result = self.api.identity(req.addr.clone());
-                        let promise_result = self.execute_read_sharded_data(
+                        self.execute_read_sharded_data(
                             action_result,
                             p.receiver_id.clone(),
                             &mut result,
                             p.action.clone(),
                             req.gas_limit,
-                        );
-                        self.run_callback(&mut result, p, promise_result)
+                        )
                     }
                 },
-                _ => {}
+                Action::Batch(batch) => Some(self.execute_batch(&mut result, p, batch)?),
+                Action::None if !p.joined.is_empty() => {
+                    let joined_results = p.joined.iter()
+                        .map(|idx| promise_results[*idx].clone().unwrap_or(PromiseResult::Empty))
+                        .collect();
+                    self.run_callback(&mut result, p, joined_results);
+                    None
+                }
+                _ => None,
+            };
+            if let Some(promise_result) = &promise_result {
+                self.run_callback(&mut result, p, vec![promise_result.clone()]);
+            }
+            promise_results.push(promise_result);
+        }
+        Ok(result)
+    }
+
+    /// Takes a snapshot before a call flushes its changes, if one of its batches may fail to revert.
+    fn batch_snapshot(&self, env: &Env<B>) -> VmResult<Option<u32>> {
+        if env.get_promises().iter().any(|p| matches!(p.action, Action::Batch(_))) {
+            Ok(Some(self.api.snapshot().0?))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Runs the promises of a call which flushed its changes after `snapshot`. If the state can't be
+    /// kept consistent, the changes of the call are reverted as well and the call fails.
+    fn execute_promises_of(&self, env: Env<B>, snapshot: Option<u32>) -> VmResult<Vec<ActionResult>> {
+        self.execute_promises(env).or_else(|err| {
+            if let Some(snapshot) = snapshot {
+                self.api.revert_to_snapshot(snapshot).0?;
+            }
+            Err(err)
+        })
+    }
+
+    /// Runs the actions of a batch promise in order, each one adds its result, so the results of a
+    /// batch match its actions up to the failed one. If an action fails, the changes of the whole
+    /// batch are reverted and the deposits of all its actions are refunded. A batch which can't be
+    /// reverted is an error, the calling action is then reverted by `execute_promises_of`.
+    fn execute_batch(&self, result: &mut Vec<ActionResult>, p: &Promise, batch: &BatchAction) -> VmResult<PromiseResult> {
+        let snapshot = match self.api.snapshot().0 {
+            Ok(id) => id,
+            Err(err) => {
+                self.refund_batch(p, batch);
+                result.push(Self::action_result_from_err(err.into(), p.receiver_id.clone(), p.action.clone(), 0, 0));
+                return Ok(Failed);
+            }
+        };
+        let first_result = result.len();
+        let mut promise_result = PromiseResult::Empty;
+        for action in &batch.actions {
+            let action_result = match action {
+                Action::FunctionCall(call) => {
+                    let mut gas_used = 0;
+                    self.apply_function_call(p.receiver_id.to_vec(), call, vec![], &mut gas_used, false)
+                        .unwrap_or_else(|err| Self::action_result_from_err(
+                            err,
+                            p.receiver_id.clone(),
+                            action.clone(),
+                            gas_used,
+                            call.gas_limit,
+                        ))
+                }
+                Action::DeployContract(deploy) => {
+                    let (action_result, gas_used) = self.api.deploy(
+                        &deploy.code,
+                        &deploy.args,
+                        &deploy.nonce,
+                        &deploy.deposit,
+                        deploy.gas_limit,
+                    );
+                    action_result.unwrap_or_else(|err| Self::action_result_from_err(
+                        err.into(),
+                        p.receiver_id.clone(),
+                        action.clone(),
+                        gas_used,
+                        deploy.gas_limit,
+                    ))
+                }
+                Action::Transfer(t) => match self.api.add_balance(p.receiver_id.clone(), t.amount.to_vec()) {
+                    (Ok(()), gas_used) => Self::action_result_from_success(action.clone(), p.receiver_id.clone(), vec![], gas_used, 0),
+                    (Err(err), gas_used) => Self::action_result_from_err(err.into(), p.receiver_id.clone(), action.clone(), gas_used, 0),
+                },
+                // `Env::add_batch_action` accepts nothing else.
+                _ => continue,
             };
+            let success = action_result.success;
+            promise_result = if action_result.output_data.is_empty() {
+                PromiseResult::Empty
+            } else {
+                PromiseResult::Value(action_result.output_data.clone())
+            };
+            result.push(action_result);
+            if !success {
+                self.api.revert_to_snapshot(snapshot).0?;
+                let reverted = VmError::batch_reverted();
+                let last = result.len() - 1;
+                for res in &mut result[first_result..last] {
+                    res.success = false;
+                    res.error = reverted.to_string();
+                    res.error_code = reverted.code();
+                    res.write_set.clear();
+                    res.events.clear();
+                }
+                self.refund_batch(p, batch);
+                return Ok(Failed);
+            }
+        }
+        Ok(promise_result)
+    }
+
+    fn refund_batch(&self, p: &Promise, batch: &BatchAction) {
+        for action in &batch.actions {
+            match action {
+                Action::FunctionCall(call) => self.refund_deposit(&p.predecessor_id, &call.deposit),
+                Action::DeployContract(deploy) => self.refund_deposit(&p.predecessor_id, &deploy.deposit),
+                Action::Transfer(t) => self.refund_deposit(&p.predecessor_id, &t.amount),
+                _ => {}
+            }
         }
-        result
     }
 
     fn execute_read_sharded_data(
@@ -432,7 +633,7 @@ impl<B: Backend + 'static> VmRunner<B> {
         &self,
         result: &mut Vec<ActionResult>,
         p: &Promise,
-        promise_result: Option<PromiseResult>,
+        promise_results: Vec<PromiseResult>,
     ) {
         if p.action_callback.is_some() {
             let action = p.action_callback.as_ref().unwrap().clone();
@@ -442,7 +643,7 @@ impl<B: Backend + 'static> VmRunner<B> {
                     let action_result = self.apply_function_call(
                         self.contact_addr.clone(),
                         &call,
-                        promise_result,
+                        promise_results,
                         &mut gas_used,
                         true,
                     );
@@ -467,6 +668,11 @@ impl<B: Backend + 'static> VmRunner<B> {
         }
     }
     pub fn deploy(mut self, code: Vec<u8>, arg_bytes: &[u8], gas_used: &mut u64) -> ActionResult {
+        let res = self.run_deploy(code, arg_bytes, gas_used);
+        self.hand_over_log("deploy", res)
+    }
+
+    fn run_deploy(&mut self, code: Vec<u8>, arg_bytes: &[u8], gas_used: &mut u64) -> ActionResult {
         *gas_used = BASE_DEPLOY_COST;
         let input_action = Action::DeployContract(DeployContractAction {
             gas_limit: self.gas_limit,
@@ -476,8 +682,17 @@ impl<B: Backend + 'static> VmRunner<B> {
             code: vec![], // drop code
         });
         let addr = self.contact_addr.clone();
+        // The ABI is optional, but a contract must not be deployed with a broken one.
+        unwrap_or_action_res!(
+            read_abi(&code).map_err(|msg| VmError::validation_err(format!("invalid abi: {}", msg))),
+            input_action,
+            *gas_used,
+            self.gas_limit,
+            addr
+        );
+        let key = self.cache_key(&code);
         let (env, module) = unwrap_or_action_res!(
-            self.build_env(code, None),
+            self.build_env(code, vec![]),
             input_action,
             *gas_used,
             self.gas_limit,
@@ -490,13 +705,18 @@ impl<B: Backend + 'static> VmRunner<B> {
             self.gas_limit,
             self.contact_addr.clone()
         );
-        unwrap_or_action_res!(
-            self.deploy_with_env(env, module, input_action.clone(), arg_bytes, gas_used),
+        let res = unwrap_or_action_res!(
+            self.deploy_with_env(env, module.clone(), input_action.clone(), arg_bytes, gas_used),
             input_action,
             *gas_used,
             self.gas_limit,
             addr
-        )
+        );
+        // Later executions load the artifact instead of compiling the code again.
+        if res.success {
+            self.store_artifact(&key, &module);
+        }
+        res
     }
     pub fn deploy_with_env(
         &self,
@@ -507,6 +727,9 @@ impl<B: Backend + 'static> VmRunner<B> {
         gas_used: &mut u64,
     ) -> VmResult<ActionResult> {
         let args = convert_args(arg_bytes)?;
+        if self.is_debug {
+            self.log.push(LogLevel::Debug, format!("deploy: args={:?}, gas limit={}", args, self.gas_limit));
+        }
 
         let required_export = ["allocate", "deploy", "memory"];
         let module_info = module.info();
@@ -514,7 +737,7 @@ impl<B: Backend + 'static> VmRunner<B> {
             match module_info.exports.get(export) {
                 Some(_) => continue,
                 None => {
-                    return Err(VmError::custom(format!(
+                    return Err(VmError::validation_err(format!(
                         "not found required export: {}",
                         export
                     )));
@@ -525,7 +748,12 @@ impl<B: Backend + 'static> VmRunner<B> {
         let wasm_args =
             self.prepare_arguments(&env.clone(), module.info(), &"deploy".to_string(), args)?;
 
-        let res = env.call_function("deploy", &wasm_args);
+        let mut snapshot = None;
+        let res = env.call_function("deploy", &wasm_args)
+            .and_then(|_| {
+                snapshot = self.batch_snapshot(&env)?;
+                flush(&env, &self.contact_addr)
+            });
 
         *gas_used = self.gas_limit.saturating_sub(env.get_gas_left());
 
@@ -540,6 +768,7 @@ impl<B: Backend + 'static> VmRunner<B> {
             ));
         }
 
+        let (write_set, events) = res.unwrap_or_default();
         let mut res = Self::action_result_from_success(
             input_action,
             self.contact_addr.clone(),
@@ -547,7 +776,20 @@ impl<B: Backend + 'static> VmRunner<B> {
             *gas_used,
             self.gas_limit,
         );
-        res.append_sub_action_results(self.execute_promises(env));
+        res.write_set = write_set;
+        res.events = events;
+        match self.execute_promises_of(env, snapshot) {
+            Ok(results) => res.append_sub_action_results(results),
+            Err(err) => {
+                return Ok(Self::action_result_from_err(
+                    err,
+                    self.contact_addr.clone(),
+                    res.input_action,
+                    *gas_used,
+                    self.gas_limit,
+                ));
+            }
+        }
 
         let gas_refund = res
             .sub_action_results
@@ -567,6 +809,17 @@ impl<B: Backend + 'static> VmRunner<B> {
         method: &String,
         arg_bytes: &[u8],
         gas_used: &mut u64,
+    ) -> ActionResult {
+        let res = self.run_execute(code, method, arg_bytes, gas_used);
+        self.hand_over_log(method, res)
+    }
+
+    fn run_execute(
+        &mut self,
+        code: Vec<u8>,
+        method: &String,
+        arg_bytes: &[u8],
+        gas_used: &mut u64,
     ) -> ActionResult {
         let input_action = Action::FunctionCall(FunctionCallAction {
             gas_limit: self.gas_limit,
@@ -576,14 +829,16 @@ impl<B: Backend + 'static> VmRunner<B> {
         });
         let invocation_ctx = self.ctx.clone().unwrap_or_default();
         let (env, module) = unwrap_or_action_res!(
-            self.build_env(code, invocation_ctx.promise_result),
+            self.build_env(code, invocation_ctx.promise_results),
             input_action,
             *gas_used,
             self.gas_limit,
             self.contact_addr.clone()
         );
+        // Queries are not transactions, only the execution is charged.
+        let base_cost = if self.read_only { 0 } else { BASE_CALL_COST };
         unwrap_or_action_res!(
-            process_gas_info(&env, BASE_CALL_COST),
+            process_gas_info(&env, base_cost),
             input_action,
             *gas_used,
             self.gas_limit,
@@ -606,6 +861,46 @@ impl<B: Backend + 'static> VmRunner<B> {
         )
     }
 
+    /// Calls a method without changing the state. Every import which would change it, including
+    /// the creation of promises, fails the call.
+    pub fn query(
+        mut self,
+        code: Vec<u8>,
+        method: &String,
+        arg_bytes: &[u8],
+        gas_used: &mut u64,
+    ) -> ActionResult {
+        self.read_only = true;
+        self.execute(code, method, arg_bytes, gas_used)
+    }
+
+    /// Serializes the value returned by a method the way typed arguments are encoded. An `i32` is
+    /// a pointer to the region of the output, unless the ABI declares an `i32` or a `bool`. Older
+    /// schedules take any value as a pointer.
+    fn read_output(env: &Env<B>, module: &Module, method: &str, val: &[Value]) -> VmResult<Vec<u8>> {
+        let typed = env.gas_schedule().typed_returns;
+        let ptr = match val.first() {
+            Some(Value::I64(v)) if !typed => *v as i32,
+            Some(Value::I32(v)) if !typed => *v,
+            Some(Value::I64(v)) => return Ok(v.to_le_bytes().to_vec()),
+            Some(Value::I32(v)) => match returned_type(module, method) {
+                Some(AbiType::I32) => return Ok(v.to_le_bytes().to_vec()),
+                Some(AbiType::Bool) => return Ok(vec![(*v != 0) as u8]),
+                _ => *v,
+            },
+            _ => 0,
+        };
+        if ptr <= 0 {
+            return Ok(vec![]);
+        }
+        match read_from_contract(env, ptr as u32, MAX_RETURN_VALUE_SIZE) {
+            Ok(data) => Ok(data),
+            Err(VmError::OutOfGas) => Err(VmError::out_of_gas()),
+            // Malformed return values are ignored.
+            Err(_) => Ok(vec![]),
+        }
+    }
+
     pub fn execute_with_env(
         &self,
         env: Env<B>,
@@ -617,36 +912,30 @@ impl<B: Backend + 'static> VmRunner<B> {
         is_callback: bool,
     ) -> VmResult<ActionResult> {
         if method == "deploy" {
-            return Err(VmError::custom("direct call to deploy is forbidden'"));
+            return Err(VmError::forbidden_method(method));
+        }
+        if method == DEFERRED_START_EXPORT {
+            return Err(VmError::forbidden_method(method));
         }
         if !is_callback && method.starts_with("_") {
-            return Err(VmError::custom(
-                "direct call to promise callback is forbidden'",
-            ));
+            return Err(VmError::forbidden_method(method));
         }
 
         let args = convert_args(arg_bytes)?;
+        if self.is_debug {
+            self.log.push(LogLevel::Debug, format!("call {}: args={:?}, gas limit={}", method, args, self.gas_limit));
+        }
 
         let wasm_args = self.prepare_arguments(&env.clone(), module.info(), &method, args)?;
         let mut output_data = vec![];
+        let mut snapshot = None;
         let res = match env.call_function(method.as_str(), &wasm_args) {
-            Ok(val) => {
-                let mut ptr = 0;
-                if !val.is_empty() {
-                    ptr = match val[0] {
-                        Value::I32(v) => v,
-                        Value::I64(v) => v as i32,
-                        _ => 0,
-                    };
-                }
-                if ptr > 0 {
-                    output_data = read_region(&env.memory(), ptr as u32, MAX_RETURN_VALUE_SIZE)
-                        .unwrap_or(vec![]);
-                }
-                Ok(())
-            }
+            Ok(val) => Self::read_output(&env, &module, method, &val).map(|data| output_data = data),
             Err(err) => Err(err),
-        };
+        }.and_then(|_| {
+            snapshot = self.batch_snapshot(&env)?;
+            flush(&env, &self.contact_addr)
+        });
         *gas_used = self.gas_limit.saturating_sub(env.get_gas_left());
         if res.is_err() {
             *gas_used = gas_used.saturating_sub(self.unused_promise_gas(env));
@@ -659,6 +948,7 @@ impl<B: Backend + 'static> VmRunner<B> {
             ));
         }
 
+        let (write_set, events) = res.unwrap_or_default();
         let mut res = Self::action_result_from_success(
             input_action,
             self.contact_addr.clone(),
@@ -666,7 +956,20 @@ impl<B: Backend + 'static> VmRunner<B> {
             *gas_used,
             self.gas_limit,
         );
-        res.append_sub_action_results(self.execute_promises(env));
+        res.write_set = write_set;
+        res.events = events;
+        match self.execute_promises_of(env, snapshot) {
+            Ok(results) => res.append_sub_action_results(results),
+            Err(err) => {
+                return Ok(Self::action_result_from_err(
+                    err,
+                    self.contact_addr.clone(),
+                    res.input_action,
+                    *gas_used,
+                    self.gas_limit,
+                ));
+            }
+        }
 
         let gas_refund = res
             .sub_action_results
@@ -677,12 +980,28 @@ impl<B: Backend + 'static> VmRunner<B> {
         }
         res.gas_used = *gas_used;
         res.remaining_gas += gas_refund;
-        if self.is_debug {
-            println!("action result={:?}", res);
-        }
         Ok(res)
     }
 
+    /// Stamps the log of the execution with its source. The records are returned in the result in
+    /// debug mode and passed to the backend otherwise.
+    fn hand_over_log(&self, method: &str, mut res: ActionResult) -> ActionResult {
+        for (level, message) in self.log.take() {
+            let record = LogRecord {
+                level,
+                contract: self.contact_addr.clone(),
+                method: method.to_string(),
+                message,
+            };
+            if self.is_debug {
+                res.logs.push(record);
+            } else {
+                let _ = self.api.log(&record);
+            }
+        }
+        res
+    }
+
     fn action_result_from_err(
         err: VmError,
         contract: Address,
@@ -693,12 +1012,16 @@ impl<B: Backend + 'static> VmRunner<B> {
         ActionResult {
             contract: contract,
             error: err.to_string(),
+            error_code: err.code(),
             success: false,
             gas_used: gas_used,
             remaining_gas: gas_limit.saturating_sub(gas_used),
             input_action: input_action,
             sub_action_results: vec![],
             output_data: vec![],
+            write_set: vec![],
+            events: vec![],
+            logs: vec![],
         }
     }
 
@@ -712,12 +1035,16 @@ impl<B: Backend + 'static> VmRunner<B> {
         ActionResult {
             contract: contract,
             error: String::new(),
+            error_code: ERROR_NONE,
             success: true,
             gas_used: gas_used,
             remaining_gas: gas_limit.saturating_sub(gas_used),
             input_action: input_action,
             sub_action_results: vec![],
             output_data: output_data,
+            write_set: vec![],
+            events: vec![],
+            logs: vec![],
         }
     }
 }
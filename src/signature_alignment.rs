@@ -0,0 +1,77 @@
+use loupe::MemoryUsage;
+use wasmer::{
+    FunctionMiddleware, FunctionType, LocalFunctionIndex, MiddlewareError, MiddlewareReaderState,
+    ModuleMiddleware,
+};
+use wasmer::wasmparser::Operator;
+use wasmer_types::ModuleInfo;
+
+/// A middleware that keeps the number of signatures of a module even.
+///
+/// The instance context starts with a 4 byte id per signature and is followed by the imported
+/// functions without any padding. With an odd number of signatures the imported functions are
+/// misaligned and wasmer copies them there through a misaligned pointer, which is undefined
+/// behaviour. An unused signature is appended in this case, the indices of the others are kept.
+///
+/// Neither the metered operators nor the signature checks of `call_indirect` change, but the
+/// layout of the instance and so the compiled code do. It is only enabled by VM configs with
+/// `align_signatures`, older ones compile to the same code as before.
+#[derive(Debug, MemoryUsage, Default)]
+#[non_exhaustive]
+pub struct SignatureAlignment {}
+
+impl ModuleMiddleware for SignatureAlignment {
+    fn generate_function_middleware(&self, _: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
+        Box::new(FunctionSignatureAlignment {})
+    }
+
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) {
+        if module_info.signatures.len() % 2 == 1 {
+            module_info.signatures.push(FunctionType::new(vec![], vec![]));
+        }
+    }
+}
+
+#[derive(Debug)]
+struct FunctionSignatureAlignment {}
+
+impl FunctionMiddleware for FunctionSignatureAlignment {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        state.push_operator(operator);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use wasmer::{CompilerConfig, Module, Store};
+    use wasmer_engine_universal::Universal;
+
+    use crate::runner::Compiler;
+
+    use super::*;
+
+    fn signatures(wat: &str, aligned: bool) -> usize {
+        let mut compiler_config = Compiler::default();
+        if aligned {
+            compiler_config.push_middleware(Arc::new(SignatureAlignment::default()));
+        }
+        let store = Store::new(&Universal::new(compiler_config).engine());
+        Module::new(&store, wat::parse_str(wat).unwrap()).unwrap().info().signatures.len()
+    }
+
+    #[test]
+    fn odd_signatures_are_padded() {
+        let odd = "(module (type (func)) (type (func (param i32))) (type (func (param i64))))";
+        assert_eq!(signatures(odd, false), 3);
+        assert_eq!(signatures(odd, true), 4);
+        let even = "(module (type (func)) (type (func (param i32))))";
+        assert_eq!(signatures(even, true), 2);
+    }
+}
@@ -0,0 +1,237 @@
+use std::sync::Mutex;
+
+use loupe::MemoryUsage;
+use wasmer::{
+    ExportIndex, FunctionMiddleware, GlobalInit, GlobalType, Instance, LocalFunctionIndex,
+    MiddlewareError, MiddlewareReaderState, ModuleMiddleware, Mutability, Type,
+};
+use wasmer::wasmparser::{
+    FuncType, ImportSectionEntryType, Operator, Parser, Payload, Result as ParserResult, TypeDef,
+    Type as WpType, TypeOrFuncType, ValidPayload, Validator,
+};
+use wasmer_types::{GlobalIndex, ModuleInfo};
+
+const MIDDLEWARE_NAME: &str = "stack_limiter";
+
+/// Name of the exported flag set when a call is rejected for exceeding the limit. Contracts can't
+/// export it, see `check_limits`.
+pub const LIMIT_EXCEEDED_EXPORT: &str = "idena_stack_limit_exceeded";
+
+#[derive(Clone, Copy, Debug, MemoryUsage)]
+struct StackGlobals {
+    height: GlobalIndex,
+    exceeded: GlobalIndex,
+}
+
+/// A middleware keeping track of the stack height, so that deep recursion fails at the same depth
+/// on every node no matter how large the native stack is.
+///
+/// A frame costs one slot plus a slot per parameter, local and value of the operand stack at its
+/// peak. Calls add the cost of the callee to the height and fail if it gets over the limit, the
+/// cost is subtracted back when the callee returns. Indirect calls are charged the cost of the most
+/// expensive function of the signature. The frame of the function called by the host and calls of
+/// imports are not counted.
+#[derive(Debug, MemoryUsage)]
+pub struct StackLimiter {
+    limit: u32,
+    /// Frame costs of all functions, `None` for imports.
+    costs: Vec<Option<u32>>,
+    /// Costs of indirect calls by type index.
+    indirect_costs: Vec<u32>,
+    globals: Mutex<Option<StackGlobals>>,
+}
+
+impl StackLimiter {
+    /// Computes the frame costs of the functions of the code. Invalid code has no costs, it is
+    /// rejected by the compiler anyway.
+    pub fn new(code: &[u8], limit: u32) -> Self {
+        let (costs, indirect_costs) = frame_costs(code, limit).unwrap_or_default();
+        StackLimiter {
+            limit,
+            costs,
+            indirect_costs,
+            globals: Mutex::new(None),
+        }
+    }
+}
+
+impl ModuleMiddleware for StackLimiter {
+    fn generate_function_middleware(&self, _: LocalFunctionIndex) -> Box<dyn FunctionMiddleware> {
+        Box::new(FunctionStackLimiter {
+            limit: self.limit,
+            costs: self.costs.clone(),
+            indirect_costs: self.indirect_costs.clone(),
+            globals: self.globals.lock().unwrap().expect("StackLimiter::transform_module_info must be called first"),
+        })
+    }
+
+    fn transform_module_info(&self, module_info: &mut ModuleInfo) {
+        let mut globals = self.globals.lock().unwrap();
+        if globals.is_some() {
+            panic!("StackLimiter::transform_module_info: the middleware can't be used for several modules");
+        }
+        let height = module_info.globals.push(GlobalType::new(Type::I32, Mutability::Var));
+        module_info.global_initializers.push(GlobalInit::I32Const(0));
+        let exceeded = module_info.globals.push(GlobalType::new(Type::I32, Mutability::Var));
+        module_info.global_initializers.push(GlobalInit::I32Const(0));
+        module_info.exports.insert(LIMIT_EXCEEDED_EXPORT.to_string(), ExportIndex::Global(exceeded));
+        *globals = Some(StackGlobals { height, exceeded });
+    }
+}
+
+#[derive(Debug)]
+struct FunctionStackLimiter {
+    limit: u32,
+    costs: Vec<Option<u32>>,
+    indirect_costs: Vec<u32>,
+    globals: StackGlobals,
+}
+
+impl FunctionMiddleware for FunctionStackLimiter {
+    fn feed<'a>(
+        &mut self,
+        operator: Operator<'a>,
+        state: &mut MiddlewareReaderState<'a>,
+    ) -> Result<(), MiddlewareError> {
+        let cost = match operator {
+            Operator::Call { function_index } => match self.costs.get(function_index as usize) {
+                Some(Some(cost)) => Some(*cost),
+                Some(None) => None,
+                None => return Err(MiddlewareError::new(MIDDLEWARE_NAME, format!("unknown function {}", function_index))),
+            },
+            Operator::CallIndirect { index, .. } => match self.indirect_costs.get(index as usize) {
+                Some(cost) => Some(*cost),
+                None => return Err(MiddlewareError::new(MIDDLEWARE_NAME, format!("unknown type {}", index))),
+            },
+            _ => None,
+        };
+        let cost = match cost {
+            Some(cost) => cost as i32,
+            None => {
+                state.push_operator(operator);
+                return Ok(());
+            }
+        };
+        let height = self.globals.height.as_u32();
+        state.extend(&[
+            Operator::GlobalGet { global_index: height },
+            Operator::I32Const { value: cost },
+            Operator::I32Add,
+            Operator::GlobalSet { global_index: height },
+            Operator::GlobalGet { global_index: height },
+            Operator::I32Const { value: self.limit as i32 },
+            Operator::I32GtU,
+            Operator::If { ty: TypeOrFuncType::Type(WpType::EmptyBlockType) },
+            Operator::I32Const { value: 1 },
+            Operator::GlobalSet { global_index: self.globals.exceeded.as_u32() },
+            Operator::Unreachable,
+            Operator::End,
+        ]);
+        state.push_operator(operator);
+        state.extend(&[
+            Operator::GlobalGet { global_index: height },
+            Operator::I32Const { value: cost },
+            Operator::I32Sub,
+            Operator::GlobalSet { global_index: height },
+        ]);
+        Ok(())
+    }
+}
+
+/// Returns whether the last call failed because the stack height limit was exceeded.
+pub fn stack_limit_exceeded(instance: &Instance) -> bool {
+    instance.exports.get_global(LIMIT_EXCEEDED_EXPORT)
+        .map_or(false, |global| global.get().i32().unwrap_or_default() != 0)
+}
+
+/// Returns the frame costs of all functions and the costs of indirect calls by type index.
+/// Costs are capped at `limit + 1`, a function costing more can never be called anyway.
+fn frame_costs(code: &[u8], limit: u32) -> ParserResult<(Vec<Option<u32>>, Vec<u32>)> {
+    let mut validator = Validator::new();
+    let mut types: Vec<Option<FuncType>> = vec![];
+    // Type indices of imported and defined functions.
+    let mut functions: Vec<u32> = vec![];
+    let mut costs: Vec<Option<u32>> = vec![];
+
+    for payload in Parser::new(0).parse_all(code) {
+        let payload = payload?;
+        match &payload {
+            Payload::TypeSection(reader) => {
+                for ty in reader.clone() {
+                    types.push(match ty? {
+                        TypeDef::Func(func) => Some(func),
+                        _ => None,
+                    });
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader.clone() {
+                    if let ImportSectionEntryType::Function(ty) = import?.ty {
+                        functions.push(ty);
+                        costs.push(None);
+                    }
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                for ty in reader.clone() {
+                    functions.push(ty?);
+                }
+            }
+            _ => {}
+        }
+        if let ValidPayload::Func(mut func, body) = validator.payload(&payload)? {
+            let params = functions.get(costs.len())
+                .and_then(|ty| types.get(*ty as usize))
+                .and_then(|ty| ty.as_ref())
+                .map_or(0, |ty| ty.params.len() as u64);
+            let mut locals = 0u64;
+            for local in body.get_locals_reader()? {
+                locals += local?.0 as u64;
+            }
+            let mut reader = body.get_binary_reader();
+            func.read_locals(&mut reader)?;
+            let mut max_height = 0;
+            while !reader.eof() {
+                let offset = reader.original_position();
+                func.op(offset, &reader.read_operator()?)?;
+                max_height = max_height.max(func.operand_stack_height());
+            }
+            let cost = 1 + params + locals + max_height as u64;
+            costs.push(Some(cost.min(limit as u64 + 1) as u32));
+        }
+    }
+
+    // The signatures are compared by value, as `call_indirect` does.
+    let indirect_costs = types.iter()
+        .map(|ty| {
+            functions.iter().zip(costs.iter())
+                .filter(|(func_ty, _)| ty.is_some() && types.get(**func_ty as usize) == Some(ty))
+                .filter_map(|(_, cost)| *cost)
+                .max()
+                .unwrap_or(0)
+        })
+        .collect();
+    Ok((costs, indirect_costs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_costs() {
+        let code = wat::parse_str(r#"(module
+          (import "env" "debug" (func (param i32)))
+          (type $binary (func (param i32 i32) (result i32)))
+          (func $add (type $binary) (i32.add (local.get 0) (local.get 1)))
+          (func $sub (param i32 i32) (result i32) (local i64 i64 i64) (i32.sub (local.get 0) (local.get 1)))
+          (func $nop))"#).unwrap();
+        let (costs, indirect_costs) = frame_costs(&code, 1000).unwrap();
+        assert_eq!(costs, vec![None, Some(5), Some(8), Some(1)]);
+        // Types are $binary, the signature of the import and the one of $nop.
+        assert_eq!(indirect_costs, vec![8, 0, 1]);
+
+        let (costs, _) = frame_costs(&code, 4).unwrap();
+        assert_eq!(costs, vec![None, Some(5), Some(5), Some(1)]);
+    }
+}
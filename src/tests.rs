@@ -1,13 +1,1177 @@
 #![allow(dead_code)]
 
+use wasmer::wasmparser::{Parser, Payload};
+
+use crate::args::{convert_args, encode_typed_args, Argument};
+use crate::config::{vm_config, VmConfig, VM_CONFIG_VERSION};
+use crate::costs::{gas_schedule, GAS_SCHEDULE_VERSION};
+use crate::crypto::tests::{sign_ed25519, sign_secp256k1};
+use crate::errors::{ERROR_BAD_ARGUMENTS, ERROR_BATCH_REVERTED, ERROR_FORBIDDEN_METHOD, ERROR_HOST_MISUSE, ERROR_JOURNAL_FULL, ERROR_METHOD_NOT_FOUND, ERROR_NONE, ERROR_OUT_OF_GAS, ERROR_READ_ONLY, ERROR_TRAP_DIVISION_BY_ZERO, ERROR_TRAP_INDIRECT_CALL, ERROR_TRAP_INTEGER_OVERFLOW, ERROR_TRAP_MEMORY_OUT_OF_BOUNDS, ERROR_TRAP_STACK_HEIGHT_EXCEEDED, ERROR_TRAP_STACK_OVERFLOW, ERROR_TRAP_UNREACHABLE, ERROR_VALIDATION};
+use crate::estimation::{GasEstimator, WriteCosts};
+use crate::imports::{host_functions, HostFunction, HOST_FUNCTIONS};
+use crate::mock::{MockBackend, MockState};
+use crate::proto::models::{Action as protoAction, ActionResult as protoActionResult, InvocationContext as protoContext};
+use crate::runner::VmRunner;
+use crate::types::{Action, ActionResult, BatchAction, Event, FunctionCallAction, InvocationContext, LogLevel, LogRecord, StorageWrite, TransferAction};
+use crate::validation::{validate_code, VIOLATION_INVALID_ABI};
 
 static CONTRACT_ERC20: &[u8] = include_bytes!("../testdata/erc20.wasm");
 
+const CONTRACT_ADDR: &[u8] = b"erc20";
+const CALLER: &[u8] = b"caller";
+// Protobuf-encoded empty argument list.
+const NO_ARGS: &[u8] = &[1];
+
+fn erc20_backend() -> MockBackend {
+    let mut state = MockState::default();
+    state.contracts.entry(CONTRACT_ADDR.to_vec()).or_default().code = CONTRACT_ERC20.to_vec();
+    MockBackend::new(state, CONTRACT_ADDR.to_vec(), CALLER.to_vec())
+}
+
+fn config() -> &'static VmConfig {
+    vm_config(VM_CONFIG_VERSION).unwrap()
+}
 
+// The contract is a debug build importing `debug`, which is only provided in debug mode.
+fn runner(backend: MockBackend, gas_limit: u64) -> VmRunner<MockBackend> {
+    let schedule = gas_schedule(GAS_SCHEDULE_VERSION).unwrap();
+    VmRunner::new(backend, CONTRACT_ADDR.to_vec(), gas_limit, schedule, config(), None, true)
+}
 
 #[test]
 fn test_deploy_erc20() {
-    let mock_backed = crate::backend::MockBackend {};
+    let backend = erc20_backend();
+    let mut gas_used = 0;
+    let res = runner(backend.clone(), 10_000_000).deploy(CONTRACT_ERC20.to_vec(), NO_ARGS, &mut gas_used);
+    assert!(res.success, "{}", res.error);
+    assert!(res.gas_used > 0);
+
+    let storage = backend.with_state(|state| state.storage_of(CONTRACT_ADDR).cloned().unwrap_or_default());
+    assert!(!storage.is_empty());
+}
+
+#[test]
+fn test_deploy_erc20_out_of_gas() {
+    let backend = erc20_backend();
     let mut gas_used = 0;
-    assert!(crate::runner::VmRunner::deploy_with_env(mock_backed, CONTRACT_ERC20.to_vec(), protobuf::RepeatedField::new(), 100000, &mut gas_used).is_ok());
-}
\ No newline at end of file
+    let res = runner(backend, 3_000_100).deploy(CONTRACT_ERC20.to_vec(), NO_ARGS, &mut gas_used);
+    assert!(!res.success);
+    assert_eq!(res.error, "Out of gas");
+    assert_eq!(res.error_code, ERROR_OUT_OF_GAS);
+}
+
+/// Bump allocator shared by the contracts below. Regions are laid out as offset | capacity | length.
+const ALLOCATE_WAT: &str = r#"
+  (global $heap (mut i32) (i32.const 1024))
+  (func (export "allocate") (param $size i32) (result i32)
+    (local $ptr i32)
+    (local.set $ptr (global.get $heap))
+    (i32.store (local.get $ptr) (i32.add (local.get $ptr) (i32.const 12)))
+    (i32.store offset=4 (local.get $ptr) (local.get $size))
+    (i32.store offset=8 (local.get $ptr) (i32.const 0))
+    (global.set $heap (i32.add (global.get $heap) (i32.add (local.get $size) (i32.const 12))))
+    (local.get $ptr))
+  (func (export "deploy"))
+"#;
+
+/// Calls `inc` or `fail` of the `callee` contract and stores the promise result under `result`.
+const CALLER_WAT: &str = r#"
+  (import "env" "create_call_function_promise" (func $call (param i32 i32 i32 i32 i32) (result i32)))
+  (import "env" "promise_then" (func $then (param i32 i32 i32 i32 i32)))
+  (import "env" "promise_result" (func $promise_result (param i32) (result i32)))
+  (import "env" "set_storage" (func $set_storage (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\06\00\00\00\06\00\00\00")
+  (data (i32.const 12) "\10\01\00\00\03\00\00\00\03\00\00\00")
+  (data (i32.const 24) "\20\01\00\00\04\00\00\00\04\00\00\00")
+  (data (i32.const 36) "\30\01\00\00\05\00\00\00\05\00\00\00")
+  (data (i32.const 48) "\40\01\00\00\06\00\00\00\06\00\00\00")
+  (data (i32.const 60) "\50\01\00\00\06\00\00\00\06\00\00\00")
+  (data (i32.const 72) "\60\01\00\00\01\00\00\00\00\00\00\00")
+  (data (i32.const 84) "\70\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 256) "callee")
+  (data (i32.const 272) "inc")
+  (data (i32.const 288) "fail")
+  (data (i32.const 304) "_done")
+  (data (i32.const 320) "result")
+  (data (i32.const 336) "failed")
+  (data (i32.const 368) "\01")
+  (func $call_then (param $method i32)
+    (call $then
+      (call $call (i32.const 0) (local.get $method) (i32.const 84) (i32.const 0) (i32.const 1000000))
+      (i32.const 36) (i32.const 84) (i32.const 0) (i32.const 1000000)))
+  (func (export "call_inc") (call $call_then (i32.const 12)))
+  (func (export "call_fail") (call $call_then (i32.const 24)))
+  (func (export "_done")
+    (local $value i32)
+    (local.set $value (call $promise_result (i32.const 72)))
+    (if (i32.eq (i32.load8_u (i32.const 352)) (i32.const 2))
+      (then (call $set_storage (i32.const 48) (local.get $value)))
+      (else (call $set_storage (i32.const 48) (i32.const 60)))))
+"#;
+
+/// Both methods write `n`, `inc` returns "done" and `fail` traps afterwards.
+const CALLEE_WAT: &str = r#"
+  (import "env" "set_storage" (func $set_storage (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 12) "\10\01\00\00\04\00\00\00\04\00\00\00")
+  (data (i32.const 256) "n")
+  (data (i32.const 272) "done")
+  (func (export "inc") (result i32)
+    (call $set_storage (i32.const 0) (i32.const 12))
+    (i32.const 12))
+  (func (export "fail")
+    (call $set_storage (i32.const 0) (i32.const 12))
+    unreachable)
+"#;
+
+const CALLEE_ADDR: &[u8] = b"callee";
+const ACCOUNT: &[u8] = b"account";
+
+fn contract(body: &str) -> Vec<u8> {
+    wat::parse_str(format!("(module {} {})", body, ALLOCATE_WAT)).unwrap()
+}
+
+/// Deploys the caller contract next to the callee one and returns the address of the caller.
+fn simulator() -> (MockBackend, Vec<u8>) {
+    let mut state = MockState::default();
+    state.contracts.entry(CALLEE_ADDR.to_vec()).or_default().code = contract(CALLEE_WAT);
+    state.balances.insert(ACCOUNT.to_vec(), 1000);
+    let backend = MockBackend::new(state, vec![], vec![]);
+    let res = backend.run_deploy(ACCOUNT.to_vec(), &contract(CALLER_WAT), NO_ARGS, &[], vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    (backend, res.contract)
+}
+
+fn stored(backend: &MockBackend, contract: &[u8], key: &[u8]) -> Option<Vec<u8>> {
+    backend.with_state(|state| state.storage_of(contract).and_then(|storage| storage.get(key).cloned()))
+}
+
+#[test]
+fn test_nested_call_with_callback() {
+    let (backend, caller) = simulator();
+    let res = backend.run_call(ACCOUNT.to_vec(), caller.clone(), "call_inc", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+
+    assert_eq!(res.sub_action_results.len(), 2);
+    let call = &res.sub_action_results[0];
+    assert!(call.success, "{}", call.error);
+    assert_eq!(call.contract, CALLEE_ADDR);
+    assert_eq!(call.output_data, b"done");
+    assert!(res.sub_action_results[1].success, "{}", res.sub_action_results[1].error);
+
+    assert_eq!(stored(&backend, CALLEE_ADDR, b"n"), Some(b"done".to_vec()));
+    assert_eq!(stored(&backend, &caller, b"result"), Some(b"done".to_vec()));
+}
+
+#[test]
+fn test_failed_nested_call_is_reverted() {
+    let (backend, caller) = simulator();
+    let res = backend.run_call(ACCOUNT.to_vec(), caller.clone(), "call_fail", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    assert!(!res.sub_action_results[0].success);
+
+    assert_eq!(stored(&backend, CALLEE_ADDR, b"n"), None);
+    assert_eq!(stored(&backend, &caller, b"result"), Some(b"failed".to_vec()));
+}
+
+#[test]
+fn test_failed_call_refunds_amount() {
+    let (backend, _) = simulator();
+    let res = backend.run_call(ACCOUNT.to_vec(), CALLEE_ADDR.to_vec(), "fail", NO_ARGS, vec![100], 10_000_000).unwrap();
+    assert!(!res.success);
+    backend.with_state(|state| {
+        assert_eq!(state.balance_of(ACCOUNT), 1000);
+        assert_eq!(state.balance_of(CALLEE_ADDR), 0);
+    });
+
+    let res = backend.run_call(ACCOUNT.to_vec(), CALLEE_ADDR.to_vec(), "inc", NO_ARGS, vec![100], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    backend.with_state(|state| {
+        assert_eq!(state.balance_of(ACCOUNT), 900);
+        assert_eq!(state.balance_of(CALLEE_ADDR), 100);
+    });
+}
+
+#[test]
+fn test_rejected_execution_keeps_state() {
+    let (backend, _) = simulator();
+    let before = backend.with_state(|state| (state.contracts.len(), state.call_stack.len()));
+    let err = backend.run_deploy(ACCOUNT.to_vec(), &contract(CALLEE_WAT), NO_ARGS, &[], vec![3, 232, 1], 10_000_000);
+    assert!(err.is_err());
+    backend.with_state(|state| state.gas_schedule_version = Some(0));
+    let err = backend.run_deploy(ACCOUNT.to_vec(), &contract(CALLEE_WAT), NO_ARGS, &[], vec![], 10_000_000);
+    assert!(err.is_err());
+    let after = backend.with_state(|state| (state.contracts.len(), state.call_stack.len()));
+    assert_eq!(before, after);
+    assert_eq!(backend.with_state(|state| state.balance_of(ACCOUNT)), 1000);
+}
+
+/// Joins calls of `inc` and `fail` of the `callee` contract, the callback stores the result of
+/// `inc` under `result` once both are done.
+const JOIN_WAT: &str = r#"
+  (import "env" "create_call_function_promise" (func $call (param i32 i32 i32 i32 i32) (result i32)))
+  (import "env" "promise_then" (func $then (param i32 i32 i32 i32 i32)))
+  (import "env" "promise_and" (func $and (param i32) (result i32)))
+  (import "env" "promise_results_count" (func $count (result i32)))
+  (import "env" "promise_result_at" (func $result_at (param i32 i32) (result i32)))
+  (import "env" "set_storage" (func $set_storage (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\06\00\00\00\06\00\00\00")
+  (data (i32.const 12) "\10\01\00\00\03\00\00\00\03\00\00\00")
+  (data (i32.const 24) "\20\01\00\00\04\00\00\00\04\00\00\00")
+  (data (i32.const 36) "\30\01\00\00\07\00\00\00\07\00\00\00")
+  (data (i32.const 48) "\40\01\00\00\06\00\00\00\06\00\00\00")
+  (data (i32.const 60) "\50\01\00\00\08\00\00\00\08\00\00\00")
+  (data (i32.const 72) "\60\01\00\00\01\00\00\00\00\00\00\00")
+  (data (i32.const 84) "\70\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 96) "\80\01\00\00\01\00\00\00\00\00\00\00")
+  (data (i32.const 256) "callee")
+  (data (i32.const 272) "inc")
+  (data (i32.const 288) "fail")
+  (data (i32.const 304) "_joined")
+  (data (i32.const 320) "result")
+  (data (i32.const 336) "\00\00\00\00\01\00\00\00")
+  (data (i32.const 368) "\01")
+  (func $call_callee (param $method i32) (result i32)
+    (call $call (i32.const 0) (local.get $method) (i32.const 84) (i32.const 0) (i32.const 1000000)))
+  (func (export "call_both")
+    (drop (call $call_callee (i32.const 12)))
+    (drop (call $call_callee (i32.const 24)))
+    (call $then (call $and (i32.const 60)) (i32.const 36) (i32.const 84) (i32.const 0) (i32.const 1000000)))
+  (func (export "join_nothing")
+    (drop (call $and (i32.const 60))))
+  (func (export "count_nothing")
+    (if (i32.ne (call $count) (i32.const 0)) (then unreachable)))
+  (func (export "_joined")
+    (local $value i32)
+    (if (i32.ne (call $count) (i32.const 2)) (then unreachable))
+    (local.set $value (call $result_at (i32.const 0) (i32.const 72)))
+    (drop (call $result_at (i32.const 1) (i32.const 96)))
+    (if (i32.ne (i32.load8_u (i32.const 352)) (i32.const 2)) (then unreachable))
+    (if (i32.ne (i32.load8_u (i32.const 384)) (i32.const 0)) (then unreachable))
+    (call $set_storage (i32.const 48) (local.get $value)))
+"#;
+
+#[test]
+fn test_joint_promise_callback() {
+    let mut state = MockState::default();
+    state.contracts.entry(CALLEE_ADDR.to_vec()).or_default().code = contract(CALLEE_WAT);
+    let backend = MockBackend::new(state, vec![], vec![]);
+    let res = backend.run_deploy(ACCOUNT.to_vec(), &contract(JOIN_WAT), NO_ARGS, &[], vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    let caller = res.contract;
+
+    let res = backend.run_call(ACCOUNT.to_vec(), caller.clone(), "call_both", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    assert_eq!(res.sub_action_results.len(), 3);
+    let callback = &res.sub_action_results[2];
+    assert!(callback.success, "{}", callback.error);
+    assert_eq!(stored(&backend, &caller, b"result"), Some(b"done".to_vec()));
+
+    // The joined promises must exist.
+    let res = backend.run_call(ACCOUNT.to_vec(), caller.clone(), "join_nothing", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.error.contains("invalid promise_idx"), "{}", res.error);
+
+    // A plain call has no promise results, neither does the context the host passes for it.
+    let res = backend.run_call(ACCOUNT.to_vec(), caller, "count_nothing", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    assert!(InvocationContext::from(protoContext::default()).promise_results.is_empty());
+}
+
+/// Sends 5 coins to the `callee` contract and calls it in one batch, the callback stores the
+/// result of the batch under `result`.
+const BATCH_WAT: &str = r#"
+  (import "env" "promise_batch_create" (func $create (param i32) (result i32)))
+  (import "env" "promise_batch_action_transfer" (func $transfer (param i32 i32)))
+  (import "env" "promise_batch_action_function_call" (func $call (param i32 i32 i32 i32 i32)))
+  (import "env" "promise_batch_action_deploy" (func $deploy (param i32 i32 i32 i32 i32 i32)))
+  (import "env" "promise_then" (func $then (param i32 i32 i32 i32 i32)))
+  (import "env" "promise_result" (func $promise_result (param i32) (result i32)))
+  (import "env" "set_storage" (func $set_storage (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\06\00\00\00\06\00\00\00")
+  (data (i32.const 12) "\10\01\00\00\03\00\00\00\03\00\00\00")
+  (data (i32.const 24) "\20\01\00\00\04\00\00\00\04\00\00\00")
+  (data (i32.const 36) "\30\01\00\00\08\00\00\00\08\00\00\00")
+  (data (i32.const 48) "\40\01\00\00\06\00\00\00\06\00\00\00")
+  (data (i32.const 60) "\50\01\00\00\06\00\00\00\06\00\00\00")
+  (data (i32.const 72) "\60\01\00\00\01\00\00\00\00\00\00\00")
+  (data (i32.const 84) "\70\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 96) "\80\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 108) "\90\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 256) "callee")
+  (data (i32.const 272) "inc")
+  (data (i32.const 288) "fail")
+  (data (i32.const 304) "_batched")
+  (data (i32.const 320) "result")
+  (data (i32.const 336) "failed")
+  (data (i32.const 368) "\01")
+  (data (i32.const 384) "\05")
+  (data (i32.const 400) "x")
+  (func $batch (result i32)
+    (local $idx i32)
+    (local.set $idx (call $create (i32.const 0)))
+    (call $transfer (local.get $idx) (i32.const 96))
+    (call $call (local.get $idx) (i32.const 12) (i32.const 84) (i32.const 0) (i32.const 1000000))
+    (local.get $idx))
+  (func $then_batched (param $idx i32)
+    (call $then (local.get $idx) (i32.const 36) (i32.const 84) (i32.const 0) (i32.const 1000000)))
+  (func (export "batch_inc")
+    (call $then_batched (call $batch)))
+  (func (export "batch_fail")
+    (local $idx i32)
+    (local.set $idx (call $batch))
+    (call $call (local.get $idx) (i32.const 24) (i32.const 84) (i32.const 0) (i32.const 1000000))
+    (call $then_batched (local.get $idx)))
+  (func (export "deploy_elsewhere")
+    (call $deploy (call $batch) (i32.const 108) (i32.const 84) (i32.const 0) (i32.const 0) (i32.const 1000000)))
+  (func (export "_batched")
+    (local $value i32)
+    (local.set $value (call $promise_result (i32.const 72)))
+    (if (i32.eq (i32.load8_u (i32.const 352)) (i32.const 2))
+      (then (call $set_storage (i32.const 48) (local.get $value)))
+      (else (call $set_storage (i32.const 48) (i32.const 60)))))
+"#;
+
+#[test]
+fn test_batch_promise_is_atomic() {
+    let mut state = MockState::default();
+    state.contracts.entry(CALLEE_ADDR.to_vec()).or_default().code = contract(CALLEE_WAT);
+    state.balances.insert(ACCOUNT.to_vec(), 1000);
+    let backend = MockBackend::new(state, vec![], vec![]);
+    let res = backend.run_deploy(ACCOUNT.to_vec(), &contract(BATCH_WAT), NO_ARGS, &[], vec![100], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    let caller = res.contract;
+
+    let res = backend.run_call(ACCOUNT.to_vec(), caller.clone(), "batch_fail", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    // Results of the transfer, `inc`, `fail` and the callback.
+    assert_eq!(res.sub_action_results.len(), 4);
+    for reverted in &res.sub_action_results[..2] {
+        assert!(!reverted.success);
+        assert_eq!(reverted.error_code, ERROR_BATCH_REVERTED, "{}", reverted.error);
+    }
+    assert!(!res.sub_action_results[2].success);
+    assert_ne!(res.sub_action_results[2].error_code, ERROR_BATCH_REVERTED);
+    assert_eq!(stored(&backend, CALLEE_ADDR, b"n"), None);
+    assert_eq!(stored(&backend, &caller, b"result"), Some(b"failed".to_vec()));
+    backend.with_state(|state| {
+        assert_eq!(state.balance_of(&caller), 100);
+        assert_eq!(state.balance_of(CALLEE_ADDR), 0);
+    });
+
+    let res = backend.run_call(ACCOUNT.to_vec(), caller.clone(), "batch_inc", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    // Results of the transfer, `inc` and the callback.
+    assert_eq!(res.sub_action_results.len(), 3);
+    assert!(matches!(res.sub_action_results[0].input_action, Action::Transfer(_)));
+    assert!(res.sub_action_results[0].success);
+    assert_eq!(stored(&backend, CALLEE_ADDR, b"n"), Some(b"done".to_vec()));
+    assert_eq!(stored(&backend, &caller, b"result"), Some(b"done".to_vec()));
+    backend.with_state(|state| {
+        assert_eq!(state.balance_of(&caller), 95);
+        assert_eq!(state.balance_of(CALLEE_ADDR), 5);
+    });
+
+    let res = backend.run_call(ACCOUNT.to_vec(), caller, "deploy_elsewhere", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.error.contains("deployed contract is not the batch receiver"), "{}", res.error);
+}
+
+#[test]
+fn test_batch_action_is_encoded() {
+    let batch = Action::Batch(BatchAction {
+        actions: vec![
+            Action::Transfer(TransferAction { amount: vec![5] }),
+            Action::FunctionCall(FunctionCallAction { method_name: "inc".to_string(), args: vec![1], gas_limit: 1000, deposit: vec![] }),
+        ],
+    });
+    let proto: protoAction = (&batch).into();
+    assert_eq!(proto.actions.len(), 2);
+    assert_eq!(format!("{:?}", Action::from(proto)), format!("{:?}", batch));
+}
+
+fn signature_count(code: &[u8]) -> u32 {
+    Parser::new(0).parse_all(code)
+        .find_map(|payload| match payload {
+            Ok(Payload::TypeSection(reader)) => Some(reader.get_count()),
+            _ => None,
+        })
+        .unwrap_or(0)
+}
+
+/// wasmer lays the imported functions out right after the signature ids, an odd number of
+/// signatures misaligns them unless the VM config aligns the signatures.
+#[test]
+fn test_odd_number_of_signatures() {
+    let code = contract(r#"
+      (import "env" "set_storage" (func $set_storage (param i32 i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 0) "\00\01\00\00\01\00\00\00\01\00\00\00")
+      (data (i32.const 256) "n")
+      (func (export "run") (call $set_storage (i32.const 0) (i32.const 0)))
+    "#);
+    assert_eq!(signature_count(&code) % 2, 1);
+    let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+    let res = runner(backend.clone(), 10_000_000).execute(code, &"run".to_string(), NO_ARGS, &mut 0);
+    assert!(res.success, "{}", res.error);
+    assert_eq!(stored(&backend, CONTRACT_ADDR, b"n"), Some(b"n".to_vec()));
+}
+
+fn importing(functions: &[&HostFunction]) -> Vec<u8> {
+    let imports: String = functions.iter().map(|(name, params, results, _)| {
+        let types = |types: &[wasmer::Type]| types.iter().map(|t| format!(" {}", t.to_string().to_lowercase())).collect::<String>();
+        format!("(import \"env\" \"{}\" (func (param{}) (result{})))", name, types(params), types(results))
+    }).collect();
+    contract(&format!("{} (memory (export \"memory\") 1)", imports))
+}
+
+/// `validate_code` must accept exactly the imports the runner provides under each gas schedule.
+#[test]
+fn test_host_functions_match_imports() {
+    for version in 1..=GAS_SCHEDULE_VERSION {
+        let schedule = gas_schedule(version).unwrap();
+        let deploy = |code: Vec<u8>| {
+            let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+            let report = validate_code(&code, config(), schedule);
+            let res = VmRunner::new(backend, CONTRACT_ADDR.to_vec(), 10_000_000, schedule, config(), None, false).deploy(code, NO_ARGS, &mut 0);
+            (report, res)
+        };
+
+        let (report, res) = deploy(importing(&host_functions(schedule).collect::<Vec<_>>()));
+        assert!(report.is_valid(), "{}: {:?}", version, report);
+        assert!(res.success, "{}: {}", version, res.error);
+
+        for function in HOST_FUNCTIONS.iter().filter(|(_, _, _, provided)| !provided(schedule)) {
+            let (report, res) = deploy(importing(&[function]));
+            assert!(!report.is_valid(), "{}: {}", version, function.0);
+            assert!(!res.success, "{}: {}", version, function.0);
+        }
+    }
+}
+
+/// Writes and reads back `n`, emits an event and burns 1 coin.
+const ESTIMATED_WAT: &str = r#"
+  (import "env" "set_storage" (func $set_storage (param i32 i32)))
+  (import "env" "get_storage" (func $get_storage (param i32) (result i32)))
+  (import "env" "emit_event" (func $emit_event (param i32 i32)))
+  (import "env" "burn" (func $burn (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 12) "\10\01\00\00\04\00\00\00\04\00\00\00")
+  (data (i32.const 24) "\20\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 256) "n")
+  (data (i32.const 272) "done")
+  (data (i32.const 288) "\01")
+  (func (export "run") (result i32)
+    (call $set_storage (i32.const 0) (i32.const 12))
+    (call $emit_event (i32.const 0) (i32.const 12))
+    (call $burn (i32.const 24))
+    (call $get_storage (i32.const 0)))
+"#;
+
+/// Same prices as the default `MockGasCosts`.
+const MOCK_WRITE_COSTS: WriteCosts = WriteCosts {
+    set_storage: 200,
+    remove_storage: 100,
+    event: 100,
+    transfer: 100,
+    per_byte: 1,
+};
+
+fn estimator(backend: MockBackend, contract: &[u8]) -> GasEstimator<MockBackend> {
+    let schedule = gas_schedule(GAS_SCHEDULE_VERSION).unwrap();
+    GasEstimator::new(backend, MOCK_WRITE_COSTS, contract.to_vec(), 10_000_000, schedule, config(), false)
+}
+
+#[test]
+fn test_estimation_discards_changes() {
+    let mut state = MockState::default();
+    state.balances.insert(CONTRACT_ADDR.to_vec(), 10);
+    let backend = MockBackend::new(state, CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+    let estimate = estimator(backend.clone(), CONTRACT_ADDR).execute(contract(ESTIMATED_WAT), &"run".to_string(), NO_ARGS, None);
+    assert!(estimate.result.success, "{}", estimate.result.error);
+    assert_eq!(estimate.result.output_data, b"done");
+    assert_eq!(estimate.gas_limit, Some(estimate.result.gas_used));
+
+    backend.with_state(|state| {
+        assert!(state.storage_of(CONTRACT_ADDR).map_or(true, |storage| storage.is_empty()));
+        assert!(state.events.is_empty());
+        assert!(state.logs.is_empty());
+        assert_eq!(state.balance_of(CONTRACT_ADDR), 10);
+        assert_eq!(state.burnt, 0);
+    });
+
+    // Estimated code is not deployed, its artifact is not kept.
+    let estimate = estimator(backend.clone(), CALLEE_ADDR).deploy(contract(CALLEE_WAT), NO_ARGS);
+    assert!(estimate.result.success, "{}", estimate.result.error);
+    backend.with_state(|state| assert!(state.artifacts.is_empty()));
+}
+
+#[test]
+fn test_estimation_runs_promises() {
+    let (backend, caller) = simulator();
+    let estimate = estimator(backend.clone(), &caller).execute(contract(CALLER_WAT), &"call_inc".to_string(), NO_ARGS, None);
+    assert!(estimate.result.success, "{}", estimate.result.error);
+    assert_eq!(estimate.result.sub_action_results.len(), 2);
+    assert_eq!(estimate.result.sub_action_results[0].output_data, b"done");
+    let callback = &estimate.result.sub_action_results[1];
+    assert!(callback.success, "{}", callback.error);
+    let gas_limit = estimate.gas_limit.unwrap();
+    assert_eq!(stored(&backend, CALLEE_ADDR, b"n"), None);
+    assert_eq!(stored(&backend, &caller, b"result"), None);
+
+    let res = backend.run_call(ACCOUNT.to_vec(), caller.clone(), "call_inc", NO_ARGS, vec![], gas_limit).unwrap();
+    assert!(res.success, "{}", res.error);
+    assert_eq!(res.gas_used, estimate.result.gas_used);
+    assert_eq!(stored(&backend, &caller, b"result"), Some(b"done".to_vec()));
+
+    // The failed call is reverted within the estimation, the callback sees it fail.
+    let backend = simulator().0;
+    let estimate = estimator(backend.clone(), &caller).execute(contract(CALLER_WAT), &"call_fail".to_string(), NO_ARGS, None);
+    assert!(estimate.result.success, "{}", estimate.result.error);
+    assert!(!estimate.result.sub_action_results[0].success);
+    assert!(estimate.result.sub_action_results[1].success);
+    assert!(estimate.gas_limit.is_some());
+}
+
+#[test]
+fn test_estimated_gas_limit_is_minimal() {
+    let callee = contract(CALLEE_WAT);
+    let backend = MockBackend::new(MockState::default(), CALLEE_ADDR.to_vec(), ACCOUNT.to_vec());
+    let estimate = estimator(backend, CALLEE_ADDR).execute(callee.clone(), &"inc".to_string(), NO_ARGS, None);
+    assert!(estimate.result.success, "{}", estimate.result.error);
+    let gas_limit = estimate.gas_limit.unwrap();
+
+    let mut state = MockState::default();
+    state.contracts.entry(CALLEE_ADDR.to_vec()).or_default().code = callee;
+    let backend = MockBackend::new(state, vec![], vec![]);
+    let res = backend.run_call(ACCOUNT.to_vec(), CALLEE_ADDR.to_vec(), "inc", NO_ARGS, vec![], gas_limit - 1).unwrap();
+    assert!(!res.success);
+    let res = backend.run_call(ACCOUNT.to_vec(), CALLEE_ADDR.to_vec(), "inc", NO_ARGS, vec![], gas_limit).unwrap();
+    assert!(res.success, "{}", res.error);
+    assert_eq!(res.gas_used, estimate.result.gas_used);
+}
+
+#[test]
+fn test_host_calls_may_use_up_the_gas() {
+    // The storage write is the last charge of `inc`.
+    for (version, exact) in [(6, false), (7, true)] {
+        let mut state = MockState::default();
+        state.contracts.entry(CALLEE_ADDR.to_vec()).or_default().code = contract(CALLEE_WAT);
+        state.gas_schedule_version = Some(version);
+        let backend = MockBackend::new(state, vec![], vec![]);
+        let res = backend.run_call(ACCOUNT.to_vec(), CALLEE_ADDR.to_vec(), "inc", NO_ARGS, vec![], 10_000_000).unwrap();
+        assert!(res.success, "{}", res.error);
+        let res = backend.run_call(ACCOUNT.to_vec(), CALLEE_ADDR.to_vec(), "inc", NO_ARGS, vec![], res.gas_used).unwrap();
+        assert_eq!(res.success, exact, "version {}: {}", version, res.error);
+    }
+}
+
+/// Returns the value stored under `n`.
+const GETTER_WAT: &str = r#"
+  (import "env" "get_storage" (func $get_storage (param i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 256) "n")
+  (func (export "get") (param i32) (result i32)
+    (call $get_storage (i32.const 0)))
+"#;
+
+fn query(backend: MockBackend, contract_addr: &[u8], code: Vec<u8>, method: &str) -> ActionResult {
+    let schedule = gas_schedule(GAS_SCHEDULE_VERSION).unwrap();
+    VmRunner::new(backend, contract_addr.to_vec(), 10_000_000, schedule, config(), None, false)
+        .query(code, &method.to_string(), NO_ARGS, &mut 0)
+}
+
+#[test]
+fn test_query_reads_state() {
+    let mut state = MockState::default();
+    state.contracts.entry(CONTRACT_ADDR.to_vec()).or_default().storage.insert(b"n".to_vec(), b"done".to_vec());
+    let backend = MockBackend::new(state, CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+    let res = query(backend, CONTRACT_ADDR, contract(GETTER_WAT), "get");
+    assert!(res.success, "{}", res.error);
+    assert_eq!(res.output_data, b"done");
+}
+
+#[test]
+fn test_artifacts_are_stored_on_deploy() {
+    let (backend, caller) = simulator();
+    let res = backend.run_call(ACCOUNT.to_vec(), caller, "call_inc", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    // The callee is compiled for the call, only the deployed caller has an artifact.
+    assert_eq!(backend.with_state(|state| state.artifacts.len()), 1);
+}
+
+#[test]
+fn test_stale_artifacts_are_replaced() {
+    let (backend, caller) = simulator();
+    let artifact = backend.with_state(|state| state.artifacts.values().next().cloned()).unwrap();
+    // The artifact was compiled for the latest schedule, the call is run with the previous one.
+    backend.with_state(|state| state.gas_schedule_version = Some(GAS_SCHEDULE_VERSION - 1));
+    let res = backend.run_call(ACCOUNT.to_vec(), caller, "call_inc", NO_ARGS, vec![], 10_000_000).unwrap();
+    assert!(res.success, "{}", res.error);
+    backend.with_state(|state| {
+        assert_eq!(state.artifacts.len(), 1);
+        assert_ne!(state.artifacts.values().next(), Some(&artifact));
+    });
+}
+
+#[test]
+fn test_query_cannot_modify_state() {
+    let backend = MockBackend::new(MockState::default(), CALLEE_ADDR.to_vec(), CALLER.to_vec());
+    let res = query(backend.clone(), CALLEE_ADDR, contract(CALLEE_WAT), "inc");
+    assert!(!res.success);
+    assert!(res.error.contains("state modification in read-only call"), "{}", res.error);
+    assert_eq!(res.error_code, ERROR_READ_ONLY);
+    assert_eq!(stored(&backend, CALLEE_ADDR, b"n"), None);
+
+    let (backend, caller) = simulator();
+    let res = query(backend, &caller, contract(CALLER_WAT), "call_inc");
+    assert!(!res.success);
+    assert!(res.error.contains("state modification in read-only call"), "{}", res.error);
+    assert!(res.sub_action_results.is_empty());
+
+    // The callback would pay 1 coin before the promise is looked up.
+    let mut state = MockState::default();
+    state.balances.insert(CONTRACT_ADDR.to_vec(), 10);
+    let backend = MockBackend::new(state, CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+    let res = query(backend.clone(), CONTRACT_ADDR, contract(THEN_WAT), "then");
+    assert_eq!(res.error_code, ERROR_READ_ONLY, "{}", res.error);
+    assert_eq!(backend.with_state(|state| state.balance_of(CONTRACT_ADDR)), 10);
+}
+
+/// Attaches a callback paying 1 coin to a promise which doesn't exist.
+const THEN_WAT: &str = r#"
+  (import "env" "promise_then" (func $then (param i32 i32 i32 i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\03\00\00\00\03\00\00\00")
+  (data (i32.const 12) "\10\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 256) "_cb")
+  (data (i32.const 272) "\01")
+  (func (export "then")
+    (call $then (i32.const 0) (i32.const 0) (i32.const 0) (i32.const 12) (i32.const 1000)))
+"#;
+
+/// `run` writes `n`, removes `m` and returns `n` read back, `trap` writes `n` and traps.
+const JOURNAL_WAT: &str = r#"
+  (import "env" "set_storage" (func $set_storage (param i32 i32)))
+  (import "env" "get_storage" (func $get_storage (param i32) (result i32)))
+  (import "env" "remove_storage" (func $remove_storage (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 12) "\10\01\00\00\04\00\00\00\04\00\00\00")
+  (data (i32.const 24) "\20\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 256) "n")
+  (data (i32.const 272) "done")
+  (data (i32.const 288) "m")
+  (func (export "run") (param i32) (result i32)
+    (call $set_storage (i32.const 0) (i32.const 12))
+    (call $remove_storage (i32.const 24))
+    (call $get_storage (i32.const 0)))
+  (func (export "trap")
+    (call $set_storage (i32.const 0) (i32.const 12))
+    unreachable)
+"#;
+
+fn journal_backend() -> MockBackend {
+    let mut state = MockState::default();
+    state.contracts.entry(CONTRACT_ADDR.to_vec()).or_default().storage.insert(b"m".to_vec(), b"old".to_vec());
+    MockBackend::new(state, CONTRACT_ADDR.to_vec(), CALLER.to_vec())
+}
+
+#[test]
+fn test_storage_writes_are_flushed_on_success() {
+    let backend = journal_backend();
+    let res = runner(backend.clone(), 10_000_000).execute(contract(JOURNAL_WAT), &"run".to_string(), NO_ARGS, &mut 0);
+    assert!(res.success, "{}", res.error);
+    assert_eq!(res.output_data, b"done");
+    assert_eq!(res.write_set, vec![
+        StorageWrite { key: b"n".to_vec(), value: Some(b"done".to_vec()) },
+        StorageWrite { key: b"m".to_vec(), value: None },
+    ]);
+    assert_eq!(stored(&backend, CONTRACT_ADDR, b"n"), Some(b"done".to_vec()));
+    assert_eq!(stored(&backend, CONTRACT_ADDR, b"m"), None);
+}
+
+#[test]
+fn test_storage_writes_are_discarded_on_trap() {
+    let backend = journal_backend();
+    let res = runner(backend.clone(), 10_000_000).execute(contract(JOURNAL_WAT), &"trap".to_string(), NO_ARGS, &mut 0);
+    assert!(!res.success);
+    assert_eq!(res.error_code, ERROR_TRAP_UNREACHABLE);
+    assert!(res.write_set.is_empty());
+    assert_eq!(stored(&backend, CONTRACT_ADDR, b"n"), None);
+    assert_eq!(stored(&backend, CONTRACT_ADDR, b"m"), Some(b"old".to_vec()));
+}
+
+#[test]
+fn test_storage_is_not_journaled_by_old_schedules() {
+    let backend = journal_backend();
+    let schedule = gas_schedule(6).unwrap();
+    let res = VmRunner::new(backend.clone(), CONTRACT_ADDR.to_vec(), 10_000_000, schedule, config(), None, false)
+        .execute(contract(JOURNAL_WAT), &"trap".to_string(), NO_ARGS, &mut 0);
+    assert_eq!(res.error_code, ERROR_TRAP_UNREACHABLE);
+    assert!(res.write_set.is_empty());
+    // The backend is responsible for reverting the write.
+    assert_eq!(stored(&backend, CONTRACT_ADDR, b"n"), Some(b"done".to_vec()));
+}
+
+/// Writes a value of 128 KiB 40 times, either under the same key or under distinct ones.
+const FILL_WAT: &str = r#"
+  (import "env" "set_storage" (func $set_storage (param i32 i32)))
+  (memory (export "memory") 3)
+  (data (i32.const 0) "\00\01\00\00\01\00\00\00\01\00\00\00")
+  (data (i32.const 12) "\00\04\00\00\00\00\02\00\00\00\02\00")
+  (func $fill (param $distinct i32)
+    (local $i i32)
+    (loop $next
+      (if (local.get $distinct) (then (i32.store8 (i32.const 256) (local.get $i))))
+      (call $set_storage (i32.const 0) (i32.const 12))
+      (local.set $i (i32.add (local.get $i) (i32.const 1)))
+      (br_if $next (i32.lt_u (local.get $i) (i32.const 40)))))
+  (func (export "same") (call $fill (i32.const 0)))
+  (func (export "distinct") (call $fill (i32.const 1)))
+"#;
+
+#[test]
+fn test_storage_journal_is_limited() {
+    let execute = |method: &str| {
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        runner(backend, 100_000_000).execute(contract(FILL_WAT), &method.to_string(), NO_ARGS, &mut 0)
+    };
+    let res = execute("same");
+    assert!(res.success, "{}", res.error);
+    // Journaled writes are charged when they are made.
+    assert!(res.gas_used > 40 * 128 * 1024, "{}", res.gas_used);
+
+    let res = execute("distinct");
+    assert!(!res.success);
+    assert_eq!(res.error_code, ERROR_JOURNAL_FULL, "{}", res.error);
+}
+
+#[test]
+fn test_memory_copies_are_charged() {
+    let value = vec![7u8; 32 * 1024];
+    let gas_used = |version: u32| {
+        let mut state = MockState::default();
+        state.contracts.entry(CONTRACT_ADDR.to_vec()).or_default().storage.insert(b"n".to_vec(), value.clone());
+        let backend = MockBackend::new(state, CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        let schedule = gas_schedule(version).unwrap();
+        let res = VmRunner::new(backend, CONTRACT_ADDR.to_vec(), 10_000_000, schedule, config(), None, false)
+            .execute(contract(GETTER_WAT), &"get".to_string(), NO_ARGS, &mut 0);
+        assert!(res.success, "{}", res.error);
+        assert_eq!(res.output_data, value);
+        res.gas_used
+    };
+    // The key is read by the host, the value is written to the contract and read back as the result.
+    assert_eq!(gas_used(3) - gas_used(2), 1 + 2 * value.len() as u64);
+}
+
+#[test]
+fn test_errors_have_codes() {
+    let execute = |code: Vec<u8>, method: &str, args: &[u8]| {
+        let backend = journal_backend();
+        runner(backend, 10_000_000).execute(code, &method.to_string(), args, &mut 0)
+    };
+    let res = execute(contract(JOURNAL_WAT), "run", NO_ARGS);
+    assert_eq!(res.error_code, ERROR_NONE);
+    assert_eq!(execute(contract(JOURNAL_WAT), "missing", NO_ARGS).error_code, ERROR_METHOD_NOT_FOUND);
+    assert_eq!(execute(contract(JOURNAL_WAT), "deploy", NO_ARGS).error_code, ERROR_FORBIDDEN_METHOD);
+    assert_eq!(execute(contract(JOURNAL_WAT), "run", &[9]).error_code, ERROR_BAD_ARGUMENTS);
+
+    let float = contract("(memory (export \"memory\") 1) (func (export \"run\") (result f32) (f32.const 1))");
+    let res = execute(float, "run", NO_ARGS);
+    assert_eq!(res.error_code, ERROR_VALIDATION, "{}", res.error);
+}
+
+/// Each method traps with a different trap code.
+const TRAPS_WAT: &str = r#"
+  (type $binary (func (param i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (table 1 funcref)
+  (elem (i32.const 0) $nop)
+  (func $nop)
+  (func (export "unreachable") unreachable)
+  (func (export "divide") (result i32) (i32.div_u (i32.const 1) (i32.const 0)))
+  (func (export "overflow") (result i32) (i32.div_s (i32.const 0x80000000) (i32.const -1)))
+  (func (export "load") (result i32) (i32.load (i32.const 0x10000)))
+  (func (export "indirect") (result i32)
+    (call_indirect (type $binary) (i32.const 0) (i32.const 0) (i32.const 0)))
+"#;
+
+#[test]
+fn test_traps_are_classified() {
+    let cases = [
+        ("unreachable", ERROR_TRAP_UNREACHABLE),
+        ("divide", ERROR_TRAP_DIVISION_BY_ZERO),
+        ("overflow", ERROR_TRAP_INTEGER_OVERFLOW),
+        ("load", ERROR_TRAP_MEMORY_OUT_OF_BOUNDS),
+        ("indirect", ERROR_TRAP_INDIRECT_CALL),
+    ];
+    for (method, code) in cases.iter() {
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        let res = runner(backend, 10_000_000).execute(contract(TRAPS_WAT), &method.to_string(), NO_ARGS, &mut 0);
+        assert!(!res.success);
+        assert_eq!(res.error_code, *code, "{}: {}", method, res.error);
+    }
+}
+
+/// `recurse` never returns, `shallow` recurses 100 times.
+const RECURSION_WAT: &str = r#"
+  (memory (export "memory") 1)
+  (func $recurse (export "recurse") (param i32) (result i32)
+    (call $recurse (local.get 0)))
+  (func $down (param i32) (result i32)
+    (if (result i32) (local.get 0)
+      (then (call $down (i32.sub (local.get 0) (i32.const 1))))
+      (else (i32.const 0))))
+  (func (export "shallow") (param i32) (result i32)
+    (call $down (i32.const 100)))
+"#;
+
+#[test]
+fn test_stack_height_is_limited() {
+    let execute = |method: &str| {
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        runner(backend, 10_000_000).execute(contract(RECURSION_WAT), &method.to_string(), NO_ARGS, &mut 0)
+    };
+    let res = execute("shallow");
+    assert!(res.success, "{}", res.error);
+
+    let res = execute("recurse");
+    assert!(!res.success);
+    assert_eq!(res.error_code, ERROR_TRAP_STACK_HEIGHT_EXCEEDED, "{}", res.error);
+
+    // The first config leaves it to the engine.
+    let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+    let schedule = gas_schedule(GAS_SCHEDULE_VERSION).unwrap();
+    let res = VmRunner::new(backend, CONTRACT_ADDR.to_vec(), 10_000_000, schedule, vm_config(1).unwrap(), None, false)
+        .execute(contract(RECURSION_WAT), &"recurse".to_string(), NO_ARGS, &mut 0);
+    assert_eq!(res.error_code, ERROR_TRAP_STACK_OVERFLOW, "{}", res.error);
+}
+
+#[test]
+fn test_module_limits_are_enforced() {
+    let execute = |code: Vec<u8>| {
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        runner(backend, 10_000_000).execute(code, &"run".to_string(), NO_ARGS, &mut 0)
+    };
+    let table = contract(&format!("(memory (export \"memory\") 1) (table {} funcref) (func (export \"run\"))", config().max_table_elements + 1));
+    let res = execute(table);
+    assert_eq!(res.error_code, ERROR_VALIDATION, "{}", res.error);
+
+    let globals = "(global i32 (i32.const 0))".repeat(config().max_globals as usize + 1);
+    let res = execute(contract(&format!("(memory (export \"memory\") 1) {} (func (export \"run\"))", globals)));
+    assert_eq!(res.error_code, ERROR_VALIDATION, "{}", res.error);
+}
+
+const GROW_WAT: &str = r#"
+  (memory (export "memory") 1)
+  (func (export "grow") (drop (memory.grow (i32.const 10))))
+"#;
+
+#[test]
+fn test_memory_grow_is_charged_per_page() {
+    let execute = |version: u32, gas_limit: u64| {
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        let schedule = gas_schedule(version).unwrap();
+        VmRunner::new(backend, CONTRACT_ADDR.to_vec(), gas_limit, schedule, config(), None, false)
+            .execute(contract(GROW_WAT), &"grow".to_string(), NO_ARGS, &mut 0)
+    };
+    let v3 = execute(3, 10_000_000);
+    assert!(v3.success, "{}", v3.error);
+    let v4 = execute(4, 10_000_000);
+    assert!(v4.success, "{}", v4.error);
+    assert_eq!(v4.gas_used - v3.gas_used, 10 * 10_000);
+
+    let res = execute(4, v3.gas_used + 50_000);
+    assert!(!res.success);
+    assert_eq!(res.error_code, ERROR_OUT_OF_GAS, "{}", res.error);
+}
+
+/// Each method hashes "abc" with the host function of the same name.
+const HASHES_WAT: &str = r#"
+  (import "env" "keccak256" (func $keccak256 (param i32) (result i32)))
+  (import "env" "sha256" (func $sha256 (param i32) (result i32)))
+  (import "env" "sha3_256" (func $sha3_256 (param i32) (result i32)))
+  (import "env" "blake2b_256" (func $blake2b_256 (param i32) (result i32)))
+  (import "env" "ripemd160" (func $ripemd160 (param i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\03\00\00\00\03\00\00\00")
+  (data (i32.const 256) "abc")
+  (func (export "keccak256") (result i32) (call $keccak256 (i32.const 0)))
+  (func (export "sha256") (result i32) (call $sha256 (i32.const 0)))
+  (func (export "sha3_256") (result i32) (call $sha3_256 (i32.const 0)))
+  (func (export "blake2b_256") (result i32) (call $blake2b_256 (i32.const 0)))
+  (func (export "ripemd160") (result i32) (call $ripemd160 (i32.const 0)))
+"#;
+
+#[test]
+fn test_native_hashes() {
+    let execute = |version: u32, method: &str| {
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        let schedule = gas_schedule(version).unwrap();
+        VmRunner::new(backend, CONTRACT_ADDR.to_vec(), 10_000_000, schedule, config(), None, false)
+            .execute(contract(HASHES_WAT), &method.to_string(), NO_ARGS, &mut 0)
+    };
+    let cases = [
+        ("keccak256", "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"),
+        ("sha256", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+        ("sha3_256", "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"),
+        ("blake2b_256", "bddd813c634239723171ef3fee98579b94964e3bb1cb3e427262c8c068d52319"),
+        ("ripemd160", "8eb208f7e05d987a9b044a8e98c6b087f15a0bfc"),
+    ];
+    for (method, hash) in cases.iter() {
+        let res = execute(5, method);
+        assert!(res.success, "{}: {}", method, res.error);
+        assert_eq!(hex::encode(res.output_data), *hash, "{}", method);
+    }
+
+    // Older schedules don't provide the native hashes.
+    let res = execute(4, "keccak256");
+    assert_eq!(res.error_code, ERROR_VALIDATION, "{}", res.error);
+}
+
+fn wat_bytes(data: &[u8]) -> String {
+    data.iter().map(|b| format!("\\{:02x}", b)).collect()
+}
+
+/// `verify` traps unless all signatures of the batch checked by `function` are valid.
+fn batch_wat(function: &str, entries: &[u8]) -> String {
+    let region = wat_bytes(&[256u32.to_le_bytes(), (entries.len() as u32).to_le_bytes(), (entries.len() as u32).to_le_bytes()].concat());
+    format!(r#"
+      (import "env" "{}" (func $verify (param i32) (result i32)))
+      (memory (export "memory") 1)
+      (data (i32.const 0) "{}")
+      (data (i32.const 256) "{}")
+      (func (export "verify")
+        (if (i32.eqz (call $verify (i32.const 0))) (then unreachable)))
+    "#, function, region, wat_bytes(entries))
+}
+
+fn execute_verify(version: u32, wat: &str) -> ActionResult {
+    let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+    let schedule = gas_schedule(version).unwrap();
+    VmRunner::new(backend, CONTRACT_ADDR.to_vec(), 10_000_000, schedule, config(), None, false)
+        .execute(contract(wat), &"verify".to_string(), NO_ARGS, &mut 0)
+}
+
+#[test]
+fn test_verify_secp256k1_batch() {
+    let first_hash = [5u8; 32];
+    let second_hash = [6u8; 32];
+    let (first, first_sig) = sign_secp256k1(1, &first_hash);
+    let (second, second_sig) = sign_secp256k1(2, &second_hash);
+    let valid = batch_wat("verify_secp256k1_batch", &[
+        first.clone(), first_hash.to_vec(), first_sig.clone(),
+        second.clone(), second_hash.to_vec(), second_sig.clone(),
+    ].concat());
+    let res = execute_verify(6, &valid);
+    assert!(res.success, "{}", res.error);
+
+    let swapped = batch_wat("verify_secp256k1_batch", &[
+        first, first_hash.to_vec(), second_sig,
+        second.clone(), second_hash.to_vec(), first_sig.clone(),
+    ].concat());
+    assert_eq!(execute_verify(6, &swapped).error_code, ERROR_TRAP_UNREACHABLE);
+
+    let truncated = batch_wat("verify_secp256k1_batch", &[second, second_hash.to_vec(), first_sig[..64].to_vec()].concat());
+    assert_eq!(execute_verify(6, &truncated).error_code, ERROR_HOST_MISUSE);
+
+    // Older schedules don't provide signature checks.
+    assert_eq!(execute_verify(5, &valid).error_code, ERROR_VALIDATION);
+}
+
+#[test]
+fn test_verify_ed25519_batch() {
+    let entry = |pubkey: &[u8], sig: &[u8], msg: &[u8]| [pubkey, sig, &(msg.len() as u32).to_le_bytes(), msg].concat();
+    let (first, first_sig) = sign_ed25519(1, b"voucher");
+    let (second, second_sig) = sign_ed25519(2, b"another voucher");
+    let valid = batch_wat("verify_ed25519_batch", &[
+        entry(&first, &first_sig, b"voucher"),
+        entry(&second, &second_sig, b"another voucher"),
+    ].concat());
+    let res = execute_verify(6, &valid);
+    assert!(res.success, "{}", res.error);
+
+    let forged = batch_wat("verify_ed25519_batch", &[
+        entry(&first, &first_sig, b"voucher"),
+        entry(&second, &second_sig, b"voucher"),
+    ].concat());
+    assert_eq!(execute_verify(6, &forged).error_code, ERROR_TRAP_UNREACHABLE);
+
+    // The length of the last message exceeds the batch.
+    let mut truncated = entry(&first, &first_sig, b"voucher");
+    truncated.pop();
+    assert_eq!(execute_verify(6, &batch_wat("verify_ed25519_batch", &truncated)).error_code, ERROR_HOST_MISUSE);
+
+    assert_eq!(execute_verify(5, &valid).error_code, ERROR_VALIDATION);
+}
+
+/// `check` traps unless it gets -5, true and a non-empty string.
+const TYPED_ARGS_WAT: &str = r#"
+  (memory (export "memory") 1)
+  (func (export "check") (param i64 i32 i32)
+    (if (i64.ne (local.get 0) (i64.const -5)) (then unreachable))
+    (if (i32.ne (local.get 1) (i32.const 1)) (then unreachable))
+    (if (i32.eqz (i32.load offset=4 (local.get 2))) (then unreachable)))
+  (func (export "check_u64") (param i64)
+    (if (i64.ne (local.get 0) (i64.const -1)) (then unreachable)))
+"#;
+
+#[test]
+fn test_typed_args() {
+    let execute = |method: &str, args: &[Argument]| {
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        runner(backend, 10_000_000).execute(contract(TYPED_ARGS_WAT), &method.to_string(), &encode_typed_args(args), &mut 0)
+    };
+    let args = [Argument::I64(-5), Argument::Bool(true), Argument::String("hi".to_string())];
+    assert_eq!(convert_args(&encode_typed_args(&args)).unwrap(), args.to_vec());
+    let res = execute("check", &args);
+    assert!(res.success, "{}", res.error);
+
+    let res = execute("check_u64", &[Argument::U64(u64::MAX)]);
+    assert!(res.success, "{}", res.error);
+
+    let res = execute("check", &[Argument::I32(-5)]);
+    assert_eq!(res.error_code, ERROR_BAD_ARGUMENTS, "{}", res.error);
+    let res = execute("check_u64", &[Argument::Bytes(vec![1])]);
+    assert_eq!(res.error_code, ERROR_BAD_ARGUMENTS, "{}", res.error);
+}
+
+/// `emit` emits `Transfer` with the data "to", `emit_and_fail` traps after emitting it.
+const EVENTS_WAT: &str = r#"
+  (import "env" "emit_event" (func $emit_event (param i32 i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\08\00\00\00\08\00\00\00")
+  (data (i32.const 12) "\10\01\00\00\02\00\00\00\02\00\00\00")
+  (data (i32.const 256) "Transfer")
+  (data (i32.const 272) "to")
+  (func (export "emit") (param i32)
+    (call $emit_event (i32.const 0) (i32.const 12)))
+  (func (export "emit_and_fail") (param i32)
+    (call $emit_event (i32.const 0) (i32.const 12))
+    unreachable)
+"#;
+
+#[test]
+fn test_events_are_buffered() {
+    let execute = |backend: &MockBackend, version: u32, method: &str, gas_limit: u64| {
+        let schedule = gas_schedule(version).unwrap();
+        VmRunner::new(backend.clone(), CONTRACT_ADDR.to_vec(), gas_limit, schedule, config(), None, false)
+            .execute(contract(EVENTS_WAT), &method.to_string(), NO_ARGS, &mut 0)
+    };
+    let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+    let res = execute(&backend, 9, "emit_and_fail", 10_000_000);
+    assert_eq!(res.error_code, ERROR_TRAP_UNREACHABLE);
+    assert!(res.events.is_empty());
+    backend.with_state(|state| assert!(state.events.is_empty()));
+
+    let res = execute(&backend, 9, "emit", 10_000_000);
+    assert!(res.success, "{}", res.error);
+    let event = Event { contract: CONTRACT_ADDR.to_vec(), topic: b"Transfer".to_vec(), data: b"to".to_vec() };
+    assert_eq!(res.events, vec![event.clone()]);
+    backend.with_state(|state| assert_eq!(state.events.len(), 1));
+
+    let proto: protoActionResult = (&res).into();
+    assert_eq!(ActionResult::from(proto).events, vec![event]);
+
+    // The event is charged when it is emitted, the call fails without reaching the backend.
+    let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+    let res = execute(&backend, 9, "emit", res.gas_used - 1);
+    assert_eq!(res.error_code, ERROR_OUT_OF_GAS, "{}", res.error);
+    backend.with_state(|state| assert!(state.events.is_empty()));
+
+    // Older schedules pass events to the backend right away.
+    let res = execute(&backend, 8, "emit_and_fail", 10_000_000);
+    assert_eq!(res.error_code, ERROR_TRAP_UNREACHABLE);
+    backend.with_state(|state| assert_eq!(state.events.len(), 1));
+}
+
+/// `fail` panics with "boom".
+const PANIC_WAT: &str = r#"
+  (import "env" "panic" (func $panic (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "\00\01\00\00\04\00\00\00\04\00\00\00")
+  (data (i32.const 256) "boom")
+  (func (export "fail")
+    (call $panic (i32.const 0)))
+"#;
+
+#[test]
+fn test_panics_are_logged() {
+    let record = LogRecord {
+        level: LogLevel::Error,
+        contract: CONTRACT_ADDR.to_vec(),
+        method: "fail".to_string(),
+        message: "wasm panicked: boom".to_string(),
+    };
+    let execute = |is_debug: bool| {
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        let schedule = gas_schedule(GAS_SCHEDULE_VERSION).unwrap();
+        let res = VmRunner::new(backend.clone(), CONTRACT_ADDR.to_vec(), 10_000_000, schedule, config(), None, is_debug)
+            .execute(contract(PANIC_WAT), &"fail".to_string(), NO_ARGS, &mut 0);
+        assert!(!res.success);
+        (res.logs, backend.with_state(|state| state.logs.clone()))
+    };
+    // The result carries the log in debug mode, the backend gets it otherwise.
+    let (logs, backend_logs) = execute(true);
+    assert!(logs.contains(&record), "{:?}", logs);
+    assert!(backend_logs.is_empty());
+
+    let (logs, backend_logs) = execute(false);
+    assert!(logs.is_empty());
+    assert_eq!(backend_logs, vec![record]);
+}
+
+/// `paused` and `count` return the same number, the ABI tells a bool from an i32. `name` returns a
+/// pointer to the region of "hi".
+const RETURNS_WAT: &str = r#"
+  (memory (export "memory") 1)
+  (data (i32.const 16) "\1c\00\00\00\02\00\00\00\02\00\00\00hi")
+  (func (export "total_supply") (result i64) (i64.const -2))
+  (func (export "paused") (result i32) (i32.const 7))
+  (func (export "count") (result i32) (i32.const 7))
+  (func (export "name") (result i32) (i32.const 16))
+"#;
+
+#[test]
+fn test_primitive_returns() {
+    let abi = r#"{"methods": [{"name": "paused", "returns": "bool"}, {"name": "count", "returns": "i32"}]}"#;
+    let code = contract(&format!(r#"{} (@custom "idena_abi" "{}")"#, RETURNS_WAT, wat_bytes(abi.as_bytes())));
+    let execute = |version: u32, method: &str| {
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        let schedule = gas_schedule(version).unwrap();
+        let res = VmRunner::new(backend, CONTRACT_ADDR.to_vec(), 10_000_000, schedule, config(), None, false)
+            .execute(code.clone(), &method.to_string(), NO_ARGS, &mut 0);
+        assert!(res.success, "{}", res.error);
+        res.output_data
+    };
+    assert_eq!(execute(8, "total_supply"), (-2i64).to_le_bytes().to_vec());
+    assert_eq!(execute(8, "paused"), vec![1]);
+    assert_eq!(execute(8, "count"), 7i32.to_le_bytes().to_vec());
+    assert_eq!(execute(8, "name"), b"hi".to_vec());
+
+    // Older schedules read every value as a pointer.
+    assert_eq!(execute(7, "total_supply"), Vec::<u8>::new());
+    assert_eq!(execute(7, "paused"), Vec::<u8>::new());
+    assert_eq!(execute(7, "name"), b"hi".to_vec());
+}
+
+#[test]
+fn test_deploy_checks_abi() {
+    let deploy = |abi: &str| {
+        let code = contract(&format!(r#"(memory (export "memory") 1) (@custom "idena_abi" "{}")"#, wat_bytes(abi.as_bytes())));
+        let backend = MockBackend::new(MockState::default(), CONTRACT_ADDR.to_vec(), CALLER.to_vec());
+        (validate_code(&code, config(), gas_schedule(GAS_SCHEDULE_VERSION).unwrap()), runner(backend, 10_000_000).deploy(code, NO_ARGS, &mut 0))
+    };
+    let (report, res) = deploy(r#"{"methods": [{"name": "deploy"}]}"#);
+    assert!(report.is_valid(), "{:?}", report);
+    assert!(res.success, "{}", res.error);
+
+    let (report, res) = deploy(r#"{"methods": [{"name": "missing"}]}"#);
+    assert_eq!(report.violations[0].kind(), VIOLATION_INVALID_ABI);
+    assert_eq!(res.error_code, ERROR_VALIDATION, "{}", res.error);
+}
@@ -1,10 +1,12 @@
-use crate::proto::models::{Action as protoAction, ActionResult as protoActionResult, InvocationContext as protoContext, PromiseResult as protoPromiseResult};
+use crate::backend::BackendError;
+use crate::proto::models::{Action as protoAction, ActionResult as protoActionResult, InvocationContext as protoContext, PromiseResult as protoPromiseResult, StorageWrite as protoStorageWrite, Event as protoEvent, LogRecord as protoLogRecord};
 
 pub const ACTION_FUNCTION_CALL: u8 = 1;
 pub const ACTION_TRANSFER: u8 = 2;
 pub const ACTION_DEPLOY_CONTRACT: u8 = 3;
 pub const ACTION_READ_CONTRACT_DATA: u8 = 4;
 pub const ACTION_READ_IDENTITY: u8 = 5;
+pub const ACTION_BATCH: u8 = 6;
 
 pub type IDNA = Vec<u8>;
 
@@ -18,6 +20,7 @@ pub enum Action {
     FunctionCall(FunctionCallAction),
     ReadShardedData(ReadShardedDataAction),
     Transfer(TransferAction),
+    Batch(BatchAction),
 }
 
 #[derive(Clone, Debug)]
@@ -42,6 +45,14 @@ pub struct TransferAction {
     pub amount: IDNA,
 }
 
+/// Actions run in order against the receiver of the promise. Either all of them succeed or the
+/// changes of the whole batch are reverted. Only transfers, function calls and deploys can be
+/// batched, the result of the batch is the one of its last call or deploy.
+#[derive(Clone, Debug, Default)]
+pub struct BatchAction {
+    pub actions: Vec<Action>,
+}
+
 #[derive(Clone, Debug)]
 pub struct ReadContractDataAction {
     pub gas_limit: u64,
@@ -88,9 +99,61 @@ pub struct ActionResult {
     pub remaining_gas: Gas,
     pub success: bool,
     pub error: String,
+    /// One of the `ERROR_*` codes, `ERROR_NONE` on success.
+    pub error_code: u32,
     pub output_data: Vec<u8>,
     pub sub_action_results: Vec<ActionResult>,
     pub contract: Address,
+    /// Storage changes of the contract applied by the call, in the order they were made.
+    pub write_set: Vec<StorageWrite>,
+    /// Events emitted by the call in the order they were emitted, empty if the call failed.
+    pub events: Vec<Event>,
+    /// Messages logged by the execution, only collected in debug mode.
+    pub logs: Vec<LogRecord>,
+}
+
+/// A storage change, `None` value means the key is removed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StorageWrite {
+    pub key: Vec<u8>,
+    pub value: Option<Vec<u8>>,
+}
+
+/// An event emitted by a contract. The topic is the name of the event, the data are its encoded
+/// arguments.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Event {
+    pub contract: Address,
+    pub topic: Vec<u8>,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LogLevel {
+    Debug = 0,
+    Info = 1,
+    Warn = 2,
+    Error = 3,
+}
+
+impl From<u32> for LogLevel {
+    fn from(level: u32) -> Self {
+        match level {
+            0 => LogLevel::Debug,
+            1 => LogLevel::Info,
+            2 => LogLevel::Warn,
+            _ => LogLevel::Error,
+        }
+    }
+}
+
+/// A message logged during an execution, with the contract and the method it comes from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogRecord {
+    pub level: LogLevel,
+    pub contract: Address,
+    pub method: String,
+    pub message: String,
 }
 
 impl Into<protoActionResult> for &ActionResult {
@@ -101,11 +164,21 @@ impl Into<protoActionResult> for &ActionResult {
         proto.output_data = self.output_data.clone();
         proto.success = self.success;
         proto.error = self.error.clone();
+        proto.error_code = self.error_code;
         proto.remaining_gas = self.remaining_gas;
         proto.contract = self.contract.clone();
         for sub_res in self.sub_action_results.iter() {
             proto.sub_action_results.push(sub_res.into());
         }
+        for write in self.write_set.iter() {
+            proto.write_set.push(write.into());
+        }
+        for event in self.events.iter() {
+            proto.events.push(event.into());
+        }
+        for record in self.logs.iter() {
+            proto.logs.push(record.into());
+        }
 
         proto
     }
@@ -120,8 +193,75 @@ impl From<protoActionResult> for ActionResult {
             gas_used: action_res.gas_used,
             sub_action_results: action_res.sub_action_results.into_iter().map(|a| a.into()).collect(),
             error: action_res.error,
+            error_code: action_res.error_code,
             output_data: action_res.output_data,
             contract: action_res.contract,
+            write_set: action_res.write_set.into_iter().map(|w| w.into()).collect(),
+            events: action_res.events.into_iter().map(|e| e.into()).collect(),
+            logs: action_res.logs.into_iter().map(|r| r.into()).collect(),
+        }
+    }
+}
+
+impl Into<protoStorageWrite> for &StorageWrite {
+    fn into(self) -> protoStorageWrite {
+        let mut proto = protoStorageWrite::default();
+        proto.key = self.key.clone();
+        match &self.value {
+            Some(value) => proto.value = value.clone(),
+            None => proto.removed = true,
+        }
+        proto
+    }
+}
+
+impl From<protoStorageWrite> for StorageWrite {
+    fn from(write: protoStorageWrite) -> Self {
+        StorageWrite {
+            key: write.key,
+            value: if write.removed { None } else { Some(write.value) },
+        }
+    }
+}
+
+impl Into<protoEvent> for &Event {
+    fn into(self) -> protoEvent {
+        let mut proto = protoEvent::default();
+        proto.contract = self.contract.clone();
+        proto.topic = self.topic.clone();
+        proto.data = self.data.clone();
+        proto
+    }
+}
+
+impl From<protoEvent> for Event {
+    fn from(event: protoEvent) -> Self {
+        Event {
+            contract: event.contract,
+            topic: event.topic,
+            data: event.data,
+        }
+    }
+}
+
+impl Into<protoLogRecord> for &LogRecord {
+    fn into(self) -> protoLogRecord {
+        let mut proto = protoLogRecord::default();
+        proto.level = self.level as u32;
+        proto.contract = self.contract.clone();
+        proto.method = self.method.clone();
+        proto.message = self.message.clone();
+        proto
+    }
+}
+
+impl From<protoLogRecord> for LogRecord {
+    fn from(record: protoLogRecord) -> Self {
+        LogRecord {
+            level: record.level.into(),
+            contract: record.contract,
+            method: record.method,
+            message: record.message,
         }
     }
 }
@@ -173,6 +313,12 @@ impl Into<protoAction> for &Action {
                 }
                 proto
             }
+            Action::Batch(batch) => {
+                let mut proto = protoAction::default();
+                proto.action_type = ACTION_BATCH as u32;
+                proto.actions = batch.actions.iter().map(|a| a.into()).collect();
+                proto
+            }
             _ => protoAction::default()
         }
     }
@@ -211,6 +357,9 @@ impl From<protoAction> for Action {
                 addr: action.key,
                 gas_limit: action.gas_limit,
             })),
+            ACTION_BATCH => Action::Batch(BatchAction {
+                actions: action.actions.into_iter().map(|a| a.into()).collect(),
+            }),
             _ => Action::None
         }
     }
@@ -242,11 +391,15 @@ pub struct Promise {
     pub receiver_id: Address,
     pub action: Action,
     pub action_callback: Option<Action>,
+    /// Indices of the promises joined by `promise_and`. A joint promise has no action of its own,
+    /// its callback gets the results of all of them.
+    pub joined: Vec<usize>,
 }
 #[derive(Clone)]
 pub struct InvocationContext {
     pub is_callback: bool,
-    pub promise_result: Option<PromiseResult>,
+    /// Results of the promises the callback waits for, a single one unless they were joined.
+    pub promise_results: Vec<PromiseResult>,
 }
 
 impl From<protoPromiseResult> for PromiseResult {
@@ -282,9 +435,15 @@ impl Into<protoPromiseResult> for &PromiseResult {
 
 impl From<protoContext> for InvocationContext {
     fn from(ctx: protoContext) -> Self {
+        // Contexts with a single result only set `promise_result`, plain calls set neither.
+        let promise_results = if ctx.promise_results.is_empty() {
+            ctx.promise_result.into_option().into_iter().map(|r| r.into()).collect()
+        } else {
+            ctx.promise_results.into_iter().map(|r| r.into()).collect()
+        };
         InvocationContext {
             is_callback: ctx.is_callback,
-            promise_result: Some(ctx.promise_result.unwrap_or_default().into()),
+            promise_results,
         }
     }
 }
@@ -293,9 +452,13 @@ impl Into<protoContext> for InvocationContext {
     fn into(self) -> protoContext {
         let mut ctx = protoContext::default();
         ctx.is_callback = self.is_callback;
-        match self.promise_result {
-            Some(v) => ctx.set_promise_result((&v).into()),
-            _ => {}
+        match self.promise_results.as_slice() {
+            [] => {}
+            [v] => ctx.set_promise_result(v.into()),
+            results => {
+                ctx.set_promise_result((&results[0]).into());
+                ctx.promise_results = results.iter().map(|r| r.into()).collect();
+            }
         }
         ctx
     }
@@ -305,16 +468,26 @@ impl Default for InvocationContext {
     fn default() -> Self {
         InvocationContext {
             is_callback: false,
-            promise_result: None,
+            promise_results: vec![],
         }
     }
 }
 
+/// Converts big-endian amount bytes, as used by the node, into a number.
+pub fn idna_to_u128(amount: &[u8]) -> Result<u128, BackendError> {
+    let start = amount.iter().position(|b| *b != 0).unwrap_or(amount.len());
+    let significant = &amount[start..];
+    if significant.len() > 16 {
+        return Err(BackendError::new("amount is too big"));
+    }
+    let mut bytes = [0u8; 16];
+    bytes[16 - significant.len()..].copy_from_slice(significant);
+    Ok(u128::from_be_bytes(bytes))
+}
 
-
-
-
-
-
-
-
+/// Converts a number into big-endian amount bytes without leading zeros.
+pub fn u128_to_idna(amount: u128) -> IDNA {
+    let bytes = amount.to_be_bytes();
+    let start = bytes.iter().position(|b| *b != 0).unwrap_or(bytes.len());
+    bytes[start..].to_vec()
+}
@@ -0,0 +1,471 @@
+use std::fmt;
+use std::mem::discriminant;
+
+use wasmer::wasmparser::{
+    ExternalKind, FuncType, ImportSectionEntryType, Parser, Payload, Result as ParserResult,
+    Type as WasmType, TypeDef, Validator,
+};
+use wasmer::Type;
+
+use crate::abi::read_abi;
+use crate::config::VmConfig;
+use crate::costs::GasSchedule;
+use crate::gatekeeper::Gatekeeper;
+use crate::imports::{host_functions, MAX_CODE_SIZE};
+use crate::proto::models::{ValidationReport as protoValidationReport, ValidationReport_Violation};
+use crate::stack_limiter::LIMIT_EXCEEDED_EXPORT;
+
+pub const VIOLATION_CODE_TOO_LARGE: u32 = 1;
+pub const VIOLATION_INVALID_MODULE: u32 = 2;
+pub const VIOLATION_FORBIDDEN_OPERATOR: u32 = 3;
+pub const VIOLATION_UNKNOWN_IMPORT: u32 = 4;
+pub const VIOLATION_IMPORT_SIGNATURE: u32 = 5;
+pub const VIOLATION_MISSING_EXPORT: u32 = 6;
+pub const VIOLATION_INVALID_EXPORT: u32 = 7;
+pub const VIOLATION_MEMORY_COUNT: u32 = 8;
+pub const VIOLATION_MEMORY_TOO_LARGE: u32 = 9;
+pub const VIOLATION_TABLE_TOO_LARGE: u32 = 10;
+pub const VIOLATION_TOO_MANY_GLOBALS: u32 = 11;
+pub const VIOLATION_TOO_MANY_FUNCTIONS: u32 = 12;
+pub const VIOLATION_INVALID_ABI: u32 = 13;
+
+/// Exports every contract must provide.
+const REQUIRED_EXPORTS: &[(&str, ExternalKind)] = &[
+    ("allocate", ExternalKind::Function),
+    ("deploy", ExternalKind::Function),
+    ("memory", ExternalKind::Memory),
+];
+
+/// A reason for the code to be rejected before it is compiled or instantiated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    CodeTooLarge { size: usize, limit: usize },
+    /// The code is not a valid wasm module, no other checks are done in this case.
+    InvalidModule(String),
+    /// Only the first forbidden operator of a function is reported.
+    ForbiddenOperator { function: u32, reason: String },
+    /// Anything but the host functions of the `env` module provided under the gas schedule.
+    UnknownImport { module: String, name: String },
+    ImportSignatureMismatch { name: String },
+    MissingExport(&'static str),
+    InvalidExport { name: &'static str, reason: &'static str },
+    /// Contracts must define exactly one memory.
+    MemoryCount(usize),
+    MemoryTooLarge { pages: u64, limit: u32 },
+    TableTooLarge { elements: u32, limit: u32 },
+    /// Imported globals are counted as well.
+    TooManyGlobals { count: usize, limit: u32 },
+    /// Imported functions are counted as well.
+    TooManyFunctions { count: usize, limit: u32 },
+    /// The `idena_abi` section is malformed or doesn't match the exports.
+    InvalidAbi(String),
+}
+
+impl Violation {
+    pub fn kind(&self) -> u32 {
+        match self {
+            Violation::CodeTooLarge { .. } => VIOLATION_CODE_TOO_LARGE,
+            Violation::InvalidModule(_) => VIOLATION_INVALID_MODULE,
+            Violation::ForbiddenOperator { .. } => VIOLATION_FORBIDDEN_OPERATOR,
+            Violation::UnknownImport { .. } => VIOLATION_UNKNOWN_IMPORT,
+            Violation::ImportSignatureMismatch { .. } => VIOLATION_IMPORT_SIGNATURE,
+            Violation::MissingExport(_) => VIOLATION_MISSING_EXPORT,
+            Violation::InvalidExport { .. } => VIOLATION_INVALID_EXPORT,
+            Violation::MemoryCount(_) => VIOLATION_MEMORY_COUNT,
+            Violation::MemoryTooLarge { .. } => VIOLATION_MEMORY_TOO_LARGE,
+            Violation::TableTooLarge { .. } => VIOLATION_TABLE_TOO_LARGE,
+            Violation::TooManyGlobals { .. } => VIOLATION_TOO_MANY_GLOBALS,
+            Violation::TooManyFunctions { .. } => VIOLATION_TOO_MANY_FUNCTIONS,
+            Violation::InvalidAbi(_) => VIOLATION_INVALID_ABI,
+        }
+    }
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::CodeTooLarge { size, limit } => write!(f, "code size {} exceeds the limit of {} bytes", size, limit),
+            Violation::InvalidModule(err) => write!(f, "invalid module: {}", err),
+            Violation::ForbiddenOperator { function, reason } => write!(f, "function {}: {}", function, reason),
+            Violation::UnknownImport { module, name } => write!(f, "unknown import {}.{}", module, name),
+            Violation::ImportSignatureMismatch { name } => write!(f, "import {} has incompatible signature", name),
+            Violation::MissingExport(name) => write!(f, "missing export {}", name),
+            Violation::InvalidExport { name, reason } => write!(f, "export {} {}", name, reason),
+            Violation::MemoryCount(count) => write!(f, "expected exactly one memory, found {}", count),
+            Violation::MemoryTooLarge { pages, limit } => write!(f, "memory of {} pages exceeds the limit of {} pages", pages, limit),
+            Violation::TableTooLarge { elements, limit } => write!(f, "table of {} elements exceeds the limit of {} elements", elements, limit),
+            Violation::TooManyGlobals { count, limit } => write!(f, "{} globals exceed the limit of {}", count, limit),
+            Violation::TooManyFunctions { count, limit } => write!(f, "{} functions exceed the limit of {}", count, limit),
+            Violation::InvalidAbi(err) => write!(f, "invalid abi: {}", err),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+    pub violations: Vec<Violation>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+impl Into<protoValidationReport> for &ValidationReport {
+    fn into(self) -> protoValidationReport {
+        let mut report = protoValidationReport::new();
+        for violation in self.violations.iter() {
+            let mut v = ValidationReport_Violation::new();
+            v.set_kind(violation.kind());
+            v.set_message(violation.to_string());
+            report.mut_violations().push(v);
+        }
+        report
+    }
+}
+
+/// Checks the code the way it is checked on deploy without compiling it, so that invalid
+/// contracts can be rejected cheaply. Every violation found is reported. The size limit is only
+/// enforced on deploys by contracts, which can't pass larger code: the host has to check it for
+/// deploy transactions.
+pub fn validate_code(code: &[u8], config: &VmConfig, gas_schedule: &GasSchedule) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    if code.len() > MAX_CODE_SIZE {
+        report.violations.push(Violation::CodeTooLarge { size: code.len(), limit: MAX_CODE_SIZE });
+    }
+    if let Err(err) = Validator::new().validate_all(code) {
+        report.violations.push(Violation::InvalidModule(err.to_string()));
+        return report;
+    }
+    if let Err(err) = check_module(code, gas_schedule, &mut report.violations) {
+        report.violations.push(Violation::InvalidModule(err.to_string()));
+    }
+    report.violations.extend(check_limits(code, config));
+    if let Err(err) = read_abi(code) {
+        report.violations.push(Violation::InvalidAbi(err));
+    }
+    report
+}
+
+/// Returns the violations of the limits of the config. Code which can't be parsed has none, it is
+/// rejected anyway. Configs with a stack limit also reserve the export of the stack limiter.
+pub fn check_limits(code: &[u8], config: &VmConfig) -> Vec<Violation> {
+    let mut violations = vec![];
+    let mut globals = 0;
+    let mut functions = 0;
+    let res: ParserResult<()> = Parser::new(0).parse_all(code).try_for_each(|payload| {
+        match payload? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    match import?.ty {
+                        ImportSectionEntryType::Function(_) => functions += 1,
+                        ImportSectionEntryType::Global(_) => globals += 1,
+                        ImportSectionEntryType::Memory(memory) => check_memory(memory.initial, config, &mut violations),
+                        ImportSectionEntryType::Table(table) => check_table(table.initial, config, &mut violations),
+                        _ => {}
+                    }
+                }
+            }
+            Payload::FunctionSection(reader) => functions += reader.get_count() as usize,
+            Payload::GlobalSection(reader) => globals += reader.get_count() as usize,
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    check_memory(memory?.initial, config, &mut violations);
+                }
+            }
+            Payload::TableSection(reader) => {
+                for table in reader {
+                    check_table(table?.initial, config, &mut violations);
+                }
+            }
+            Payload::ExportSection(reader) if config.has_stack_limit() => {
+                for export in reader {
+                    if export?.field == LIMIT_EXCEEDED_EXPORT {
+                        violations.push(Violation::InvalidExport { name: LIMIT_EXCEEDED_EXPORT, reason: "is reserved" });
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    });
+    if res.is_err() {
+        return vec![];
+    }
+    if globals > config.max_globals as usize {
+        violations.push(Violation::TooManyGlobals { count: globals, limit: config.max_globals });
+    }
+    if functions > config.max_functions as usize {
+        violations.push(Violation::TooManyFunctions { count: functions, limit: config.max_functions });
+    }
+    violations
+}
+
+fn check_memory(pages: u64, config: &VmConfig, violations: &mut Vec<Violation>) {
+    if pages > config.max_memory_pages as u64 {
+        violations.push(Violation::MemoryTooLarge { pages, limit: config.max_memory_pages });
+    }
+}
+
+fn check_table(elements: u32, config: &VmConfig, violations: &mut Vec<Violation>) {
+    if elements > config.max_table_elements {
+        violations.push(Violation::TableTooLarge { elements, limit: config.max_table_elements });
+    }
+}
+
+fn check_module(code: &[u8], gas_schedule: &GasSchedule, violations: &mut Vec<Violation>) -> ParserResult<()> {
+    let gatekeeper = Gatekeeper::default().config();
+    let mut types: Vec<Option<FuncType>> = vec![];
+    // Type indices of imported and defined functions.
+    let mut functions: Vec<u32> = vec![];
+    let mut imported_functions = 0;
+    let mut memories = 0;
+    let mut exports = vec![];
+    let mut body_index = 0;
+
+    for payload in Parser::new(0).parse_all(code) {
+        match payload? {
+            Payload::TypeSection(reader) => {
+                for ty in reader {
+                    types.push(match ty? {
+                        TypeDef::Func(func) => Some(func),
+                        _ => None,
+                    });
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import?;
+                    let name = import.field.unwrap_or_default();
+                    if let ImportSectionEntryType::Function(ty) = import.ty {
+                        functions.push(ty);
+                        imported_functions += 1;
+                    }
+                    let host_function = host_functions(gas_schedule).find(|(host_name, ..)| *host_name == name);
+                    match (import.module, import.ty, host_function) {
+                        ("env", ImportSectionEntryType::Function(ty), Some((_, params, results, _))) => {
+                            let matches = types.get(ty as usize)
+                                .and_then(|t| t.as_ref())
+                                .map_or(false, |func| same_types(&func.params, params) && same_types(&func.returns, results));
+                            if !matches {
+                                violations.push(Violation::ImportSignatureMismatch { name: name.to_string() });
+                            }
+                        }
+                        _ => violations.push(Violation::UnknownImport {
+                            module: import.module.to_string(),
+                            name: name.to_string(),
+                        }),
+                    }
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                for ty in reader {
+                    functions.push(ty?);
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    memory?;
+                    memories += 1;
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export?;
+                    exports.push((export.field.to_string(), export.kind, export.index));
+                }
+            }
+            Payload::CodeSectionEntry(body) => {
+                let function = imported_functions + body_index;
+                body_index += 1;
+                let mut reader = body.get_operators_reader()?;
+                while !reader.eof() {
+                    if let Err(reason) = gatekeeper.check_operator(&reader.read()?) {
+                        violations.push(Violation::ForbiddenOperator { function, reason });
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if memories != 1 {
+        violations.push(Violation::MemoryCount(memories));
+    }
+    for (name, kind) in REQUIRED_EXPORTS.iter() {
+        match exports.iter().find(|(field, _, _)| field == name) {
+            None => violations.push(Violation::MissingExport(name)),
+            Some((_, export_kind, _)) if discriminant(export_kind) != discriminant(kind) => {
+                violations.push(Violation::InvalidExport { name, reason: "has wrong kind" })
+            }
+            Some((_, _, index)) if *name == "allocate" => {
+                // The host passes the size of a region and expects a pointer to it.
+                let ok = functions.get(*index as usize)
+                    .and_then(|ty| types.get(*ty as usize))
+                    .and_then(|t| t.as_ref())
+                    .map_or(false, |func| *func.params == [WasmType::I32] && *func.returns == [WasmType::I32]);
+                if !ok {
+                    violations.push(Violation::InvalidExport { name, reason: "must take and return i32" });
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn same_types(actual: &[WasmType], expected: &[Type]) -> bool {
+    actual.len() == expected.len() && actual.iter().zip(expected).all(|pair| matches!(pair,
+        (WasmType::I32, Type::I32) | (WasmType::I64, Type::I64) |
+        (WasmType::F32, Type::F32) | (WasmType::F64, Type::F64) |
+        (WasmType::V128, Type::V128) | (WasmType::FuncRef, Type::FuncRef) |
+        (WasmType::ExternRef, Type::ExternRef)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::config::{vm_config, VM_CONFIG_VERSION};
+    use crate::costs::{gas_schedule, GAS_SCHEDULE_VERSION};
+
+    use super::*;
+
+    fn config() -> &'static VmConfig {
+        vm_config(VM_CONFIG_VERSION).unwrap()
+    }
+
+    fn schedule() -> &'static GasSchedule {
+        gas_schedule(GAS_SCHEDULE_VERSION).unwrap()
+    }
+
+    fn validate(wat: &str) -> Vec<Violation> {
+        validate_code(&wat::parse_str(wat).unwrap(), config(), schedule()).violations
+    }
+
+    const VALID: &str = r#"(module
+      (import "env" "set_storage" (func (param i32 i32)))
+      (memory (export "memory") 1)
+      (func (export "allocate") (param i32) (result i32) (local.get 0))
+      (func (export "deploy")))"#;
+
+    #[test]
+    fn test_valid_contract() {
+        assert_eq!(validate(VALID), vec![]);
+    }
+
+    #[test]
+    fn test_all_violations_are_reported() {
+        let violations = validate(r#"(module
+          (import "env" "set_storage" (func (param i32)))
+          (import "env" "debug" (func (param i32)))
+          (import "wasi" "fd_write" (func (param i32)))
+          (memory 200)
+          (func (export "allocate") (result i32) (i32.const 0))
+          (func (export "deploy") (drop (f32.add (f32.const 1) (f32.const 2)))))"#);
+        assert_eq!(violations, vec![
+            Violation::ImportSignatureMismatch { name: "set_storage".to_string() },
+            Violation::UnknownImport { module: "env".to_string(), name: "debug".to_string() },
+            Violation::UnknownImport { module: "wasi".to_string(), name: "fd_write".to_string() },
+            Violation::ForbiddenOperator { function: 4, reason: "Float operator detected: F32Const { value: Ieee32(1065353216) }. The use of floats is not supported.".to_string() },
+            Violation::InvalidExport { name: "allocate", reason: "must take and return i32" },
+            Violation::MissingExport("memory"),
+            Violation::MemoryTooLarge { pages: 200, limit: config().max_memory_pages },
+        ]);
+    }
+
+    #[test]
+    fn test_imports_depend_on_gas_schedule() {
+        let code = wat::parse_str(r#"(module
+          (import "env" "sha256" (func (param i32) (result i32)))
+          (memory (export "memory") 1)
+          (func (export "allocate") (param i32) (result i32) (local.get 0))
+          (func (export "deploy")))"#).unwrap();
+        assert_eq!(validate_code(&code, config(), schedule()).violations, vec![]);
+        assert_eq!(validate_code(&code, config(), gas_schedule(1).unwrap()).violations, vec![
+            Violation::UnknownImport { module: "env".to_string(), name: "sha256".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn test_memory_count() {
+        assert_eq!(validate(r#"(module (func (export "allocate") (param i32) (result i32) (local.get 0)) (func (export "deploy")) (export "memory" (func 0)))"#), vec![
+            Violation::MemoryCount(0),
+            Violation::InvalidExport { name: "memory", reason: "has wrong kind" },
+        ]);
+    }
+
+    #[test]
+    fn test_limits() {
+        let config = VmConfig {
+            max_memory_pages: 2,
+            max_table_elements: 10,
+            max_globals: 1,
+            max_functions: 3,
+            ..*config()
+        };
+        let code = wat::parse_str(r#"(module
+          (import "env" "set_storage" (func (param i32 i32)))
+          (memory (export "memory") 3)
+          (table 11 funcref)
+          (global i32 (i32.const 0))
+          (global i32 (i32.const 0))
+          (func (export "allocate") (param i32) (result i32) (local.get 0))
+          (func (export "deploy"))
+          (func))"#).unwrap();
+        assert_eq!(validate_code(&code, &config, schedule()).violations, vec![
+            Violation::TableTooLarge { elements: 11, limit: 10 },
+            Violation::MemoryTooLarge { pages: 3, limit: 2 },
+            Violation::TooManyGlobals { count: 2, limit: 1 },
+            Violation::TooManyFunctions { count: 4, limit: 3 },
+        ]);
+        assert_eq!(validate_code(&code, &VmConfig { max_functions: 4, ..config }, schedule()).violations.len(), 3);
+    }
+
+    #[test]
+    fn test_reserved_export() {
+        let code = wat::parse_str(r#"(module
+          (memory (export "memory") 1)
+          (global (export "idena_stack_limit_exceeded") i32 (i32.const 0))
+          (func (export "allocate") (param i32) (result i32) (local.get 0))
+          (func (export "deploy")))"#).unwrap();
+        assert_eq!(validate_code(&code, config(), schedule()).violations, vec![
+            Violation::InvalidExport { name: LIMIT_EXCEEDED_EXPORT, reason: "is reserved" },
+        ]);
+        assert_eq!(validate_code(&code, vm_config(1).unwrap(), schedule()).violations, vec![]);
+    }
+
+    #[test]
+    fn test_invalid_module() {
+        let report = validate_code(b"\0asm\x01\0\0\0\x01", config(), schedule());
+        assert_eq!(report.violations.len(), 1);
+        assert_eq!(report.violations[0].kind(), VIOLATION_INVALID_MODULE);
+    }
+
+    #[test]
+    fn test_code_too_large() {
+        let mut code = wat::parse_str(VALID).unwrap();
+        // A custom section keeps the module valid.
+        let padding = MAX_CODE_SIZE;
+        code.push(0);
+        code.extend_from_slice(&leb128(padding as u32 + 1));
+        code.push(0);
+        code.resize(code.len() + padding, 0);
+        assert_eq!(validate_code(&code, config(), schedule()).violations, vec![
+            Violation::CodeTooLarge { size: code.len(), limit: MAX_CODE_SIZE },
+        ]);
+    }
+
+    fn leb128(mut value: u32) -> Vec<u8> {
+        let mut out = vec![];
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                out.push(byte);
+                return out;
+            }
+            out.push(byte | 0x80);
+        }
+    }
+}